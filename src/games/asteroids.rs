@@ -0,0 +1,750 @@
+use core::fmt;
+
+use embassy_embedded_hal::shared_bus::blocking::spi::SpiDevice;
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_rp::{
+    clocks::RoscRng, gpio::Output, spi::Spi
+};
+
+use mipidsi::interface::SpiInterface;
+use mipidsi::models::ST7735s;
+
+use embedded_graphics::{
+    mono_font::{ascii::{FONT_10X20, FONT_6X10}, iso_8859_14::FONT_5X8, MonoTextStyle}, pixelcolor::Rgb565, prelude::*, primitives::{
+        Line, PrimitiveStyle, Rectangle
+    }, text::Text
+};
+use embassy_futures::select::{select, Either};
+use embassy_time::{Duration, Instant, Timer};
+
+use heapless::{
+    Vec, String
+};
+use rand::Rng;
+
+use crate::{menu::selector::Menu, INPUT_SIGNAL};
+use crate::set_current;
+
+use {defmt_rtt as _, panic_probe as _};
+use defmt::*;
+
+use rust_pico_console::{Input, MenuOption};
+use rust_pico_console::physics::{self, ONE};
+
+// the score line at the top, the field under it wraps around on every side
+const HUD: i32 = 12;
+const WIDTH: i32 = 128 * ONE;
+const HEIGHT: i32 = (160 - HUD) * ONE;
+const FRAME: u64 = 20;
+// headings go around in 32 steps, 0 points up
+const HEADINGS: u8 = 32;
+// sine of the first quarter turn, in ONE
+const SINE: [i32; 9] = [0, 50, 98, 142, 181, 213, 237, 251, 256];
+// speed added for every thrust input, the ship slows down by 1/64 of its speed every frame
+const THRUST: i32 = ONE / 6;
+const MAX_SHIP_SPEED: i32 = 3 * ONE;
+const SHIP_RADIUS: i32 = 3;
+const SHOT_SPEED: i32 = 3 * ONE;
+// frames a shot flies before it fades
+const SHOT_LIFE: u8 = 36;
+const SHOT_COOLDOWN: u8 = 8;
+// shots one ship can have in the air
+const SHOTS_PER_SHIP: usize = 4;
+const MAX_SHOTS: usize = 2 * SHOTS_PER_SHIP;
+const MAX_ROCKS: usize = 32;
+// radius, points and slowest speed of every rock size, size 0 is unused
+const ROCK_RADIUS: [i32; 4] = [0, 3, 6, 10];
+const ROCK_POINTS: [u32; 4] = [0, 100, 50, 20];
+const ROCK_SPEED: [i32; 4] = [0, ONE * 3 / 4, ONE / 2, ONE / 4];
+// how far every corner of a rock is from its middle, in eighths of its radius
+const ROCK_SHAPES: [[i32; 8]; 3] = [
+    [8, 6, 8, 7, 8, 5, 8, 7],
+    [7, 8, 5, 8, 8, 6, 7, 8],
+    [8, 8, 6, 7, 5, 8, 8, 6],
+];
+// rocks never show up closer than this to a ship, in pixels
+const SAFE_DISTANCE: i32 = 30;
+const RESPAWN_FRAMES: u16 = 90;
+// frames a new ship can't be hit, it blinks meanwhile
+const SAFE_FRAMES: u16 = 120;
+const HYPERSPACE_COOLDOWN: u8 = 100;
+const WAVE_DELAY: u8 = 60;
+const EXTRA_LIFE: u32 = 5000;
+const LIVES: [u8; 2] = [3, 5];
+const OPTIONS: usize = 2;
+const COLORS: [Rgb565; 2] = [Rgb565::CYAN, Rgb565::CSS_ORANGE];
+
+// x and y of a heading, in ONE
+fn direction(heading: u8) -> (i32, i32) {
+    let sine = |h: u8| {
+        let h = h % HEADINGS;
+        let r = (h % 8) as usize;
+        match h / 8 {
+            0 => SINE[r],
+            1 => SINE[8 - r],
+            2 => -SINE[r],
+            _ => -SINE[8 - r],
+        }
+    };
+    (sine(heading), -sine(heading + 8))
+}
+
+// shortest way from b to a on a field that wraps
+fn gap(a: i32, b: i32, size: i32) -> i32 {
+    let d = (a - b).rem_euclid(size);
+    if d > size / 2 { d - size } else { d }
+}
+
+#[derive(Clone)]
+#[derive(Copy)]
+struct Ship {
+    x: i32,
+    y: i32,
+    vx: i32,
+    vy: i32,
+    heading: u8,
+    alive: bool,
+    lives: u8,
+    score: u32,
+    cooldown: u8,
+    hyperspace: u8,
+    // frames the ship can't be hit
+    safe: u16,
+    // frames until a lost ship comes back
+    respawn: u16,
+    // frames the flame stays on after a thrust input
+    flame: u8,
+    // where it was drawn last, to wipe it
+    drawn: Option<(Point, u8, bool)>,
+}
+
+impl Ship {
+    fn new() -> Ship {
+        Ship {
+            x: 0,
+            y: 0,
+            vx: 0,
+            vy: 0,
+            heading: 0,
+            alive: false,
+            lives: 0,
+            score: 0,
+            cooldown: 0,
+            hyperspace: 0,
+            safe: 0,
+            respawn: 0,
+            flame: 0,
+            drawn: None,
+        }
+    }
+}
+
+#[derive(Clone)]
+#[derive(Copy)]
+struct Rock {
+    x: i32,
+    y: i32,
+    vx: i32,
+    vy: i32,
+    size: u8,
+    shape: u8,
+    drawn: Option<Point>,
+}
+
+#[derive(Clone)]
+#[derive(Copy)]
+struct Shot {
+    x: i32,
+    y: i32,
+    vx: i32,
+    vy: i32,
+    life: u8,
+    owner: usize,
+    drawn: Option<Point>,
+}
+
+pub struct Asteroids {
+    ships: [Ship; 2],
+    // 2 for co-op, both ships clear the same waves
+    players: usize,
+    lives: usize,
+    rocks: Vec<Rock, MAX_ROCKS>,
+    shots: Vec<Shot, MAX_SHOTS>,
+    wave: u8,
+    // frames until the next wave once the field is clear
+    next_wave: u8,
+    // rocks and shots that are gone but still on screen
+    wrecks: Vec<Rock, MAX_ROCKS>,
+    faded: Vec<Point, MAX_SHOTS>,
+}
+
+impl Asteroids {
+    pub fn new() -> Asteroids {
+        Asteroids {
+            ships: [Ship::new(); 2],
+            players: 1,
+            lives: 0,
+            rocks: Vec::new(),
+            shots: Vec::new(),
+            wave: 0,
+            next_wave: 0,
+            wrecks: Vec::new(),
+            faded: Vec::new(),
+        }
+    }
+
+    pub fn init(&mut self) {
+        for p in 0..2 {
+            let ship = &mut self.ships[p];
+            *ship = Ship::new();
+            if p < self.players {
+                ship.lives = LIVES[self.lives];
+                ship.respawn = 1;
+            }
+        }
+        self.rocks.clear();
+        self.shots.clear();
+        self.wave = 0;
+        self.next_wave = 1;
+    }
+
+    // ships start next to each other in the middle of the field
+    fn spawn_point(&self, p: usize) -> (i32, i32) {
+        let x = if self.players == 1 { WIDTH / 2 } else { WIDTH / 2 + (p as i32 * 2 - 1) * 16 * ONE };
+        (x, HEIGHT / 2)
+    }
+
+    fn near_rock(&self, x: i32, y: i32, distance: i32) -> bool {
+        self.rocks.iter().any(|rock| {
+            let dx = gap(rock.x, x, WIDTH) / ONE;
+            let dy = gap(rock.y, y, HEIGHT) / ONE;
+            let reach = distance + ROCK_RADIUS[rock.size as usize];
+            dx * dx + dy * dy < reach * reach
+        })
+    }
+
+    fn near_ship(&self, x: i32, y: i32, distance: i32) -> bool {
+        (0..self.players).any(|p| {
+            let (sx, sy) = if self.ships[p].alive { (self.ships[p].x, self.ships[p].y) } else { self.spawn_point(p) };
+            let dx = gap(sx, x, WIDTH) / ONE;
+            let dy = gap(sy, y, HEIGHT) / ONE;
+            dx * dx + dy * dy < distance * distance
+        })
+    }
+
+    fn add_rock(&mut self, x: i32, y: i32, size: u8) {
+        let mut rng = RoscRng;
+        let (dx, dy) = direction(rng.gen_range(0..HEADINGS));
+        // every wave is a bit faster
+        let speed = ROCK_SPEED[size as usize] + self.wave as i32 * ONE / 32 + rng.gen_range(0..ONE / 4);
+        let rock = Rock {
+            x,
+            y,
+            vx: dx * speed / ONE,
+            vy: dy * speed / ONE,
+            size,
+            shape: rng.gen_range(0..ROCK_SHAPES.len() as u8),
+            drawn: None,
+        };
+        match self.rocks.push(rock) {
+            Ok(_) => {}
+            Err(_) => {}
+        }
+    }
+
+    // one more large rock every wave, away from the ships
+    fn spawn_wave(&mut self) {
+        self.wave += 1;
+        info!("wave {}", self.wave);
+        let mut rng = RoscRng;
+        let count = (3 + self.wave as usize).min(8);
+        for _ in 0..count {
+            let mut x = 0;
+            let mut y = 0;
+            for _ in 0..20 {
+                x = rng.gen_range(0..WIDTH);
+                y = rng.gen_range(0..HEIGHT);
+                if !self.near_ship(x, y, SAFE_DISTANCE) {
+                    break;
+                }
+            }
+            self.add_rock(x, y, 3);
+        }
+    }
+
+    // a hit rock breaks in two smaller ones, the smallest ones are gone
+    fn break_rock(&mut self, index: usize, player: usize) {
+        let rock = self.rocks.swap_remove(index);
+        if rock.drawn.is_some() {
+            match self.wrecks.push(rock) {
+                Ok(_) => {}
+                Err(_) => {}
+            }
+        }
+        let ship = &mut self.ships[player];
+        let before = ship.score / EXTRA_LIFE;
+        ship.score += ROCK_POINTS[rock.size as usize];
+        if ship.score / EXTRA_LIFE > before {
+            ship.lives += 1;
+            // a shot still flying can bring back a ship that was out
+            if !ship.alive && ship.respawn == 0 {
+                ship.respawn = RESPAWN_FRAMES;
+            }
+        }
+        if rock.size > 1 {
+            for _ in 0..2 {
+                self.add_rock(rock.x, rock.y, rock.size - 1);
+            }
+        }
+    }
+
+    fn turn(&mut self, p: usize, step: i8) {
+        let ship = &mut self.ships[p];
+        if ship.alive {
+            ship.heading = (ship.heading as i8 + step).rem_euclid(HEADINGS as i8) as u8;
+        }
+    }
+
+    fn thrust(&mut self, p: usize) {
+        let ship = &mut self.ships[p];
+        if !ship.alive {
+            return;
+        }
+        let (dx, dy) = direction(ship.heading);
+        ship.vx += dx * THRUST / ONE;
+        ship.vy += dy * THRUST / ONE;
+        let speed = physics::speed(ship.vx, ship.vy);
+        if speed > MAX_SHIP_SPEED {
+            ship.vx = ship.vx * MAX_SHIP_SPEED / speed;
+            ship.vy = ship.vy * MAX_SHIP_SPEED / speed;
+        }
+        ship.flame = 6;
+    }
+
+    fn fire(&mut self, p: usize) {
+        let ship = self.ships[p];
+        if !ship.alive || ship.cooldown > 0 || self.shots.iter().filter(|shot| shot.owner == p).count() >= SHOTS_PER_SHIP {
+            return;
+        }
+        let (dx, dy) = direction(ship.heading);
+        let shot = Shot {
+            x: ship.x + dx * 5,
+            y: ship.y + dy * 5,
+            vx: ship.vx + dx * SHOT_SPEED / ONE,
+            vy: ship.vy + dy * SHOT_SPEED / ONE,
+            life: SHOT_LIFE,
+            owner: p,
+            drawn: None,
+        };
+        match self.shots.push(shot) {
+            Ok(_) => self.ships[p].cooldown = SHOT_COOLDOWN,
+            Err(_) => {}
+        }
+    }
+
+    // jumps somewhere random, it might not be any safer there
+    fn hyperspace(&mut self, p: usize) {
+        let mut rng = RoscRng;
+        let ship = &mut self.ships[p];
+        if !ship.alive || ship.hyperspace > 0 {
+            return;
+        }
+        ship.x = rng.gen_range(0..WIDTH);
+        ship.y = rng.gen_range(0..HEIGHT);
+        ship.vx = 0;
+        ship.vy = 0;
+        ship.hyperspace = HYPERSPACE_COOLDOWN;
+    }
+
+    fn update_frame(&mut self) -> bool {
+        for ship in self.ships.iter_mut() {
+            ship.cooldown = ship.cooldown.saturating_sub(1);
+            ship.hyperspace = ship.hyperspace.saturating_sub(1);
+            ship.flame = ship.flame.saturating_sub(1);
+            ship.safe = ship.safe.saturating_sub(1);
+            if !ship.alive {
+                continue;
+            }
+            ship.vx -= ship.vx / 64;
+            ship.vy -= ship.vy / 64;
+            ship.x = (ship.x + ship.vx).rem_euclid(WIDTH);
+            ship.y = (ship.y + ship.vy).rem_euclid(HEIGHT);
+        }
+        for rock in self.rocks.iter_mut() {
+            rock.x = (rock.x + rock.vx).rem_euclid(WIDTH);
+            rock.y = (rock.y + rock.vy).rem_euclid(HEIGHT);
+        }
+        for shot in self.shots.iter_mut() {
+            shot.x = (shot.x + shot.vx).rem_euclid(WIDTH);
+            shot.y = (shot.y + shot.vy).rem_euclid(HEIGHT);
+            shot.life -= 1;
+        }
+
+        // shots against rocks
+        for s in 0..self.shots.len() {
+            let shot = self.shots[s];
+            let hit = self.rocks.iter().position(|rock| {
+                let dx = gap(shot.x, rock.x, WIDTH) / ONE;
+                let dy = gap(shot.y, rock.y, HEIGHT) / ONE;
+                let radius = ROCK_RADIUS[rock.size as usize];
+                dx * dx + dy * dy <= radius * radius
+            });
+            if let Some(index) = hit {
+                self.shots[s].life = 0;
+                self.break_rock(index, shot.owner);
+            }
+        }
+        for shot in self.shots.iter().filter(|shot| shot.life == 0) {
+            if let Some(position) = shot.drawn {
+                match self.faded.push(position) {
+                    Ok(_) => {}
+                    Err(_) => {}
+                }
+            }
+        }
+        self.shots.retain(|shot| shot.life > 0);
+
+        // ships against rocks, the rock breaks too
+        for p in 0..self.players {
+            let ship = self.ships[p];
+            if !ship.alive || ship.safe > 0 {
+                continue;
+            }
+            let hit = self.rocks.iter().position(|rock| {
+                let dx = gap(ship.x, rock.x, WIDTH) / ONE;
+                let dy = gap(ship.y, rock.y, HEIGHT) / ONE;
+                let reach = ROCK_RADIUS[rock.size as usize] + SHIP_RADIUS;
+                dx * dx + dy * dy < reach * reach
+            });
+            if let Some(index) = hit {
+                info!("ship {} lost", p + 1);
+                self.break_rock(index, p);
+                let ship = &mut self.ships[p];
+                ship.alive = false;
+                ship.lives -= 1;
+                ship.respawn = if ship.lives > 0 { RESPAWN_FRAMES } else { 0 };
+            }
+        }
+
+        // lost ships come back once their spot is clear
+        for p in 0..self.players {
+            if self.ships[p].respawn == 0 {
+                continue;
+            }
+            let (x, y) = self.spawn_point(p);
+            if self.ships[p].respawn > 1 {
+                self.ships[p].respawn -= 1;
+            } else if !self.near_rock(x, y, SAFE_DISTANCE / 2) {
+                let ship = &mut self.ships[p];
+                ship.x = x;
+                ship.y = y;
+                ship.vx = 0;
+                ship.vy = 0;
+                ship.heading = 0;
+                ship.alive = true;
+                ship.respawn = 0;
+                ship.safe = SAFE_FRAMES;
+            }
+        }
+
+        if self.rocks.is_empty() {
+            self.next_wave = self.next_wave.saturating_sub(1);
+            if self.next_wave == 0 {
+                self.spawn_wave();
+                self.next_wave = WAVE_DELAY;
+            }
+        }
+        // the game goes on while any ship is flying or coming back
+        self.ships.iter().any(|ship| ship.alive || ship.respawn > 0)
+    }
+
+    fn ship_lines(position: Point, heading: u8, flame: bool) -> ([(Point, Point); 4], usize) {
+        let at = |h: u8, length: i32| {
+            let (dx, dy) = direction(h);
+            position + Point::new(dx * length / ONE, dy * length / ONE)
+        };
+        let nose = at(heading, 5);
+        let left = at(heading + 13, 4);
+        let right = at(heading + HEADINGS - 13, 4);
+        let lines = [(nose, left), (nose, right), (left, right), (at(heading + 16, 2), at(heading + 16, 5))];
+        (lines, if flame { 4 } else { 3 })
+    }
+
+    fn rock_lines(rock: &Rock, position: Point) -> [(Point, Point); 8] {
+        let radius = ROCK_RADIUS[rock.size as usize];
+        let shape = ROCK_SHAPES[rock.shape as usize];
+        let corner = |k: usize| {
+            let (dx, dy) = direction(k as u8 * 4);
+            let length = radius * shape[k] / 8;
+            position + Point::new(dx * length / ONE, dy * length / ONE)
+        };
+        let mut lines = [(Point::zero(), Point::zero()); 8];
+        for k in 0..8 {
+            lines[k] = (corner(k), corner((k + 1) % 8));
+        }
+        lines
+    }
+
+    fn pixel(x: i32, y: i32) -> Point {
+        Point::new(x / ONE, HUD + y / ONE)
+    }
+
+    // wipes everything where it was drawn last and draws it again, the field is clipped so nothing spills into the hud
+    fn draw(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let mut field = screen.clipped(&Rectangle::new(Point::new(0, HUD), Size::new(128, (160 - HUD) as u32)));
+        let black = PrimitiveStyle::with_stroke(Rgb565::BLACK, 1);
+        for rock in self.wrecks.iter().chain(self.rocks.iter()) {
+            if let Some(position) = rock.drawn {
+                for (a, b) in Self::rock_lines(rock, position) {
+                    Line::new(a, b).into_styled(black).draw(&mut field).unwrap();
+                }
+            }
+        }
+        for ship in self.ships.iter() {
+            if let Some((position, heading, flame)) = ship.drawn {
+                let (lines, count) = Self::ship_lines(position, heading, flame);
+                for (a, b) in &lines[..count] {
+                    Line::new(*a, *b).into_styled(black).draw(&mut field).unwrap();
+                }
+            }
+        }
+        for position in self.faded.iter().chain(self.shots.iter().filter_map(|shot| shot.drawn.as_ref())) {
+            Rectangle::new(*position, Size::new(1, 1))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(&mut field)
+                .unwrap();
+        }
+        self.wrecks.clear();
+        self.faded.clear();
+
+        for rock in self.rocks.iter_mut() {
+            let position = Self::pixel(rock.x, rock.y);
+            for (a, b) in Self::rock_lines(rock, position) {
+                Line::new(a, b).into_styled(PrimitiveStyle::with_stroke(Rgb565::CSS_LIGHT_GRAY, 1)).draw(&mut field).unwrap();
+            }
+            rock.drawn = Some(position);
+        }
+        for p in 0..2 {
+            let ship = &mut self.ships[p];
+            // a new ship blinks while it can't be hit
+            if !ship.alive || (ship.safe / 8) % 2 == 1 {
+                ship.drawn = None;
+                continue;
+            }
+            let position = Self::pixel(ship.x, ship.y);
+            let (lines, count) = Self::ship_lines(position, ship.heading, ship.flame > 0);
+            for (k, (a, b)) in lines[..count].iter().enumerate() {
+                let color = if k == 3 { Rgb565::YELLOW } else { COLORS[p] };
+                Line::new(*a, *b).into_styled(PrimitiveStyle::with_stroke(color, 1)).draw(&mut field).unwrap();
+            }
+            ship.drawn = Some((position, ship.heading, ship.flame > 0));
+        }
+        for shot in self.shots.iter_mut() {
+            let position = Self::pixel(shot.x, shot.y);
+            Rectangle::new(position, Size::new(1, 1))
+                .into_styled(PrimitiveStyle::with_fill(COLORS[shot.owner]))
+                .draw(&mut field)
+                .unwrap();
+            shot.drawn = Some(position);
+        }
+    }
+
+    fn draw_hud(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, HUD as u32))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<20> = String::new();
+        for p in 0..self.players {
+            temp.clear();
+            fmt::write(&mut temp, format_args!("{} x{}", self.ships[p].score, self.ships[p].lives)).unwrap();
+            let x = if p == 0 { 2 } else { 126 - temp.len() as i32 * 5 };
+            Text::new(&temp, Point::new(x, 8), MonoTextStyle::new(&FONT_5X8, COLORS[p]))
+                .draw(screen).unwrap();
+        }
+        temp.clear();
+        fmt::write(&mut temp, format_args!("W{}", self.wave)).unwrap();
+        Text::new(&temp, Point::new(58, 8), MonoTextStyle::new(&FONT_5X8, Rgb565::CSS_GRAY))
+            .draw(screen).unwrap();
+    }
+
+    fn redraw(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        for rock in self.rocks.iter_mut() {
+            rock.drawn = None;
+        }
+        for ship in self.ships.iter_mut() {
+            ship.drawn = None;
+        }
+        for shot in self.shots.iter_mut() {
+            shot.drawn = None;
+        }
+        self.wrecks.clear();
+        self.faded.clear();
+        self.draw_hud(screen);
+        self.draw(screen);
+    }
+
+    // left and right turn, up thrusts, down jumps through hyperspace, left+shoot and right+shoot fire
+    fn handle_input(&mut self, input: &Input) -> bool {
+        let second = self.players == 2;
+        match input {
+            Input::Back => return false,
+            Input::Left => self.turn(0, -1),
+            Input::Right => self.turn(0, 1),
+            Input::Up => self.thrust(0),
+            Input::Down => self.hyperspace(0),
+            Input::Left_Shoot | Input::Right_Shoot => self.fire(0),
+            Input::Left2 if second => self.turn(1, -1),
+            Input::Right2 if second => self.turn(1, 1),
+            Input::Up2 if second => self.thrust(1),
+            Input::Down2 if second => self.hyperspace(1),
+            Input::Left2_Shoot | Input::Right2_Shoot if second => self.fire(1),
+            _ => {}
+        }
+        true
+    }
+
+    // pre-game settings, up and down pick a row, left and right change it, false when the players leave
+    async fn options(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let mut selected = 0;
+        self.draw_options(selected, screen);
+        loop {
+            Timer::after(Duration::from_millis(100)).await;
+            INPUT_SIGNAL.reset();
+            match INPUT_SIGNAL.wait().await {
+                Input::Up | Input::Up2 => {
+                    selected = if selected > 0 { selected - 1 } else { OPTIONS - 1 };
+                }
+                Input::Down | Input::Down2 => {
+                    selected = if selected + 1 < OPTIONS { selected + 1 } else { 0 };
+                }
+                Input::Left | Input::Left2 | Input::Right | Input::Right2 => {
+                    match selected {
+                        0 => self.players = 3 - self.players,
+                        _ => self.lives = 1 - self.lives,
+                    }
+                }
+                Input::Select => return true,
+                Input::Back => return false,
+                _ => continue,
+            }
+            self.draw_options(selected, screen);
+        }
+    }
+
+    fn draw_options(&self, selected: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        Text::new("Asteroids", Point::new(19, 30), MonoTextStyle::new(&FONT_10X20, Rgb565::RED))
+            .draw(screen).unwrap();
+        for row in 0..OPTIONS {
+            let mut temp: String<24> = String::new();
+            match row {
+                0 => fmt::write(&mut temp, format_args!("Players: < {} >", if self.players == 2 { "Co-op" } else { "1" })).unwrap(),
+                _ => fmt::write(&mut temp, format_args!("Lives: < {} >", LIVES[self.lives])).unwrap(),
+            }
+            Text::new(&temp, Point::new(4, 55 + row as i32 * 15), MonoTextStyle::new(&FONT_6X10, if row == selected { Rgb565::WHITE } else { Rgb565::CSS_GRAY }))
+                .draw(screen).unwrap();
+        }
+        Text::new("e: play q: exit", Point::new(19, 150), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
+            .draw(screen).unwrap();
+    }
+
+    fn draw_results(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 120), Size::new(128, 40))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<24> = String::new();
+        for p in 0..self.players {
+            temp.clear();
+            fmt::write(&mut temp, format_args!("P{}: {}", p + 1, self.ships[p].score)).unwrap();
+            Text::new(&temp, Point::new(10, 132 + p as i32 * 11), MonoTextStyle::new(&FONT_6X10, COLORS[p]))
+                .draw(screen).unwrap();
+        }
+        temp.clear();
+        fmt::write(&mut temp, format_args!("Wave {}", self.wave)).unwrap();
+        Text::new(&temp, Point::new(10, 154), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_GRAY))
+            .draw(screen).unwrap();
+    }
+
+    pub async fn game_loop(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if self.options(screen).await == false {
+            set_current(0);
+            return;
+        }
+        self.init();
+        self.redraw(screen);
+        // frames keep their pace however fast the inputs come in
+        let mut next_frame = Instant::now();
+        loop {
+            match select(INPUT_SIGNAL.wait(), Timer::at(next_frame)).await {
+                Either::First(input) => {
+                    if self.handle_input(&input) == false {
+                        // create pause menu
+                        let mut pause_menu: Menu<'_> = Menu::init("Pause menu", &[MenuOption::Resume, MenuOption::Restart, MenuOption::Exit], screen);
+                        let result: MenuOption = pause_menu.menu_loop(screen).await;
+                        match result {
+                            MenuOption::Resume | MenuOption::None => {}
+                            MenuOption::Restart => self.init(),
+                            MenuOption::Exit => {
+                                set_current(0);
+                                return;
+                            }
+                            _ => {}
+                        }
+                        self.redraw(screen);
+                        Timer::after(Duration::from_millis(100)).await;
+                        INPUT_SIGNAL.reset();
+                        next_frame = Instant::now();
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            // a slow frame doesn't make the next ones rush to catch up
+            next_frame = (next_frame + Duration::from_millis(FRAME)).max(Instant::now());
+
+            let hud = (self.ships[0].score, self.ships[0].lives, self.ships[1].score, self.ships[1].lives, self.wave);
+            let running = self.update_frame();
+            self.draw(screen);
+            if hud != (self.ships[0].score, self.ships[0].lives, self.ships[1].score, self.ships[1].lives, self.wave) {
+                self.draw_hud(screen);
+            }
+            if running {
+                continue;
+            }
+
+            info!("game over!");
+            let mut end_menu: Menu<'_> = Menu::init("Game over", &[MenuOption::Restart, MenuOption::Options, MenuOption::Exit], screen);
+            self.draw_results(screen);
+            let result: MenuOption = end_menu.menu_loop(screen).await;
+            match result {
+                MenuOption::Restart | MenuOption::None => {}
+                MenuOption::Options => {
+                    if self.options(screen).await == false {
+                        set_current(0);
+                        return;
+                    }
+                }
+                MenuOption::Exit => {
+                    set_current(0);
+                    return;
+                }
+                _ => {}
+            }
+            self.init();
+            self.redraw(screen);
+            Timer::after(Duration::from_millis(100)).await;
+            INPUT_SIGNAL.reset();
+            next_frame = Instant::now();
+        }
+    }
+}
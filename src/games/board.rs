@@ -0,0 +1,560 @@
+use core::fmt;
+
+use embassy_embedded_hal::shared_bus::blocking::spi::SpiDevice;
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_rp::{
+    gpio::Output, spi::Spi
+};
+
+use mipidsi::interface::SpiInterface;
+use mipidsi::models::ST7735s;
+
+use embedded_graphics::{
+    mono_font::{ascii::{FONT_10X20, FONT_6X10}, MonoTextStyle}, pixelcolor::Rgb565, prelude::*, primitives::{
+        Line, PrimitiveStyle, Rectangle
+    }, text::Text
+};
+use embassy_time::{Duration, Timer};
+
+use heapless::{
+    Vec, String
+};
+
+use crate::{menu::selector::Menu, INPUT_SIGNAL};
+use crate::set_current;
+
+use {defmt_rtt as _, panic_probe as _};
+use defmt::*;
+
+use rust_pico_console::{Input, MenuOption};
+
+// the biggest board any game uses, connect four
+pub const MAX_COLS: usize = 7;
+pub const MAX_ROWS: usize = 6;
+const MAX_MOVES: usize = MAX_COLS * MAX_ROWS;
+const HUD: i32 = 20;
+// a line on the board beats any position, quicker wins score higher
+const WIN: i32 = 100_000;
+const OPTIONS: usize = 2;
+const LEVELS: [&str; 3] = ["Easy", "Normal", "Hard"];
+// the four ways a line can go
+const DIRECTIONS: [(i8, i8); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+// what makes one board game different from the others, the board takes care of the rest
+pub struct Rules {
+    pub title: &'static str,
+    pub cols: u8,
+    pub rows: u8,
+    // pieces in a row that win
+    pub line: u8,
+    // pieces fall to the lowest free cell of a column, the cursor only picks columns
+    pub gravity: bool,
+    // how many moves ahead the cpu looks on every level
+    pub depths: [u8; 3],
+    pub colors: [Rgb565; 2],
+    // fills a cell before its piece is drawn
+    pub background: Rgb565,
+    // the empty board, it gets the area and the size of a cell
+    pub draw_board: fn(Rectangle, i32, &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>),
+    // one piece, player 0 is an empty cell
+    pub draw_piece: fn(Point, i32, u8, &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>),
+}
+
+pub struct Board {
+    rules: &'static Rules,
+    // 0 is empty, 1 and 2 are the players
+    cells: [[u8; MAX_COLS]; MAX_ROWS],
+    filled: u8,
+    cursor: (u8, u8),
+    turn: u8,
+    // whoever opened the current game, the other player opens the next one
+    first: u8,
+    cpu: bool,
+    level: usize,
+    wins: [u8; 2],
+    draws: u8,
+}
+
+impl Board {
+    pub fn new(rules: &'static Rules) -> Board {
+        Board {
+            rules,
+            cells: [[0; MAX_COLS]; MAX_ROWS],
+            filled: 0,
+            cursor: (0, 0),
+            turn: 1,
+            first: 1,
+            cpu: true,
+            level: 1,
+            wins: [0; 2],
+            draws: 0,
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.cells = [[0; MAX_COLS]; MAX_ROWS];
+        self.filled = 0;
+        self.cursor = (self.rules.cols / 2, if self.rules.gravity { 0 } else { self.rules.rows / 2 });
+        self.turn = self.first;
+    }
+
+    fn at(&self, cell: (i8, i8)) -> Option<u8> {
+        if cell.0 < 0 || cell.1 < 0 || cell.0 >= self.rules.cols as i8 || cell.1 >= self.rules.rows as i8 {
+            return None;
+        }
+        Some(self.cells[cell.1 as usize][cell.0 as usize])
+    }
+
+    // the cell a piece ends up in when played under the cursor, None when the move isn't allowed
+    fn target(&self, cursor: (u8, u8)) -> Option<(u8, u8)> {
+        if self.rules.gravity {
+            (0..self.rules.rows).rev().find(|y| self.cells[*y as usize][cursor.0 as usize] == 0).map(|y| (cursor.0, y))
+        } else if self.cells[cursor.1 as usize][cursor.0 as usize] == 0 {
+            Some(cursor)
+        } else {
+            None
+        }
+    }
+
+    // every legal move, the middle of the board first so the search cuts off sooner
+    fn moves(&self) -> Vec<(u8, u8), MAX_MOVES> {
+        let mut moves: Vec<(u8, u8), MAX_MOVES> = Vec::new();
+        for y in 0..if self.rules.gravity { 1 } else { self.rules.rows } {
+            for x in 0..self.rules.cols {
+                if let Some(cell) = self.target((x, y)) {
+                    match moves.push(cell) {
+                        Ok(_) => {}
+                        Err(_) => {}
+                    }
+                }
+            }
+        }
+        let (cols, rows) = (self.rules.cols as i8 - 1, self.rules.rows as i8 - 1);
+        moves.sort_unstable_by_key(|cell| (2 * cell.0 as i8 - cols).abs() + if self.rules.gravity { 0 } else { (2 * cell.1 as i8 - rows).abs() });
+        moves
+    }
+
+    // the winning line through a cell, its first cell and its direction
+    fn line_through(&self, cell: (u8, u8)) -> Option<((u8, u8), (i8, i8))> {
+        let player = self.cells[cell.1 as usize][cell.0 as usize];
+        if player == 0 {
+            return None;
+        }
+        for (dx, dy) in DIRECTIONS {
+            let mut start = (cell.0 as i8, cell.1 as i8);
+            while self.at((start.0 - dx, start.1 - dy)) == Some(player) {
+                start = (start.0 - dx, start.1 - dy);
+            }
+            let mut length = 0;
+            while self.at((start.0 + length * dx, start.1 + length * dy)) == Some(player) {
+                length += 1;
+            }
+            if length >= self.rules.line as i8 {
+                return Some(((start.0 as u8, start.1 as u8), (dx, dy)));
+            }
+        }
+        None
+    }
+
+    // every stretch of the board long enough for a line, worth more the more pieces of one player it holds
+    fn evaluate(&self, player: u8) -> i32 {
+        let line = self.rules.line as i8;
+        let mut score = 0;
+        for y in 0..self.rules.rows as i8 {
+            for x in 0..self.rules.cols as i8 {
+                for (dx, dy) in DIRECTIONS {
+                    if self.at((x + (line - 1) * dx, y + (line - 1) * dy)).is_none() {
+                        continue;
+                    }
+                    let mut counts = [0u32; 3];
+                    for k in 0..line {
+                        counts[self.cells[(y + k * dy) as usize][(x + k * dx) as usize] as usize] += 1;
+                    }
+                    let (mine, theirs) = (counts[player as usize], counts[3 - player as usize]);
+                    if theirs == 0 {
+                        score += ((1 << (2 * mine)) >> 2) as i32;
+                    } else if mine == 0 {
+                        score -= ((1 << (2 * theirs)) >> 2) as i32;
+                    }
+                }
+            }
+        }
+        score
+    }
+
+    // negamax with alpha-beta, the score of the best move for the player to move
+    fn search(&mut self, depth: u8, mut alpha: i32, beta: i32, player: u8) -> i32 {
+        let moves = self.moves();
+        if moves.is_empty() {
+            return 0;
+        }
+        let mut best = -WIN * 2;
+        for cell in moves {
+            self.cells[cell.1 as usize][cell.0 as usize] = player;
+            let score = if self.line_through(cell).is_some() {
+                WIN + depth as i32
+            } else if depth <= 1 {
+                self.evaluate(player)
+            } else {
+                -self.search(depth - 1, -beta, -alpha, 3 - player)
+            };
+            self.cells[cell.1 as usize][cell.0 as usize] = 0;
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+
+    fn best_move(&mut self, player: u8) -> Option<(u8, u8)> {
+        let depth = self.rules.depths[self.level];
+        let mut alpha = -WIN * 2;
+        let mut best = None;
+        for cell in self.moves() {
+            self.cells[cell.1 as usize][cell.0 as usize] = player;
+            let score = if self.line_through(cell).is_some() {
+                WIN + depth as i32
+            } else if depth <= 1 {
+                self.evaluate(player)
+            } else {
+                -self.search(depth - 1, -WIN * 2, -alpha, 3 - player)
+            };
+            self.cells[cell.1 as usize][cell.0 as usize] = 0;
+            if best.is_none() || score > alpha {
+                alpha = score;
+                best = Some(cell);
+            }
+        }
+        best
+    }
+
+    // puts the player's piece down, Some(winner) when the game is over, 0 for a draw
+    fn play(&mut self, cell: (u8, u8), screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> Option<u8> {
+        self.cells[cell.1 as usize][cell.0 as usize] = self.turn;
+        self.filled += 1;
+        self.draw_cell(cell, screen);
+        if let Some((start, (dx, dy))) = self.line_through(cell) {
+            // a line through the middle of the winning cells
+            let size = self.cell_size();
+            let center = |x: i32, y: i32| self.origin() + Point::new(x * size + size / 2, y * size + size / 2);
+            let length = self.rules.line as i32 - 1;
+            Line::new(center(start.0 as i32, start.1 as i32), center(start.0 as i32 + length * dx as i32, start.1 as i32 + length * dy as i32))
+                .into_styled(PrimitiveStyle::with_stroke(Rgb565::WHITE, 2))
+                .draw(screen)
+                .unwrap();
+            return Some(self.turn);
+        }
+        if self.filled == self.rules.cols * self.rules.rows {
+            return Some(0);
+        }
+        self.turn = 3 - self.turn;
+        self.draw_hud(screen);
+        self.draw_cursor(screen);
+        None
+    }
+
+    fn move_cursor(&mut self, dx: i8, dy: i8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let x = (self.cursor.0 as i8 + dx).clamp(0, self.rules.cols as i8 - 1) as u8;
+        let y = if self.rules.gravity { 0 } else { (self.cursor.1 as i8 + dy).clamp(0, self.rules.rows as i8 - 1) as u8 };
+        self.clear_cursor(screen);
+        self.cursor = (x, y);
+        self.draw_cursor(screen);
+    }
+
+    fn cpu_turn(&self) -> bool {
+        self.cpu && self.turn == 2
+    }
+
+    fn name(&self, player: u8) -> &'static str {
+        match player {
+            1 => "P1",
+            _ if self.cpu => "CPU",
+            _ => "P2",
+        }
+    }
+
+    fn cell_size(&self) -> i32 {
+        // games with gravity keep a row above the board for the piece about to drop
+        let rows = self.rules.rows as i32 + if self.rules.gravity { 1 } else { 0 };
+        ((128 - 2) / self.rules.cols as i32).min((160 - HUD - 2) / rows)
+    }
+
+    fn origin(&self) -> Point {
+        let size = self.cell_size();
+        let top = if self.rules.gravity { size } else { 0 };
+        let height = 160 - HUD - top;
+        Point::new((128 - self.rules.cols as i32 * size) / 2, HUD + top + (height - self.rules.rows as i32 * size) / 2)
+    }
+
+    fn draw_cell(&self, cell: (u8, u8), screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let size = self.cell_size();
+        let corner = self.origin() + Point::new(cell.0 as i32 * size, cell.1 as i32 * size);
+        Rectangle::new(corner + Point::new(1, 1), Size::new(size as u32 - 1, size as u32 - 1))
+            .into_styled(PrimitiveStyle::with_fill(self.rules.background))
+            .draw(screen)
+            .unwrap();
+        (self.rules.draw_piece)(corner, size, self.cells[cell.1 as usize][cell.0 as usize], screen);
+    }
+
+    fn clear_cursor(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if self.rules.gravity {
+            let size = self.cell_size();
+            Rectangle::new(Point::new(0, self.origin().y - size), Size::new(128, size as u32))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(screen)
+                .unwrap();
+        } else {
+            self.draw_cell(self.cursor, screen);
+        }
+    }
+
+    // the piece about to drop above its column, or a frame around the cell
+    fn draw_cursor(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let size = self.cell_size();
+        if self.rules.gravity {
+            self.clear_cursor(screen);
+            if !self.cpu_turn() {
+                (self.rules.draw_piece)(self.origin() + Point::new(self.cursor.0 as i32 * size, -size), size, self.turn, screen);
+            }
+        } else if !self.cpu_turn() {
+            let corner = self.origin() + Point::new(self.cursor.0 as i32 * size, self.cursor.1 as i32 * size);
+            Rectangle::new(corner + Point::new(1, 1), Size::new(size as u32 - 1, size as u32 - 1))
+                .into_styled(PrimitiveStyle::with_stroke(self.rules.colors[self.turn as usize - 1], 1))
+                .draw(screen)
+                .unwrap();
+        }
+    }
+
+    // games won by both sides and draws, the player to move is underlined
+    fn draw_hud(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, HUD as u32 - 2))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<20> = String::new();
+        for player in 1..=2u8 {
+            temp.clear();
+            fmt::write(&mut temp, format_args!("{} {}", self.name(player), self.wins[player as usize - 1])).unwrap();
+            let x = 2 + (player as i32 - 1) * 44;
+            Text::new(&temp, Point::new(x, 10), MonoTextStyle::new(&FONT_6X10, self.rules.colors[player as usize - 1]))
+                .draw(screen).unwrap();
+            if player == self.turn {
+                Rectangle::new(Point::new(x, 13), Size::new(temp.len() as u32 * 6, 2))
+                    .into_styled(PrimitiveStyle::with_fill(self.rules.colors[player as usize - 1]))
+                    .draw(screen)
+                    .unwrap();
+            }
+        }
+        temp.clear();
+        fmt::write(&mut temp, format_args!("={}", self.draws)).unwrap();
+        Text::new(&temp, Point::new(98, 10), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_GRAY))
+            .draw(screen).unwrap();
+    }
+
+    fn redraw(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        self.draw_hud(screen);
+        let size = self.cell_size();
+        (self.rules.draw_board)(Rectangle::new(self.origin(), Size::new((self.rules.cols as i32 * size) as u32 + 1, (self.rules.rows as i32 * size) as u32 + 1)), size, screen);
+        for y in 0..self.rules.rows {
+            for x in 0..self.rules.cols {
+                self.draw_cell((x, y), screen);
+            }
+        }
+        self.draw_cursor(screen);
+    }
+
+    // the player to move uses their own controller, left+shoot or right+shoot plays under the cursor
+    fn handle_input(&mut self, input: &Input, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> (bool, Option<u8>) {
+        let first = self.turn == 1;
+        match input {
+            Input::Back => return (false, None),
+            Input::Up if first => self.move_cursor(0, -1, screen),
+            Input::Down if first => self.move_cursor(0, 1, screen),
+            Input::Left if first => self.move_cursor(-1, 0, screen),
+            Input::Right if first => self.move_cursor(1, 0, screen),
+            Input::Up2 if !first => self.move_cursor(0, -1, screen),
+            Input::Down2 if !first => self.move_cursor(0, 1, screen),
+            Input::Left2 if !first => self.move_cursor(-1, 0, screen),
+            Input::Right2 if !first => self.move_cursor(1, 0, screen),
+            Input::Left_Shoot | Input::Right_Shoot if first => {
+                if let Some(cell) = self.target(self.cursor) {
+                    return (true, self.play(cell, screen));
+                }
+            }
+            Input::Left2_Shoot | Input::Right2_Shoot if !first => {
+                if let Some(cell) = self.target(self.cursor) {
+                    return (true, self.play(cell, screen));
+                }
+            }
+            _ => {}
+        }
+        (true, None)
+    }
+
+    // pre-game settings, up and down pick a row, left and right change it, false when the players leave
+    async fn options(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let mut selected = 0;
+        self.draw_options(selected, screen);
+        loop {
+            Timer::after(Duration::from_millis(100)).await;
+            INPUT_SIGNAL.reset();
+            match INPUT_SIGNAL.wait().await {
+                Input::Up | Input::Up2 => {
+                    selected = if selected > 0 { selected - 1 } else { OPTIONS - 1 };
+                }
+                Input::Down | Input::Down2 => {
+                    selected = if selected + 1 < OPTIONS { selected + 1 } else { 0 };
+                }
+                input @ (Input::Left | Input::Left2 | Input::Right | Input::Right2) => {
+                    let forward = input == Input::Right || input == Input::Right2;
+                    match selected {
+                        0 => self.cpu = !self.cpu,
+                        _ => {
+                            let count = LEVELS.len();
+                            self.level = if forward { (self.level + 1) % count } else { (self.level + count - 1) % count };
+                        }
+                    }
+                }
+                Input::Select => {
+                    // a new opponent starts a new tally
+                    self.wins = [0; 2];
+                    self.draws = 0;
+                    self.first = 1;
+                    return true;
+                }
+                Input::Back => return false,
+                _ => continue,
+            }
+            self.draw_options(selected, screen);
+        }
+    }
+
+    fn draw_options(&self, selected: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        Text::new(self.rules.title, Point::new(64 - self.rules.title.len() as i32 * 5, 30), MonoTextStyle::new(&FONT_10X20, Rgb565::RED))
+            .draw(screen).unwrap();
+        for row in 0..OPTIONS {
+            let mut temp: String<24> = String::new();
+            match row {
+                0 => fmt::write(&mut temp, format_args!("P2: < {} >", if self.cpu { "CPU" } else { "Human" })).unwrap(),
+                _ => fmt::write(&mut temp, format_args!("Level: < {} >", LEVELS[self.level])).unwrap(),
+            }
+            // the level only matters against the cpu
+            let color = if row == selected { Rgb565::WHITE } else if row == 1 && !self.cpu { Rgb565::CSS_DIM_GRAY } else { Rgb565::CSS_GRAY };
+            Text::new(&temp, Point::new(4, 55 + row as i32 * 15), MonoTextStyle::new(&FONT_6X10, color))
+                .draw(screen).unwrap();
+        }
+        Text::new("e: play q: exit", Point::new(19, 150), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
+            .draw(screen).unwrap();
+    }
+
+    fn draw_results(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 120), Size::new(128, 40))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<24> = String::new();
+        for player in 1..=2u8 {
+            temp.clear();
+            fmt::write(&mut temp, format_args!("{}: {} wins", self.name(player), self.wins[player as usize - 1])).unwrap();
+            Text::new(&temp, Point::new(10, 121 + player as i32 * 11), MonoTextStyle::new(&FONT_6X10, self.rules.colors[player as usize - 1]))
+                .draw(screen).unwrap();
+        }
+        temp.clear();
+        fmt::write(&mut temp, format_args!("Draws: {}", self.draws)).unwrap();
+        Text::new(&temp, Point::new(10, 154), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_GRAY))
+            .draw(screen).unwrap();
+    }
+
+    pub async fn game_loop(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if self.options(screen).await == false {
+            set_current(0);
+            return;
+        }
+        self.init();
+        self.redraw(screen);
+        loop {
+            let result = if self.cpu_turn() {
+                // a short wait so the cpu's move doesn't land together with the player's
+                Timer::after(Duration::from_millis(300)).await;
+                match self.best_move(2) {
+                    Some(cell) => self.play(cell, screen),
+                    None => Some(0),
+                }
+            } else {
+                let input = INPUT_SIGNAL.wait().await;
+                let (running, result) = self.handle_input(&input, screen);
+                if running == false {
+                    // create pause menu
+                    let mut pause_menu: Menu<'_> = Menu::init("Pause menu", &[MenuOption::Resume, MenuOption::Restart, MenuOption::Exit], screen);
+                    let choice: MenuOption = pause_menu.menu_loop(screen).await;
+                    match choice {
+                        MenuOption::Resume | MenuOption::None => {}
+                        MenuOption::Restart => self.init(),
+                        MenuOption::Exit => {
+                            set_current(0);
+                            return;
+                        }
+                        _ => {}
+                    }
+                    self.redraw(screen);
+                    Timer::after(Duration::from_millis(100)).await;
+                    INPUT_SIGNAL.reset();
+                }
+                result
+            };
+
+            let winner = match result {
+                None => continue,
+                Some(winner) => winner,
+            };
+            info!("game over!");
+            let title = match winner {
+                0 => {
+                    self.draws += 1;
+                    "Draw!"
+                }
+                1 => {
+                    self.wins[0] += 1;
+                    "P1 wins!"
+                }
+                _ => {
+                    self.wins[1] += 1;
+                    if self.cpu { "CPU wins!" } else { "P2 wins!" }
+                }
+            };
+            Timer::after(Duration::from_millis(700)).await;
+            let mut end_menu: Menu<'_> = Menu::init(title, &[MenuOption::Restart, MenuOption::Options, MenuOption::Exit], screen);
+            self.draw_results(screen);
+            let choice: MenuOption = end_menu.menu_loop(screen).await;
+            match choice {
+                MenuOption::Restart | MenuOption::None => self.first = 3 - self.first,
+                MenuOption::Options => {
+                    if self.options(screen).await == false {
+                        set_current(0);
+                        return;
+                    }
+                }
+                MenuOption::Exit => {
+                    set_current(0);
+                    return;
+                }
+                _ => {}
+            }
+            self.init();
+            self.redraw(screen);
+            Timer::after(Duration::from_millis(100)).await;
+            INPUT_SIGNAL.reset();
+        }
+    }
+}
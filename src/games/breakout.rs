@@ -1,7 +1,5 @@
 use core::fmt;
 
-#[allow(static_mut_refs)]
-
 use embassy_embedded_hal::shared_bus::blocking::spi::SpiDevice;
 
 use embassy_sync::blocking_mutex::raw::NoopRawMutex;
@@ -29,19 +27,38 @@ use rand::seq::SliceRandom;
 use rand::*;
 
 use crate::{menu::selector::Menu, INPUT_SIGNAL};
-use crate::CURRENT;
+use crate::games::breakout_levels::LEVELS;
+use crate::set_current;
 
 use {defmt_rtt as _, panic_probe as _};
 use defmt::*;
 
 use rust_pico_console::{Input, MenuOption};
+use rust_pico_console::physics::{self, Ball, Grid, Hit, Rect, MAX_SPEED, MIN_SPEED, ONE, SPEEDUP};
 
 const BRICK_HEIGHT: i16 = 3;
 const BRICK_WIDTH: i16 = 7;
 const OFFSET_Y: u8 = 30;
-const PLAYER_WIDTH: i8 = 30;
-const MAXSPEED: f32 = 1.0;
-const MINSPEED: f32 = -1.0;
+const PLAYER_WIDTH: u8 = 30;
+const WIDE_WIDTH: u8 = 40;
+const NARROW_WIDTH: u8 = 20;
+const START_LIVES: u8 = 3;
+const MAX_LIVES: u8 = 5;
+// chance a broken brick drops a powerup
+const DROP_CHANCE: f64 = 0.3;
+const BALL_SIZE: i32 = 2 * ONE;
+// sideways speed added to or taken from the balls split off by the orange powerup
+const SPLIT_SPEED: i32 = ONE / 5;
+const BRICKS: Grid = Grid {
+    x: 0,
+    y: OFFSET_Y as i32 * ONE,
+    pitch_x: 8 * ONE,
+    pitch_y: 4 * ONE,
+    width: BRICK_WIDTH as i32 * ONE,
+    height: BRICK_HEIGHT as i32 * ONE,
+    cols: 16,
+    rows: 25,
+};
 
 #[derive(Debug)]
 #[derive(Clone)]
@@ -51,15 +68,79 @@ pub enum Block {
     None,
     Green,
     Yellow,
-    Red
+    Red,
+    // takes this many more hits to break, gets darker with every hit
+    Tough(u8),
+    // never breaks and does not count towards clearing the level
+    Steel,
+    // breaks the bricks around it, steel included
+    Explosive,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PowerKind {
+    // two more balls split off a random ball
+    Split,
+    // two more balls from the paddle that caught it
+    Triple,
+    Wide,
+    Narrow,
+    Slow,
+    // Up fires two shots that break bricks
+    Laser,
+    // balls stay on the paddle until Up is pressed
+    Sticky,
+    ExtraLife,
+    // balls go through bricks and break them in one hit, steel still bounces them
+    Fireball,
+}
+
+#[derive(Clone, Copy)]
+pub struct PowerUp {
+    x: u8,
+    y: u8,
+    kind: PowerKind,
+    active: bool,
+}
+
+// when each timed powerup runs out, None while it is off
+// paddle powerups belong to the player who caught them, slow and fireball work on every ball
+#[derive(Default)]
+struct Effects {
+    wide: [Option<Instant>; 2],
+    narrow: [Option<Instant>; 2],
+    laser: [Option<Instant>; 2],
+    sticky: [Option<Instant>; 2],
+    slow: Option<Instant>,
+    fireball: Option<Instant>,
+}
+
+// what a ball ran into during its move
+enum Contact {
+    Brick(usize, usize),
+    Wall,
+    // the player whose paddle it was
+    Paddle(usize),
 }
+
 pub struct Breakout<'a> {
     bricks: &'a mut Vec<Vec<Block,16>,36>,
     bricks_count: u16,
     walls: &'a mut Vec<bool,32>,
     lastwall: i16,
-    balls: &'a mut Vec<(f32, i16, f32, i16, bool), 50>, // posx, posy, speedx, speedy, active
-    powerups: &'a mut Vec<(u8, u8, bool, bool), 20>,
+    balls: &'a mut Vec<Ball, 50>,
+    powerups: &'a mut Vec<PowerUp, 20>,
+    // powerups a broken brick can drop on this level
+    drops: Vec<PowerKind, 16>,
+    effects: Effects,
+    widths: [u8; 2],
+    // laser shots, x, y, active and the player who fired
+    shots: Vec<(u8, u8, bool, usize), 16>,
+    // balls held by sticky paddles, the player, where on the paddle and how fast the ball was
+    caught: Vec<(usize, i32, i32), 8>,
+    // balls every player has left, serving again costs one
+    lives: [u8; 2],
+    frame: u32,
     player1_started: bool,
     player2_started: bool,
     level: u8,
@@ -69,11 +150,11 @@ pub struct Breakout<'a> {
     player2_pos: u8,
     player2_pos_prev: i8,
     wallpos: i16,
-    score: u64,
+    scores: [u64; 2],
 }
 
 impl <'a> Breakout<'a> {
-    pub fn new(bricks: &'a mut Vec<Vec<Block, 16>, 36>, walls: &'a mut Vec<bool, 32>, balls: &'a mut Vec<(f32, i16, f32, i16, bool), 50>, powerups: &'a mut Vec<(u8, u8, bool, bool), 20>) -> Breakout <'a> {
+    pub fn new(bricks: &'a mut Vec<Vec<Block, 16>, 36>, walls: &'a mut Vec<bool, 32>, balls: &'a mut Vec<Ball, 50>, powerups: &'a mut Vec<PowerUp, 20>) -> Breakout <'a> {
         Breakout { 
            bricks,
            bricks_count: 0,
@@ -81,6 +162,13 @@ impl <'a> Breakout<'a> {
            lastwall: 0,
            balls,
            powerups,
+           drops: Vec::new(),
+           effects: Effects::default(),
+           widths: [PLAYER_WIDTH; 2],
+           shots: Vec::new(),
+           caught: Vec::new(),
+           lives: [START_LIVES; 2],
+           frame: 0,
            player1_started: false,
            player2_started: false,
            level: 1,
@@ -90,507 +178,454 @@ impl <'a> Breakout<'a> {
            player2_pos: 60,
            player2_pos_prev: 0,
            wallpos: 0,
-           score: 0,
+           scores: [0; 2],
         }
     }
     pub fn init(&mut self) {
         self.balls.clear();
         self.powerups.clear();
+        self.shots.clear();
+        self.caught.clear();
+        self.effects = Effects::default();
+        self.widths = [PLAYER_WIDTH; 2];
+        self.player1_pos = self.player1_pos.min(128 - PLAYER_WIDTH);
+        self.player2_pos = self.player2_pos.min(128 - PLAYER_WIDTH);
         self.drawn_init = false;
         self.player1_started = false;
         self.player2_started = false;
-        for i in 0..16 {
-            for j in 0..25 {
-                self.bricks[j][i] = Block::None;
+        self.load(LEVELS[(self.level as usize - 1) % LEVELS.len()]);
+    }
+
+    // reads a level from the pack in breakout_levels.rs, after the last one the pack starts over
+    fn load(&mut self, level: &[&str]) {
+        for row in self.bricks.iter_mut() {
+            for brick in row.iter_mut() {
+                *brick = Block::None;
             }
         }
-        match self.level {
-            1 => {
-                for i in 0..16 {
-                    self.bricks[0][i] = Block::Red;
-                    self.bricks[1][i] = Block::Red;
-                    self.bricks[2][i] = Block::Red;
-                    self.bricks[3][i] = Block::Yellow;
-                    self.bricks[4][i] = Block::Yellow;
-                    self.bricks[5][i] = Block::Yellow;
-                    self.bricks[6][i] = Block::Green;
-                    self.bricks[7][i] = Block::Green;
-                    self.bricks[8][i] = Block::Green;
-                    self.walls[i] = false;
-                    self.walls[32 - i - 1] = false;
-                }
-                self.bricks_count = 144;
-                self.lastwall = 0;
-                self.wallpos = 100;
-            }
-            2 => {
-                for i in 0..16 {
-                    self.bricks[0][i] = Block::Red;
-                    self.bricks[1][i] = Block::Red;
-                    self.bricks[2][i] = Block::Red;
-                    self.bricks[3][i] = Block::Red;
-                    self.bricks[4][i] = Block::Yellow;
-                    self.bricks[5][i] = Block::Yellow;
-                    self.bricks[6][i] = Block::Yellow;
-                    self.bricks[7][i] = Block::Yellow;
-                    self.bricks[8][i] = Block::Green;
-                    self.bricks[9][i] = Block::Green;
-                    self.bricks[10][i] = Block::Green;
-                    self.bricks[11][i] = Block::Green;
-                    self.walls[i] = false;
-                    self.walls[32 - i - 1] = false;
-                }
-                self.bricks_count = 192;
-                for i in 0..5 {
-                    // info!(" i {}", (32 -i -1));
-                    self.walls[i] = true;
-                    self.walls[32 - i - 1] = true;
-                } 
-                self.lastwall = 4;
-                self.wallpos = 80;
-            }
-            3 => {
-                for i in 0..16 {
-                    self.bricks[0][i] = Block::Red;
-                    self.bricks[1][i] = Block::Red;
-                    self.bricks[2][i] = Block::Red;
-                    self.bricks[3][i] = Block::Red;
-                    self.bricks[4][i] = Block::Red;
-                    self.bricks[5][i] = Block::Yellow;
-                    self.bricks[6][i] = Block::Yellow;
-                    self.bricks[7][i] = Block::Yellow;
-                    self.bricks[8][i] = Block::Yellow;
-                    self.bricks[9][i] = Block::Yellow;
-                    self.bricks[10][i] = Block::Green;
-                    self.bricks[11][i] = Block::Green;
-                    self.bricks[12][i] = Block::Green;
-                    self.bricks[13][i] = Block::Green;
-                    self.bricks[14][i] = Block::Green;
-                    self.walls[i] = false;
-                    self.walls[32 - i - 1] = false;
-                }
-                self.bricks_count = 240;
-                for i in 0..8 {
-                    self.walls[i] = true;
-                    self.walls[32 - i - 1] = true;
-                } 
-                self.lastwall = 7;
-                self.wallpos = 90;
-            }
-            4 => {
-                for i in 0..16 {
-                    self.bricks[0][i] = Block::Red;
-                    self.bricks[1][i] = Block::Red;
-                    self.bricks[2][i] = Block::Red;
-                    self.bricks[3][i] = Block::Red;
-                    self.bricks[4][i] = Block::Red;
-                    self.bricks[5][i] = Block::Red;
-                    self.bricks[6][i] = Block::Red;
-                    self.bricks[7][i] = Block::Yellow;
-                    self.bricks[8][i] = Block::Yellow;
-                    self.bricks[9][i] = Block::Yellow;
-                    self.bricks[10][i] = Block::Yellow;
-                    self.bricks[11][i] = Block::Yellow;
-                    self.bricks[12][i] = Block::Yellow;
-                    self.bricks[13][i] = Block::Green;
-                    self.bricks[14][i] = Block::Green;
-                    self.bricks[15][i] = Block::Green;
-                    self.bricks[16][i] = Block::Green;
-                    self.bricks[17][i] = Block::Green;
-                    self.bricks[18][i] = Block::Green;
-                    self.bricks[19][i] = Block::Green;
-                    self.walls[i] = false;
-                    self.walls[32 - i - 1] = false;
-                }
-                self.bricks_count = 320;
-
-                for i in 0..12 {
-                    self.walls[i] = true;
-                    self.walls[32 - i - 1] = true;
-                } 
-                self.lastwall = 11;
-                self.wallpos = 105;
-            }
-            5 => {
-                for i in 0..16 {
-                    // info!("i {}", i);
-                    self.bricks[0][i] = Block::Red;
-                    self.bricks[1][i] = Block::Red;
-                    self.bricks[2][i] = Block::Red;
-                    self.bricks[3][i] = Block::Red;
-                    self.bricks[4][i] = Block::Red;
-                    self.bricks[5][i] = Block::Red;
-                    self.bricks[6][i] = Block::Red;
-                    self.bricks[7][i] = Block::Red;
-                    self.bricks[8][i] = Block::Yellow;
-                    self.bricks[9][i] = Block::Yellow;
-                    self.bricks[10][i] = Block::Yellow;
-                    self.bricks[11][i] = Block::Yellow;
-                    self.bricks[12][i] = Block::Yellow;
-                    self.bricks[13][i] = Block::Yellow;
-                    self.bricks[14][i] = Block::Yellow;
-                    self.bricks[15][i] = Block::Yellow;
-                    self.bricks[16][i] = Block::Yellow;
-                    self.bricks[17][i] = Block::Green;
-                    self.bricks[18][i] = Block::Green;
-                    self.bricks[19][i] = Block::Green;
-                    self.bricks[20][i] = Block::Green;
-                    self.bricks[21][i] = Block::Green;
-                    self.bricks[22][i] = Block::Green;
-                    self.bricks[23][i] = Block::Green;
-                    self.bricks[24][i] = Block::Green;
-                    self.walls[i] = false;
-                    self.walls[32 - i - 1] = false;
-                }
-                self.bricks_count = 400;
-                for i in 0..15 {
-                    self.walls[i] = true;
-                    self.walls[32 - i - 1] = true;
-                } 
-                self.lastwall = 14;
-                self.wallpos = 130;
-            }
-            _ => {
-                for i in 0..16 {
-                    // info!("i {}", i);
-                    self.bricks[0][i] = Block::Red;
-                    self.bricks[1][i] = Block::Red;
-                    self.bricks[2][i] = Block::Red;
-                    self.bricks[3][i] = Block::Red;
-                    self.bricks[4][i] = Block::Red;
-                    self.bricks[5][i] = Block::Red;
-                    self.bricks[6][i] = Block::Red;
-                    self.bricks[7][i] = Block::Red;
-                    self.bricks[8][i] = Block::Yellow;
-                    self.bricks[9][i] = Block::Yellow;
-                    self.bricks[10][i] = Block::Yellow;
-                    self.bricks[11][i] = Block::Yellow;
-                    self.bricks[12][i] = Block::Yellow;
-                    self.bricks[13][i] = Block::Yellow;
-                    self.bricks[14][i] = Block::Yellow;
-                    self.bricks[15][i] = Block::Yellow;
-                    self.bricks[16][i] = Block::Yellow;
-                    self.bricks[17][i] = Block::Green;
-                    self.bricks[18][i] = Block::Green;
-                    self.bricks[19][i] = Block::Green;
-                    self.bricks[20][i] = Block::Green;
-                    self.bricks[21][i] = Block::Green;
-                    self.bricks[22][i] = Block::Green;
-                    self.bricks[23][i] = Block::Green;
-                    self.bricks[24][i] = Block::Green;
-                    self.walls[i] = false;
-                    self.walls[32 - i - 1] = false;
-                }
-                self.bricks_count = 400;
-                for i in 0..15 {
-                    self.walls[i] = true;
-                    self.walls[32 - i - 1] = true;
-                } 
-                self.lastwall = 14;
-                self.wallpos = 130;
+        let mut header = level[0].split_whitespace();
+        let wall = header.next().and_then(|value| value.parse::<i16>().ok()).unwrap_or(0).clamp(0, 16);
+        self.wallpos = header.next().and_then(|value| value.parse::<i16>().ok()).unwrap_or(100);
+        self.drops.clear();
+        for drop in header.next().unwrap_or("MT").chars() {
+            let kind = match drop {
+                'M' => PowerKind::Split,
+                'T' => PowerKind::Triple,
+                'W' => PowerKind::Wide,
+                'N' => PowerKind::Narrow,
+                'S' => PowerKind::Slow,
+                'L' => PowerKind::Laser,
+                'C' => PowerKind::Sticky,
+                'E' => PowerKind::ExtraLife,
+                'F' => PowerKind::Fireball,
+                _ => continue,
+            };
+            match self.drops.push(kind) {
+                Ok(_) => {}
+                Err(_) => {}
+            }
+        }
+        for i in 0..32 {
+            self.walls[i] = (i as i16) < wall || (i as i16) >= 32 - wall;
+        }
+        self.lastwall = (wall - 1).max(0);
+        self.bricks_count = 0;
+        for (i, line) in level[1..].iter().take(25).enumerate() {
+            for (j, brick) in line.chars().take(16).enumerate() {
+                self.bricks[i][j] = match brick {
+                    'G' => Block::Green,
+                    'Y' => Block::Yellow,
+                    'R' => Block::Red,
+                    '2' => Block::Tough(2),
+                    '3' => Block::Tough(3),
+                    'S' => Block::Steel,
+                    'X' => Block::Explosive,
+                    _ => Block::None,
+                };
+                if self.bricks[i][j] != Block::None && self.bricks[i][j] != Block::Steel {
+                    self.bricks_count += 1;
+                }
             }
         }
     }
 
-    async fn update_frame(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
-        // info!("left {}", self.bricks_count);
-        let mut rng = RoscRng;
-        for powerup in self.powerups.iter_mut() {
-            Rectangle::new(Point::new(powerup.0 as i32,  powerup.1 as i32), Size::new(4, 4))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                .draw(screen)
-                .unwrap(); 
-            powerup.1 += 1;
-            if powerup.1 == 147 {
-                if powerup.0 >= self.player1_pos && powerup.0 <= self.player1_pos + 19 ||
-                powerup.0 + 3 >= self.player1_pos && powerup.0 + 3 <= self.player1_pos + 19 {
-                    powerup.3 = false;
-                    if powerup.2 == true {
-                        match self.balls.choose(&mut rng) {
-                            Some(&t) => {
-                                match self.balls.push((t.0, t.1, if t.2 - 0.2 >= MINSPEED { t.2 - 0.2 } else { MINSPEED }, t.3, true)) {
-                                    Ok(_) => {}
-                                    Err(_) => {}
-                                }
-                                match self.balls.push((t.0, t.1, if t.2 + 0.2 <= MAXSPEED { t.2 + 0.2 } else { MAXSPEED }, t.3, true)) {
-                                    Ok(_) => {}
-                                    Err(_) => {}
+    fn hit_brick(&mut self, row: usize, col: usize, player: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        match self.bricks[row][col] {
+            Block::None | Block::Steel => {}
+            Block::Tough(hits) if hits > 1 => {
+                self.bricks[row][col] = Block::Tough(hits - 1);
+                self.scores[player] += 50 * self.level as u64;
+                draw_scores(&self.scores, screen);
+                draw_brick(self.bricks[row][col], row, col, screen);
+            }
+            Block::Explosive => {
+                // explosions spread to the neighbouring explosive bricks
+                let mut blasts: Vec<(usize, usize), 32> = Vec::new();
+                self.break_brick(row, col, player, screen);
+                match blasts.push((row, col)) {
+                    Ok(_) => {}
+                    Err(_) => {}
+                }
+                while let Some((row, col)) = blasts.pop() {
+                    for i in row.saturating_sub(1)..=(row + 1).min(24) {
+                        for j in col.saturating_sub(1)..=(col + 1).min(15) {
+                            match self.bricks[i][j] {
+                                Block::None => {}
+                                Block::Explosive => {
+                                    self.break_brick(i, j, player, screen);
+                                    match blasts.push((i, j)) {
+                                        Ok(_) => {}
+                                        Err(_) => {}
+                                    }
                                 }
+                                _ => self.break_brick(i, j, player, screen),
                             }
-                            None => {}
-                        }
-                    } else {
-                        match self.balls.push((self.player1_pos as f32 + 10.0, 148, -0.2, -1, true)) {
-                            Ok(_) => {}
-                            Err(_) => {}
-                        }
-                        match self.balls.push((self.player1_pos as f32 + 10.0, 148, 0.2, -1, true)) {
-                            Ok(_) => {}
-                            Err(_) => {}
                         }
                     }
+                }
+            }
+            _ => self.break_brick(row, col, player, screen),
+        }
+    }
 
-                } else if powerup.0 >= self.player2_pos && powerup.0 <= self.player2_pos + 19 ||
-                powerup.0 + 3 >= self.player2_pos && powerup.0 + 3 <= self.player2_pos + 19 {
-                    powerup.3 = false;
-                    if powerup.2 == true {
-                        match self.balls.choose(&mut rng) {
-                            Some(&t) => {
-                                match self.balls.push((t.0, t.1, if t.2 - 0.2 >= MINSPEED { t.2 - 0.2 } else { MINSPEED }, t.3, true)) {
-                                    Ok(_) => {}
-                                    Err(_) => {}
-                                }
-                                match self.balls.push((t.0, t.1, if t.2 + 0.2 <= MAXSPEED { t.2 - 0.2 } else { MAXSPEED }, t.3, true)) {
-                                    Ok(_) => {}
-                                    Err(_) => {}
-                                }
-                            }
-                            None => {}
-                        }
-                    } else {
-                        match self.balls.push((self.player2_pos as f32 + 10.0, 148, -0.2, -1, true)) {
+    fn break_brick(&mut self, row: usize, col: usize, player: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let mut rng = RoscRng;
+        match self.bricks[row][col] {
+            Block::Green => self.scores[player] += 100 * self.level as u64,
+            Block::Yellow => self.scores[player] += 200 * self.level as u64,
+            Block::Red | Block::Explosive => self.scores[player] += 300 * self.level as u64,
+            Block::Tough(_) => self.scores[player] += 400 * self.level as u64,
+            _ => {}
+        }
+        if self.bricks[row][col] != Block::Steel {
+            self.bricks_count -= 1;
+        }
+        self.bricks[row][col] = Block::None;
+        draw_scores(&self.scores, screen);
+        if rng.gen_bool(DROP_CHANCE) {
+            if let Some(&kind) = self.drops.choose(&mut rng) {
+                match self.powerups.push(PowerUp { x: col as u8 * 8, y: row as u8 * 4 + OFFSET_Y, kind, active: true }) {
+                    Ok(_) => {}
+                    Err(_) => {}
+                }
+            }
+        }
+        draw_brick(Block::None, row, col, screen);
+    }
+
+    // starts a powerup for the player who caught it
+    fn collect(&mut self, kind: PowerKind, player: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let mut rng = RoscRng;
+        let now = Instant::now();
+        let pos = if player == 0 { self.player1_pos } else { self.player2_pos };
+        match kind {
+            PowerKind::Split => {
+                match self.balls.choose(&mut rng) {
+                    Some(&t) => {
+                        match self.balls.push(Ball { vx: (t.vx - SPLIT_SPEED).max(-MAX_SPEED), ..t }) {
                             Ok(_) => {}
                             Err(_) => {}
                         }
-                        match self.balls.push((self.player2_pos as f32 + 10.0, 148, 0.2, -1, true)) {
+                        match self.balls.push(Ball { vx: (t.vx + SPLIT_SPEED).min(MAX_SPEED), ..t }) {
                             Ok(_) => {}
                             Err(_) => {}
                         }
                     }
+                    None => {}
                 }
             }
-            if powerup.1 > 160 {
-                powerup.3 = false;
+            PowerKind::Triple => {
+                match self.balls.push(Ball::new((pos as i32 + 10) * ONE, 148 * ONE, -SPLIT_SPEED, -MIN_SPEED, player)) {
+                    Ok(_) => {}
+                    Err(_) => {}
+                }
+                match self.balls.push(Ball::new((pos as i32 + 10) * ONE, 148 * ONE, SPLIT_SPEED, -MIN_SPEED, player)) {
+                    Ok(_) => {}
+                    Err(_) => {}
+                }
+            }
+            // a wide paddle cancels a narrow one and the other way around
+            PowerKind::Wide => {
+                self.effects.narrow[player] = None;
+                extend(&mut self.effects.wide[player], duration(kind), now);
+            }
+            PowerKind::Narrow => {
+                self.effects.wide[player] = None;
+                extend(&mut self.effects.narrow[player], duration(kind), now);
+            }
+            PowerKind::Slow => extend(&mut self.effects.slow, duration(kind), now),
+            PowerKind::Laser => extend(&mut self.effects.laser[player], duration(kind), now),
+            PowerKind::Sticky => extend(&mut self.effects.sticky[player], duration(kind), now),
+            PowerKind::Fireball => extend(&mut self.effects.fireball, duration(kind), now),
+            PowerKind::ExtraLife => {
+                self.lives[player] = (self.lives[player] + 1).min(MAX_LIVES);
+                draw_lives(&self.lives, screen);
             }
         }
-        self.powerups.retain(|powerup| powerup.3);
-        for ball in self.balls.iter_mut() {
-            if ball.1 as i16 > 160 {
-                ball.4 = false;
+        self.update_effects(screen);
+        self.draw_effects(screen);
+    }
+
+    // ends the powerups that ran out and sizes the paddles to match the ones still running
+    fn update_effects(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let now = Instant::now();
+        let mut changed = expire(&mut self.effects.slow, now) | expire(&mut self.effects.fireball, now);
+        for player in 0..2 {
+            changed |= expire(&mut self.effects.wide[player], now) | expire(&mut self.effects.narrow[player], now) | expire(&mut self.effects.laser[player], now);
+            if expire(&mut self.effects.sticky[player], now) {
+                self.release(player);
+                changed = true;
+            }
+            let width = if self.effects.wide[player].is_some() {
+                WIDE_WIDTH
+            } else if self.effects.narrow[player].is_some() {
+                NARROW_WIDTH
             } else {
-                // info!("ball.3 is {}", ball.3);
-                // info!("drawing!");
-                Rectangle::new(Point::new( ball.0 as i32 , ball.1 as i32), Size::new(2, 2))
+                PLAYER_WIDTH
+            };
+            if width != self.widths[player] {
+                let pos = if player == 0 { self.player1_pos } else { self.player2_pos };
+                Rectangle::new(Point::new(pos as i32, 150), Size::new(self.widths[player] as u32, 4))
                     .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
                     .draw(screen)
                     .unwrap();
-                ball.0 += ball.2;   // x + speedx
-                ball.1 += ball.3;   // y + speedy
-                Rectangle::new(Point::new( ball.0 as i32 , ball.1 as i32), Size::new(2, 2))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::WHITE))
-                    .draw(screen)
-                    .unwrap();
-                
-                'checker: for x in 0..32 as usize {
-                    if x < 16 {
-                        if ball.0 as i16 >= x as i16 * 8 && ball.0 as i16 <= x as i16 * 8 + BRICK_WIDTH - 1 ||
-                        ball.0 as  i16 + 1 >= x as i16 * 8 && ball.0 as i16 + 1 <= x as i16 * 8 + BRICK_WIDTH - 1 {
-                            for y in 0..32 as usize {
-                                if ball.1 - 1== y as i16 * 4 + BRICK_HEIGHT - 1 + OFFSET_Y as i16  {
-                                    if self.bricks[y][x] != Block::None {
-                                        // info!("collision at ball coords{} {}, computed {} {}", ball.0, ball.1, x*4, OFFSET_Y + (y * 4) as u8);
-                                        // bottom collision
-                                        ball.3 = -ball.3;
-                                        match self.bricks[y][x] {
-                                            Block::Green => {
-                                                self.score += (100 * self.level) as u64;
-                                            }
-                                            Block::Yellow => {
-                                                self.score += (200 * self.level) as u64;
-                                            }
-                                            Block::Red => {
-                                                self.score += (300 as u64 * self.level as u64);
-                                            }
-                                            _ => {}
-                                        }
-                                        draw_score(self.score, screen);
-                                        self.bricks[y][x] = Block::None;
-                                        self.bricks_count -= 1;
-                                        if rng.gen_bool(0.3) {
-                                            match self.powerups.push((x as u8 * 8, y as u8 * 4 + OFFSET_Y, rng.gen_bool(0.5), true)) {
-                                                Ok(_) => {}
-                                                Err(_) => {}
-                                            }
-                                        }
-                                        Rectangle::new(Point::new( (x * 8) as i32 , (OFFSET_Y as usize + (y * 4)) as i32), Size::new(BRICK_WIDTH as u32, BRICK_HEIGHT as u32))
-                                            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                            .draw(screen)
-                                            .unwrap();
-                                        break 'checker;
-                                    }
-                                } else if ball.1 + 2 == y as i16 * 4 + OFFSET_Y as i16 {
-                                    if self.bricks[y][x] != Block::None {
-                                        // top collision
-                                        ball.3 = -ball.3;
-                                        match self.bricks[y][x] {
-                                            Block::Green => {
-                                                self.score += (100 * self.level) as u64;
-                                            }
-                                            Block::Yellow => {
-                                                self.score += (200 * self.level) as u64;
-                                            }
-                                            Block::Red => {
-                                                self.score += (300 as u64 * self.level as u64);
-                                            }
-                                            _ => {}
-                                        }
-                                        draw_score(self.score, screen);
-                                        self.bricks[y][x] = Block::None;
-                                        self.bricks_count -= 1;
-                                        if rng.gen_bool(0.3) {
-                                            match self.powerups.push((x as u8 * 8, y as u8 * 4 + OFFSET_Y, rng.gen_bool(0.5), true)) {
-                                                Ok(_) => {}
-                                                Err(_) => {}
-                                            }
-                                        }
-                                        Rectangle::new(Point::new( (x * 8) as i32 , (OFFSET_Y as usize + (y * 4)) as i32), Size::new(BRICK_WIDTH as u32, BRICK_HEIGHT as u32))
-                                            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                            .draw(screen)
-                                            .unwrap();
-                                        break 'checker;
-                                    }
+                self.widths[player] = width;
+                if player == 0 {
+                    self.player1_pos = self.player1_pos.min(128 - width);
+                } else {
+                    self.player2_pos = self.player2_pos.min(128 - width);
+                }
+                self.draw_paddles(screen);
+            }
+        }
+        // the bars shrink about every quarter of a second
+        if changed || self.frame % 25 == 0 {
+            self.draw_effects(screen);
+        }
+    }
+
+    // Up lets go of the balls on a sticky paddle and fires the laser
+    fn shoot(&mut self, player: usize) {
+        self.release(player);
+        if self.effects.laser[player].is_some() {
+            let pos = if player == 0 { self.player1_pos } else { self.player2_pos };
+            for x in [pos + 1, pos + self.widths[player] - 2] {
+                match self.shots.push((x, 147, true, player)) {
+                    Ok(_) => {}
+                    Err(_) => {}
+                }
+            }
+        }
+    }
+
+    fn release(&mut self, player: usize) {
+        let pos = if player == 0 { self.player1_pos } else { self.player2_pos };
+        let half_width = self.widths[player] as i32 * ONE / 2;
+        for &(owner, offset, speed) in self.caught.iter() {
+            if owner == player {
+                let (vx, vy) = physics::paddle_bounce(offset * ONE + BALL_SIZE / 2 - half_width, half_width, 0, speed);
+                match self.balls.push(Ball::new((pos as i32 + offset) * ONE, 148 * ONE, vx, vy, player)) {
+                    Ok(_) => {}
+                    Err(_) => {}
+                }
+            }
+        }
+        self.caught.retain(|caught| caught.0 != player);
+    }
+
+    async fn update_frame(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        // info!("left {}", self.bricks_count);
+        self.frame = self.frame.wrapping_add(1);
+        self.update_effects(screen);
+        let paddles = [(self.player1_pos, self.widths[0]), (self.player2_pos, self.widths[1])];
+        let mut collected: Vec<(PowerKind, usize), 20> = Vec::new();
+        for powerup in self.powerups.iter_mut() {
+            Rectangle::new(Point::new(powerup.x as i32,  powerup.y as i32), Size::new(4, 4))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(screen)
+                .unwrap(); 
+            powerup.y += 1;
+            if powerup.y == 147 {
+                for (player, &(pos, width)) in paddles.iter().enumerate() {
+                    if powerup.x + 3 >= pos && powerup.x <= pos + width - 1 {
+                        powerup.active = false;
+                        match collected.push((powerup.kind, player)) {
+                            Ok(_) => {}
+                            Err(_) => {}
+                        }
+                        break;
+                    }
+                }
+            }
+            if powerup.y > 160 {
+                powerup.active = false;
+            }
+        }
+        self.powerups.retain(|powerup| powerup.active);
+        for (kind, player) in collected {
+            self.collect(kind, player, screen);
+        }
+        // bricks hit during this frame, handled once every ball moved
+        // true when a fireball burnt through, then the player who gets the points
+        let mut hits: Vec<(usize, usize, bool, usize), 50> = Vec::new();
+        // players who lost a ball this frame
+        let mut lost = [false; 2];
+        let paddles = [
+            (self.player1_pos, self.player1_pos_prev, self.widths[0], self.effects.sticky[0].is_some()),
+            (self.player2_pos, self.player2_pos_prev, self.widths[1], self.effects.sticky[1].is_some()),
+        ];
+        let pace = if self.effects.slow.is_some() { ONE / 2 } else { ONE };
+        let fireball = self.effects.fireball.is_some();
+        for ball in self.balls.iter_mut() {
+            Rectangle::new(Point::new(ball.x / ONE, ball.y / ONE), Size::new(2, 2))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(screen)
+                .unwrap();
+            // the ball bounces at most a few times a frame, what is left of the move after a bounce goes the new way
+            let mut left = ONE;
+            for _ in 0..3 {
+                let dx = ball.vx * left / ONE * pace / ONE;
+                let dy = ball.vy * left / ONE * pace / ONE;
+                let mut first: Option<(Hit, Contact)> = None;
+                let solid = |row: usize, col: usize| self.bricks[row][col] != Block::None && !hits.iter().any(|hit| hit.0 == row && hit.1 == col && hit.2);
+                if let Some((row, col, hit)) = physics::sweep_grid(ball, BALL_SIZE, dx, dy, &BRICKS, solid) {
+                    earlier(&mut first, Some(hit), Contact::Brick(row, col));
+                }
+                // screen sides and the top bar with the score
+                earlier(&mut first, physics::sweep(ball, BALL_SIZE, dx, dy, &Rect { x: -8 * ONE, y: 0, width: 8 * ONE, height: 160 * ONE }), Contact::Wall);
+                earlier(&mut first, physics::sweep(ball, BALL_SIZE, dx, dy, &Rect { x: 128 * ONE, y: 0, width: 8 * ONE, height: 160 * ONE }), Contact::Wall);
+                earlier(&mut first, physics::sweep(ball, BALL_SIZE, dx, dy, &Rect { x: -8 * ONE, y: 0, width: 144 * ONE, height: 20 * ONE }), Contact::Wall);
+                if self.walls[0] {
+                    let wall_width = (self.lastwall as i32 + 1) * 4 * ONE;
+                    earlier(&mut first, physics::sweep(ball, BALL_SIZE, dx, dy, &Rect { x: 0, y: self.wallpos as i32 * ONE, width: wall_width, height: 3 * ONE }), Contact::Wall);
+                    earlier(&mut first, physics::sweep(ball, BALL_SIZE, dx, dy, &Rect { x: 128 * ONE - wall_width, y: self.wallpos as i32 * ONE, width: wall_width, height: 3 * ONE }), Contact::Wall);
+                }
+                if ball.vy > 0 {
+                    for (player, &(pos, _, width, _)) in paddles.iter().enumerate() {
+                        let paddle = Rect { x: pos as i32 * ONE, y: 150 * ONE, width: width as i32 * ONE, height: 4 * ONE };
+                        earlier(&mut first, physics::sweep(ball, BALL_SIZE, dx, dy, &paddle), Contact::Paddle(player));
+                    }
+                }
+                match first {
+                    None => {
+                        ball.advance(dx, dy, ONE);
+                        break;
+                    }
+                    Some((hit, contact)) => {
+                        ball.advance(dx, dy, hit.time);
+                        left -= left * hit.time / ONE;
+                        match contact {
+                            Contact::Brick(row, col) => {
+                                let burn = fireball && self.bricks[row][col] != Block::Steel;
+                                if !burn {
+                                    ball.bounce(hit.normal);
+                                }
+                                match hits.push((row, col, burn, ball.owner)) {
+                                    Ok(_) => {}
+                                    Err(_) => {}
                                 }
                             }
-                        }
-                    } 
-                    if ball.1 >= x as i16 * 4 + OFFSET_Y as i16 && ball.1  <= x as i16 * 4 + BRICK_HEIGHT - 1 + OFFSET_Y as i16 ||
-                    ball.1 + 1 >= x as i16 * 4 + OFFSET_Y as i16 && ball.1 + 1 <= x as i16 * 4 + BRICK_HEIGHT - 1 + OFFSET_Y as i16 {
-                        for y in 0..16 as usize {
-                            if ball.0 as i16 + 1 == y as i16 * 8 + BRICK_WIDTH - 1 {
-                                 if self.bricks[x][y] != Block::None {
-                                    // right collision
-                                    ball.2 = -ball.2;
-                                    match self.bricks[x][y] {
-                                        Block::Green => {
-                                            self.score += (100 * self.level) as u64;
-                                        }
-                                        Block::Yellow => {
-                                            self.score += (200 * self.level) as u64;
-                                        }
-                                        Block::Red => {
-                                            self.score += (300 as u64 * self.level as u64);
-                                        }
-                                        _ => {}
+                            Contact::Wall => ball.bounce(hit.normal),
+                            Contact::Paddle(player) => {
+                                ball.owner = player;
+                                let (pos, moving, width, sticky) = paddles[player];
+                                let speed = physics::speed(ball.vx, ball.vy) + SPEEDUP;
+                                if sticky {
+                                    ball.active = false;
+                                    let offset = (ball.x / ONE - pos as i32).clamp(0, width as i32 - 2);
+                                    match self.caught.push((player, offset, speed)) {
+                                        Ok(_) => {}
+                                        Err(_) => ball.active = true,
                                     }
-                                    draw_score(self.score, screen);
-                                    self.bricks[x][y] = Block::None;
-                                    self.bricks_count -= 1;
-                                    if rng.gen_bool(0.3) {
-                                        match self.powerups.push((y as u8 * 8, x as u8 * 4 + OFFSET_Y, rng.gen_bool(0.5), true)) {
-                                            Ok(_) => {}
-                                            Err(_) => {}
-                                        }
-                                    }
-                                    Rectangle::new(Point::new( (y * 8) as i32 , (OFFSET_Y as usize + (x * 4)) as i32), Size::new(BRICK_WIDTH as u32, BRICK_HEIGHT as u32))
-                                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                        .draw(screen)
-                                        .unwrap();
-                                    break 'checker;
-                                 }
-                            } else if ball.0 as i16  + 2 == y as i16 * 8 {
-                                if self.bricks[x][y] != Block::None {
-                                    // left collision
-                                    ball.2 = -ball.2;
-                                    match self.bricks[x][y] {
-                                        Block::Green => {
-                                            self.score += (100 * self.level) as u64;
-                                        }
-                                        Block::Yellow => {
-                                            self.score += (200 * self.level) as u64;
-                                        }
-                                        Block::Red => {
-                                            self.score += (300 as u64 * self.level as u64);
-                                        }
-                                        _ => {}
-                                    }
-                                    draw_score(self.score, screen);
-                                    self.bricks[x][y] = Block::None;
-                                    self.bricks_count -= 1;
-                                    if rng.gen_bool(0.3) {
-                                        match self.powerups.push((y as u8 * 8, x as u8 * 4 + OFFSET_Y, rng.gen_bool(0.5), true)) {
-                                            Ok(_) => {}
-                                            Err(_) => {}
-                                        }
-                                    }
-                                    Rectangle::new(Point::new( (y * 8) as i32 , (OFFSET_Y as usize + (x * 4)) as i32), Size::new(BRICK_WIDTH as u32, BRICK_HEIGHT as u32))
-                                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                        .draw(screen)
-                                        .unwrap();
-                                    break 'checker;
                                 }
-                            } 
+                                if ball.active {
+                                    // the bounce angle depends on where the ball lands on the paddle and how the paddle moves
+                                    let offset = ball.x + BALL_SIZE / 2 - (pos as i32 * ONE + width as i32 * ONE / 2);
+                                    (ball.vx, ball.vy) = physics::paddle_bounce(offset, width as i32 * ONE / 2, moving as i32 * ONE, speed);
+                                } else {
+                                    break;
+                                }
+                            }
                         }
                     }
                 }
-
-                if ball.1 < 20 {
-                    ball.3 = -ball.3;
-                }
-                // 25
-                // 5 5 5 5 5
-                // 5 10 15 20 25 30
-                if ball.1 == 148 {
-                    // info!("ball.0 is {} {} {}", ball.0, self.player1_pos, self.player1_pos + 19);
-                    if ball.0 as i16 >= self.player1_pos as i16 && ball.0 as i16 <= self.player1_pos as i16 + 19 {
-                        // info!("true!");
-                        let dist = ball.0 as i16 - self.player1_pos as i16;
-                        if dist < 6 {
-                            ball.2 = if ball.2 - 0.2 > MINSPEED { ball.2 - 0.2 } else { MINSPEED };
-                        } else if dist < 11 {
-                            ball.2 = if ball.2 - 0.1 > MINSPEED { ball.2 - 0.1 } else { MINSPEED };
-                        } else if dist < 21 {
-
-                        } else if dist < 26 {
-                            ball.2 = if ball.2 + 0.1 > MAXSPEED { ball.2 + 0.1 } else { MAXSPEED };
-                        } else {
-                            ball.2 = if ball.2 + 0.2 > MAXSPEED { ball.2 + 0.2 } else { MAXSPEED };
-                        }
-                        ball.3 = -ball.3;
-                    } else if ball.0 as i16 >= self.player2_pos as i16 && ball.0 as i16 <= self.player2_pos as i16 + 19 {
-                        let dist = ball.0 as i16 - self.player2_pos as i16;
-                        if dist < 6 {
-                            ball.2 = if ball.2 - 0.2 > MINSPEED { ball.2 - 0.2 } else { MINSPEED };
-                        } else if dist < 11 {
-                            ball.2 = if ball.2 - 0.1 > MINSPEED { ball.2 - 0.1 } else { MINSPEED };
-                        } else if dist < 16 {
-
-                        } else if dist < 21 {
-                            ball.2 = if ball.2 + 0.1 > MAXSPEED { ball.2 + 0.1 } else { MAXSPEED };
-                        } else {
-                            ball.2 = if ball.2 + 0.2 > MAXSPEED { ball.2 + 0.2 } else { MAXSPEED };
-                        }
-                        ball.3 = -ball.3;
-                    }
+            }
+            if ball.y >= 160 * ONE {
+                ball.active = false;
+                lost[ball.owner] = true;
+            } else if ball.active {
+                Rectangle::new(Point::new(ball.x / ONE, ball.y / ONE), Size::new(2, 2))
+                    .into_styled(PrimitiveStyle::with_fill(if fireball { Rgb565::CSS_ORANGE_RED } else { Rgb565::WHITE }))
+                    .draw(screen)
+                    .unwrap();
+            }
+        }
+        self.balls.retain(|ball| ball.active);
+        // laser shots climb 3 pixels a frame and stop at the first brick they touch
+        for shot in self.shots.iter_mut() {
+            Rectangle::new(Point::new(shot.0 as i32, shot.1 as i32), Size::new(1, 3))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(screen)
+                .unwrap();
+            for _ in 0..3 {
+                shot.1 -= 1;
+                if shot.1 < 20 {
+                    shot.2 = false;
+                    break;
                 }
-                if ball.0 <= 0.0 || ball.0 >= 128.0 {
-                    ball.2 = -ball.2;
+                if shot.1 < OFFSET_Y || (shot.1 - OFFSET_Y) % 4 >= BRICK_HEIGHT as u8 || shot.0 % 8 >= BRICK_WIDTH as u8 {
+                    continue;
                 }
-                if self.lastwall > 0 {
-                    if ball.1 >= self.wallpos - 2 && ball.1 <= self.wallpos + 3 {
-                        if ball.0 > 0.0 && ball.0 as i16 - 1 <= (self.lastwall + 1) * 4 {
-                            ball.3 = -ball.3;
-                        } else if ball.0 <= 128.0 && ball.0 as i16 + 1 >= (32 - self.lastwall - 1) * 4 - 1 {
-                            ball.3 = -ball.3;
-                        }
-                    }
-
-                    if ball.1 >= self.wallpos - 1 && ball.1 <= self.wallpos + 2 {
-                        if ball.0 as i16 - 1 <= (self.lastwall + 1) * 4 + 1 {
-                            ball.2 = -ball.2;
-                        } else if ball.0 as i16 + 1 >= (32 - self.lastwall - 1) * 4 - 1 {
-                            ball.2 = -ball.2;
-                        }
+                let row = ((shot.1 - OFFSET_Y) / 4) as usize;
+                let col = (shot.0 / 8) as usize;
+                if row < BRICKS.rows as usize && self.bricks[row][col] != Block::None {
+                    shot.2 = false;
+                    match hits.push((row, col, false, shot.3)) {
+                        Ok(_) => {}
+                        Err(_) => {}
                     }
+                    break;
                 }
-
-                if ball.1 >= 160 {
-                    ball.4 = false;
+            }
+            if shot.2 {
+                Rectangle::new(Point::new(shot.0 as i32, shot.1 as i32), Size::new(1, 3))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
+                    .draw(screen)
+                    .unwrap();
+            }
+        }
+        self.shots.retain(|shot| shot.2);
+        for (row, col, burn, player) in hits {
+            if burn {
+                // fireballs break anything but steel in one go
+                match self.bricks[row][col] {
+                    Block::None | Block::Steel => {}
+                    Block::Tough(_) => self.break_brick(row, col, player, screen),
+                    _ => self.hit_brick(row, col, player, screen),
                 }
+            } else {
+                self.hit_brick(row, col, player, screen);
             }
         }
-        self.balls.retain(|ball| ball.4);
         // info!("bricks {} {}", self.bricks_count, self.bricks_count != 0);
-        if self.balls.len() == 0 && self.player1_started == true && self.player2_started == true {
+        // a player whose last ball fell loses a life
+        // anyone left without a ball serves again while they have lives, even if someone else lost the ball they served
+        for player in 0..2 {
+            let in_play = self.balls.iter().any(|ball| ball.owner == player) || self.caught.iter().any(|caught| caught.0 == player);
+            if lost[player] && !in_play {
+                self.lives[player] = self.lives[player].saturating_sub(1);
+                draw_lives(&self.lives, screen);
+            }
+            if !in_play && self.lives[player] > 0 {
+                if player == 0 {
+                    self.player1_started = false;
+                } else {
+                    self.player2_started = false;
+                }
+            }
+        }
+        // game over once nothing is in play and nobody has a life left to serve with
+        if self.balls.len() == 0 && self.caught.len() == 0 && self.lives.iter().all(|&lives| lives == 0) {
             return false;
         }
         return self.bricks_count != 0;
@@ -606,53 +641,25 @@ impl <'a> Breakout<'a> {
             fmt::write(&mut temp, format_args!("Level: {}", self.level)).unwrap();
             Text::new( &temp, Point::new(0, 8), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
                 .draw(screen).unwrap();
-            temp.clear();
-            fmt::write(&mut temp, format_args!("Score: {}", self.score)).unwrap();
-            Text::new(&temp, Point::new(0, 18), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
-                .draw(screen).unwrap();
+            draw_scores(&self.scores, screen);
             for i in 0..32 {
                 for j in 0..16 {
                     if self.bricks[i][j] != Block::None {
                         // info!("drawing to {} {}", j * 4, i * 4 + OFFSET_Y as usize);
-                        Rectangle::new(Point::new( (j * 8) as i32 , (OFFSET_Y as usize + (i * 4)) as i32), Size::new(BRICK_WIDTH as u32, BRICK_HEIGHT as  u32))
-                            .into_styled(PrimitiveStyle::with_fill(
-                                match self.bricks[i][j] {
-                                    Block::Green => if (j +i) %2 == 0 { Rgb565::GREEN } else { Rgb565::CSS_SEA_GREEN },
-                                    Block::Yellow => if (j + i) % 2 == 0 { Rgb565::YELLOW } else { Rgb565::CSS_ORANGE},
-                                    Block::Red => if (j + i) % 2 == 0 { Rgb565::RED } else { Rgb565::CSS_DARK_RED },
-                                    _ => Rgb565::RED
-                                }
-                            ))
-                            .draw(screen)
-                            .unwrap();
+                        draw_brick(self.bricks[i][j], i, j, screen);
                     }
                 }
             }
-            Rectangle::new(Point::new(self.player1_pos as i32 , 150 as i32), Size::new(PLAYER_WIDTH as u32, 4))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
-                .draw(screen)
-                .unwrap();
-            Rectangle::new(Point::new(self.player2_pos as i32 , 150 as i32), Size::new(PLAYER_WIDTH as u32, 4))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
-                .draw(screen)
-                .unwrap();
+            self.draw_paddles(screen);
+            draw_lives(&self.lives, screen);
+            self.draw_effects(screen);
             self.drawn_init = true;
         } else {
             for powerup in self.powerups.iter() {
-                match powerup.2 {
-                    true => {
-                        Rectangle::new(Point::new(powerup.0 as i32,  powerup.1 as i32), Size::new(4, 4))
-                            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_DARK_ORANGE))
-                            .draw(screen)
-                            .unwrap(); 
-                    }
-                    false => {
-                        Rectangle::new(Point::new(powerup.0 as i32,  powerup.1 as i32), Size::new(4, 4))
-                            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_DARK_CYAN))
-                            .draw(screen)
-                            .unwrap(); 
-                    }
-                }
+                Rectangle::new(Point::new(powerup.x as i32,  powerup.y as i32), Size::new(4, 4))
+                    .into_styled(PrimitiveStyle::with_fill(power_color(powerup.kind)))
+                    .draw(screen)
+                    .unwrap(); 
             }
             for i in 0..32 {
                 if self.walls[i] == true {
@@ -662,74 +669,79 @@ impl <'a> Breakout<'a> {
                         .unwrap();
                 }
             }
-            if self.player1_pos_prev > 0 {
-                if self.player1_pos - 1 >= self.player2_pos && self.player1_pos - 1 <= self.player2_pos + PLAYER_WIDTH as u8 - 1 {
-                    Rectangle::new(Point::new(self.player1_pos as i32 - 1, 150 as i32), Size::new(1, 4))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
-                        .draw(screen)
-                        .unwrap(); 
-                } else {
-                    Rectangle::new(Point::new(self.player1_pos as i32 - 1, 150 as i32), Size::new(1, 4))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                        .draw(screen)
-                        .unwrap();
-                }
-                Rectangle::new(Point::new(self.player1_pos as i32 + PLAYER_WIDTH as i32 - 1, 150 as i32), Size::new(1, 4))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
-                    .draw(screen)
-                    .unwrap();
-            } else if self.player1_pos_prev < 0 {
-                Rectangle::new(Point::new(self.player1_pos as i32 , 150 as i32), Size::new(1, 4))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
-                    .draw(screen)
-                    .unwrap();
-                if self.player1_pos + PLAYER_WIDTH as u8 >= self.player2_pos && self.player1_pos + PLAYER_WIDTH as u8 <= self.player2_pos + PLAYER_WIDTH as u8 - 1 {
-                    Rectangle::new(Point::new(self.player1_pos as i32 + PLAYER_WIDTH as i32, 150 as i32), Size::new(1, 4))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
-                        .draw(screen)
-                        .unwrap();
-                } else {
-                    Rectangle::new(Point::new(self.player1_pos as i32 + PLAYER_WIDTH as i32, 150 as i32), Size::new(1, 4))
+            // clear the column each paddle left, then draw both again so overlapping paddles stay whole
+            let moves = [
+                (self.player1_pos, self.player1_pos_prev, self.widths[0]),
+                (self.player2_pos, self.player2_pos_prev, self.widths[1]),
+            ];
+            for &(pos, prev, width) in moves.iter() {
+                let x = if prev > 0 { pos as i32 - 1 } else { pos as i32 + width as i32 };
+                if prev != 0 {
+                    Rectangle::new(Point::new(x, 150), Size::new(1, 4))
                         .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
                         .draw(screen)
                         .unwrap();
                 }
             }
-            self.player1_pos_prev = 0;
-            if self.player2_pos_prev > 0 {
-                if self.player2_pos - 1 >= self.player1_pos && self.player2_pos - 1 <= self.player1_pos + PLAYER_WIDTH as u8 - 1 {
-                    Rectangle::new(Point::new(self.player2_pos as i32 - 1, 150 as i32), Size::new(1, 4))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
-                        .draw(screen)
-                        .unwrap();    
-                } else {
-                    Rectangle::new(Point::new(self.player2_pos as i32 - 1, 150 as i32), Size::new(1, 4))
+            if self.player1_pos_prev != 0 || self.player2_pos_prev != 0 {
+                self.draw_paddles(screen);
+            }
+            // balls held by sticky paddles ride along with them
+            for &(player, offset, _) in self.caught.iter() {
+                let (pos, prev, _) = moves[player];
+                if prev != 0 {
+                    Rectangle::new(Point::new(pos as i32 - prev as i32 + offset, 148), Size::new(2, 2))
                         .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
                         .draw(screen)
                         .unwrap();
-                } 
-                Rectangle::new(Point::new(self.player2_pos as i32 + PLAYER_WIDTH as i32 - 1, 150 as i32), Size::new(1, 4))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
-                    .draw(screen)
-                    .unwrap();
-            } else if self.player2_pos_prev < 0 {
-                Rectangle::new(Point::new(self.player2_pos as i32 , 150 as i32), Size::new(1, 4))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
+                }
+                Rectangle::new(Point::new(pos as i32 + offset, 148), Size::new(2, 2))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::WHITE))
                     .draw(screen)
                     .unwrap();
-                if self.player2_pos + PLAYER_WIDTH as u8 >= self.player1_pos && self.player2_pos + PLAYER_WIDTH as u8 <= self.player1_pos + PLAYER_WIDTH as u8 - 1 {
-                    Rectangle::new(Point::new(self.player2_pos as i32 + PLAYER_WIDTH as i32, 150 as i32), Size::new(1, 4))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
-                        .draw(screen)
-                        .unwrap();
-                } else {
-                    Rectangle::new(Point::new(self.player2_pos as i32 + PLAYER_WIDTH as i32, 150 as i32), Size::new(1, 4))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            }
+            self.player1_pos_prev = 0;
+            self.player2_pos_prev = 0;
+        }
+    }
+
+    fn draw_paddles(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(self.player1_pos as i32 , 150 as i32), Size::new(self.widths[0] as u32, 4))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
+            .draw(screen)
+            .unwrap();
+        Rectangle::new(Point::new(self.player2_pos as i32 , 150 as i32), Size::new(self.widths[1] as u32, 4))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
+            .draw(screen)
+            .unwrap();
+    }
+
+    // a bar for every running powerup on the right of the top bar
+    // player 1 on the first row, player 2 on the second and the ones for every ball on the third
+    fn draw_effects(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let now = Instant::now();
+        Rectangle::new(Point::new(84, 0), Size::new(44, 20))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let rows = [
+            [(self.effects.wide[0], PowerKind::Wide), (self.effects.narrow[0], PowerKind::Narrow), (self.effects.laser[0], PowerKind::Laser), (self.effects.sticky[0], PowerKind::Sticky)],
+            [(self.effects.wide[1], PowerKind::Wide), (self.effects.narrow[1], PowerKind::Narrow), (self.effects.laser[1], PowerKind::Laser), (self.effects.sticky[1], PowerKind::Sticky)],
+            [(self.effects.slow, PowerKind::Slow), (self.effects.fireball, PowerKind::Fireball), (None, PowerKind::Slow), (None, PowerKind::Slow)],
+        ];
+        for (row, timers) in rows.iter().enumerate() {
+            let mut x = 84;
+            for &(timer, kind) in timers.iter() {
+                if let Some(end) = timer {
+                    // a full bar is twice the duration, the longest a powerup stacks to
+                    let length = end.saturating_duration_since(now).as_millis() * 10 / (duration(kind).as_millis() * 2);
+                    Rectangle::new(Point::new(x, 1 + row as i32 * 7), Size::new(length.max(1) as u32, 4))
+                        .into_styled(PrimitiveStyle::with_fill(power_color(kind)))
                         .draw(screen)
                         .unwrap();
+                    x += 11;
                 }
             }
-            self.player2_pos_prev = 0;
         }
     }
     
@@ -743,12 +755,17 @@ impl <'a> Breakout<'a> {
             }
             Input::Up => {
                 if self.player1_started == false {
-                    self.player1_started = true;
-                    match self.balls.push((self.player1_pos as f32 + 12.0, 146, 0.0, -1, true)) {
-                        Ok(_) => {}
-                        Err(_) => {}
+                    // out of lives, nothing left to serve
+                    if self.lives[0] > 0 {
+                        self.player1_started = true;
+                        match self.balls.push(Ball::new((self.player1_pos as i32 + 12) * ONE, 146 * ONE, 0, -MIN_SPEED, 0)) {
+                            Ok(_) => {}
+                            Err(_) => {}
+                        }
                     }
                     // info!("spawned ball 1");
+                } else {
+                    self.shoot(0);
                 }
                 return true
             }
@@ -760,7 +777,7 @@ impl <'a> Breakout<'a> {
                 return true
             }
             Input::Right => { 
-                if self.player1_pos < 98 { 
+                if self.player1_pos + self.widths[0] < 128 { 
                     self.player1_pos_prev = 1; 
                     self.player1_pos += 1 
                 }
@@ -768,12 +785,17 @@ impl <'a> Breakout<'a> {
             }
             Input::Up2 => {
                 if self.player2_started == false {
-                    self.player2_started = true;
-                    match self.balls.push((self.player2_pos as f32 + 12.0, 146, 0.0, -1, true)) {
-                        Ok(_) => {}
-                        Err(_) => {}
+                    // out of lives, nothing left to serve
+                    if self.lives[1] > 0 {
+                        self.player2_started = true;
+                        match self.balls.push(Ball::new((self.player2_pos as i32 + 12) * ONE, 146 * ONE, 0, -MIN_SPEED, 1)) {
+                            Ok(_) => {}
+                            Err(_) => {}
+                        }
                     }
                     // info!("spawned ball 2");
+                } else {
+                    self.shoot(1);
                 }
                 return true
             }
@@ -785,7 +807,7 @@ impl <'a> Breakout<'a> {
                 return true
             }
             Input::Right2 => {
-                if self.player2_pos < 98 { 
+                if self.player2_pos + self.widths[1] < 128 { 
                     self.player2_pos_prev = 1; 
                     self.player2_pos += 1 
                 }
@@ -803,7 +825,7 @@ impl <'a> Breakout<'a> {
                 return true
             }
             Input::RightLeft => {
-                if self.player1_pos < 98 { 
+                if self.player1_pos + self.widths[0] < 128 { 
                     self.player1_pos_prev = 1; 
                     self.player1_pos += 1 
                 }
@@ -818,18 +840,18 @@ impl <'a> Breakout<'a> {
                     self.player1_pos_prev = -1; 
                     self.player1_pos -= 1 
                 }
-                if self.player2_pos < 98 { 
+                if self.player2_pos + self.widths[1] < 128 { 
                     self.player2_pos_prev = 1; 
                     self.player2_pos += 1 
                 }
                 return true
             }
             Input::RightRight => {
-                if self.player1_pos < 98 { 
+                if self.player1_pos + self.widths[0] < 128 { 
                     self.player1_pos_prev = 1; 
                     self.player1_pos += 1;
                 }
-                if self.player2_pos < 98 { 
+                if self.player2_pos + self.widths[1] < 128 { 
                     self.player2_pos_prev = 1; 
                     self.player2_pos += 1 
                 }
@@ -856,7 +878,7 @@ impl <'a> Breakout<'a> {
                                 INPUT_SIGNAL.reset();
                             },
                             MenuOption::Exit => {
-                                unsafe { CURRENT = 0 };
+                                set_current(0);
                                 return;
                             }
                             _ => {}
@@ -880,7 +902,7 @@ impl <'a> Breakout<'a> {
                             INPUT_SIGNAL.reset();
                         },
                         MenuOption::Exit => {
-                            unsafe { CURRENT = 0 };
+                            set_current(0);
                             return;
                         }
                         _ => {}
@@ -888,7 +910,15 @@ impl <'a> Breakout<'a> {
                     self.level += 1;
                     self.init();
                 } else {
-                    let mut failed_menu: Menu<'_> = Menu::init("Failed!", &[MenuOption::Restart, MenuOption::Exit], screen);
+                    self.draw_results(screen);
+                    let winner = if self.scores[0] > self.scores[1] {
+                        "P1 wins!"
+                    } else if self.scores[1] > self.scores[0] {
+                        "P2 wins!"
+                    } else {
+                        "Draw!"
+                    };
+                    let mut failed_menu: Menu<'_> = Menu::init(winner, &[MenuOption::Restart, MenuOption::Exit], screen);
                     let result: MenuOption = failed_menu.menu_loop(screen).await;
                     info!("obtained result... somehow?");
                     match result {
@@ -898,12 +928,14 @@ impl <'a> Breakout<'a> {
                             INPUT_SIGNAL.reset();
                         },
                         MenuOption::Exit => {
-                            unsafe { CURRENT = 0 };
+                            set_current(0);
                             return;
                         }
                         _ => {}
                     }
                     self.level = 1;
+                    self.lives = [START_LIVES; 2];
+                    self.scores = [0; 2];
                     self.init();
                 }
             }
@@ -911,6 +943,21 @@ impl <'a> Breakout<'a> {
         }
     }
 
+    fn draw_results(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 120), Size::new(128, 40))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<20> = String::new();
+        fmt::write(&mut temp, format_args!("P1: {}", self.scores[0])).unwrap();
+        Text::new(&temp, Point::new(10, 135), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_LIGHT_BLUE))
+            .draw(screen).unwrap();
+        temp.clear();
+        fmt::write(&mut temp, format_args!("P2: {}", self.scores[1])).unwrap();
+        Text::new(&temp, Point::new(10, 150), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
+            .draw(screen).unwrap();
+    }
+
     async fn redraw(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
         Rectangle::new(Point::new(0, 0), Size::new(128, 160))
             .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
@@ -920,52 +967,24 @@ impl <'a> Breakout<'a> {
             fmt::write(&mut temp, format_args!("Level: {}", self.level)).unwrap();
             Text::new( &temp, Point::new(0, 8), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
                 .draw(screen).unwrap();
-            temp.clear();
-            fmt::write(&mut temp, format_args!("Score: {}", self.score)).unwrap();
-            Text::new(&temp, Point::new(0, 18), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
-                .draw(screen).unwrap();
+            draw_scores(&self.scores, screen);
         for i in 0..32 {
             for j in 0..16 {
                 if self.bricks[i][j] != Block::None {
                     // info!("drawing to {} {}", j * 4, i * 4 + OFFSET_Y as usize);
-                    Rectangle::new(Point::new( (j * 8) as i32 , (OFFSET_Y as usize + (i * 4)) as i32), Size::new(BRICK_WIDTH as u32, BRICK_HEIGHT as  u32))
-                        .into_styled(PrimitiveStyle::with_fill(
-                            match self.bricks[i][j] {
-                                Block::Green => if (j +i) %2 == 0 { Rgb565::GREEN } else { Rgb565::CSS_SEA_GREEN },
-                                Block::Yellow => if (j + i) % 2 == 0 { Rgb565::YELLOW } else { Rgb565::CSS_ORANGE},
-                                Block::Red => if (j + i) % 2 == 0 { Rgb565::RED } else { Rgb565::CSS_DARK_RED },
-                                _ => Rgb565::RED
-                            }
-                        ))
-                        .draw(screen)
-                        .unwrap();
+                    draw_brick(self.bricks[i][j], i, j, screen);
                 }
             }
         }
-        Rectangle::new(Point::new(self.player1_pos as i32 , 150 as i32), Size::new(PLAYER_WIDTH as u32, 4))
-            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
-            .draw(screen)
-            .unwrap();
-        Rectangle::new(Point::new(self.player2_pos as i32 , 150 as i32), Size::new(PLAYER_WIDTH as u32, 4))
-            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
-            .draw(screen)
-            .unwrap();
+        self.draw_paddles(screen);
+        draw_lives(&self.lives, screen);
+        self.draw_effects(screen);
         self.drawn_init = true;
         for powerup in self.powerups.iter() {
-            match powerup.2 {
-                true => {
-                    Rectangle::new(Point::new(powerup.0 as i32,  powerup.1 as i32), Size::new(4, 4))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_DARK_ORANGE))
-                        .draw(screen)
-                        .unwrap(); 
-                }
-                false => {
-                    Rectangle::new(Point::new(powerup.0 as i32,  powerup.1 as i32), Size::new(4, 4))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_DARK_CYAN))
-                        .draw(screen)
-                        .unwrap(); 
-                }
-            }
+            Rectangle::new(Point::new(powerup.x as i32,  powerup.y as i32), Size::new(4, 4))
+                .into_styled(PrimitiveStyle::with_fill(power_color(powerup.kind)))
+                .draw(screen)
+                .unwrap(); 
         }
         for i in 0..32 {
             if self.walls[i] == true {
@@ -978,13 +997,114 @@ impl <'a> Breakout<'a> {
     }       
 }
 
-fn draw_score(score: u64, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
-    Rectangle::new(Point::new(34, 12), Size::new(80, 8))
+// keeps the contact the ball reaches first
+fn earlier(first: &mut Option<(Hit, Contact)>, hit: Option<Hit>, contact: Contact) {
+    if let Some(hit) = hit {
+        if first.as_ref().map_or(true, |(best, _)| hit.time < best.time) {
+            *first = Some((hit, contact));
+        }
+    }
+}
+
+// how long a timed powerup lasts when caught once
+fn duration(kind: PowerKind) -> Duration {
+    match kind {
+        PowerKind::Wide | PowerKind::Narrow | PowerKind::Sticky => Duration::from_secs(15),
+        PowerKind::Slow | PowerKind::Laser => Duration::from_secs(10),
+        PowerKind::Fireball => Duration::from_secs(8),
+        _ => Duration::from_secs(0),
+    }
+}
+
+// catching a powerup that is still running adds its time to what is left, up to twice its duration
+fn extend(timer: &mut Option<Instant>, duration: Duration, now: Instant) {
+    let start = match *timer {
+        Some(end) if end > now => end,
+        _ => now,
+    };
+    *timer = Some((start + duration).min(now + duration + duration));
+}
+
+// turns a powerup off once its time is up, true when it just ran out
+fn expire(timer: &mut Option<Instant>, now: Instant) -> bool {
+    match *timer {
+        Some(end) if end <= now => {
+            *timer = None;
+            true
+        }
+        _ => false,
+    }
+}
+
+fn power_color(kind: PowerKind) -> Rgb565 {
+    match kind {
+        PowerKind::Split => Rgb565::CSS_DARK_ORANGE,
+        PowerKind::Triple => Rgb565::CSS_DARK_CYAN,
+        PowerKind::Wide => Rgb565::CSS_LIME,
+        PowerKind::Narrow => Rgb565::CSS_PURPLE,
+        PowerKind::Slow => Rgb565::CSS_DEEP_SKY_BLUE,
+        PowerKind::Laser => Rgb565::RED,
+        PowerKind::Sticky => Rgb565::CSS_GOLD,
+        PowerKind::ExtraLife => Rgb565::CSS_HOT_PINK,
+        PowerKind::Fireball => Rgb565::CSS_ORANGE_RED,
+    }
+}
+
+// a dot for every life left next to the level, player 1 on top
+fn draw_lives(lives: &[u8; 2], screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+    Rectangle::new(Point::new(50, 1), Size::new(MAX_LIVES as u32 * 4, 6))
+        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+        .draw(screen)
+        .unwrap();
+    for (player, &count) in lives.iter().enumerate() {
+        for i in 0..count {
+            Rectangle::new(Point::new(50 + i as i32 * 4, 1 + player as i32 * 4), Size::new(2, 2))
+                .into_styled(PrimitiveStyle::with_fill(if player == 0 { Rgb565::CSS_LIGHT_BLUE } else { Rgb565::CSS_ORANGE }))
+                .draw(screen)
+                .unwrap();
+        }
+    }
+}
+
+// both scores under the level, in the colour of each paddle
+fn draw_scores(scores: &[u64; 2], screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+    Rectangle::new(Point::new(0, 11), Size::new(84, 9))
         .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
         .draw(screen)
         .unwrap();
     let mut temp: String<20> = String::new();
-    fmt::write(&mut temp, format_args!("{}", score)).unwrap();
-    Text::new( &temp, Point::new(35, 18), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
-        .draw(screen).unwrap();
+    for (player, score) in scores.iter().enumerate() {
+        temp.clear();
+        fmt::write(&mut temp, format_args!("{}", score)).unwrap();
+        Text::new(&temp, Point::new(player as i32 * 42, 18), MonoTextStyle::new(&FONT_5X8, if player == 0 { Rgb565::CSS_LIGHT_BLUE } else { Rgb565::CSS_ORANGE }))
+            .draw(screen).unwrap();
+    }
+}
+
+fn brick_color(brick: Block, i: usize, j: usize) -> Rgb565 {
+    match brick {
+        Block::Green => if (j + i) % 2 == 0 { Rgb565::GREEN } else { Rgb565::CSS_SEA_GREEN },
+        Block::Yellow => if (j + i) % 2 == 0 { Rgb565::YELLOW } else { Rgb565::CSS_ORANGE },
+        Block::Red => if (j + i) % 2 == 0 { Rgb565::RED } else { Rgb565::CSS_DARK_RED },
+        Block::Tough(3) => Rgb565::CSS_VIOLET,
+        Block::Tough(2) => Rgb565::CSS_MEDIUM_PURPLE,
+        Block::Tough(_) => Rgb565::CSS_INDIGO,
+        Block::Steel => Rgb565::CSS_LIGHT_STEEL_BLUE,
+        Block::Explosive => if (j + i) % 2 == 0 { Rgb565::MAGENTA } else { Rgb565::CSS_DEEP_PINK },
+        Block::None => Rgb565::BLACK,
+    }
+}
+
+fn draw_brick(brick: Block, i: usize, j: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+    Rectangle::new(Point::new((j * 8) as i32, (OFFSET_Y as usize + (i * 4)) as i32), Size::new(BRICK_WIDTH as u32, BRICK_HEIGHT as u32))
+        .into_styled(PrimitiveStyle::with_fill(brick_color(brick, i, j)))
+        .draw(screen)
+        .unwrap();
+    if let Block::Tough(1) = brick {
+        // cracked
+        Rectangle::new(Point::new((j * 8) as i32 + 3, (OFFSET_Y as usize + (i * 4)) as i32), Size::new(1, 2))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+    }
 }
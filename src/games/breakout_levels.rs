@@ -0,0 +1,377 @@
+// Breakout levels, one string per line
+// the first line holds the wall: how many 4 px segments grow from each side of the screen and its height,
+// then the drop table, one letter for every entry a falling powerup is picked from:
+// 'M' two balls split off a ball, 'T' two balls from the paddle, 'W' wide paddle, 'N' narrow paddle,
+// 'S' slow balls, 'L' laser paddle, 'C' sticky paddle, 'E' extra life and 'F' fireball
+// every other line is a row of 16 bricks, '.' empty, 'G' green, 'Y' yellow, 'R' red,
+// '2' and '3' bricks that take that many hits, 'S' steel and 'X' explosive
+pub const LEVELS: [&[&str]; 20] = [
+    // 1, classic
+    &[
+        "0 100 MT",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+    ],
+    // 2, walls
+    &[
+        "5 80 MTW",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+    ],
+    // 3, narrow
+    &[
+        "8 90 MTWN",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+    ],
+    // 4, tight
+    &[
+        "12 105 MTWS",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+    ],
+    // 5, gate
+    &[
+        "15 130 MTWNS",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "RRRRRRRRRRRRRRRR",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "YYYYYYYYYYYYYYYY",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+        "GGGGGGGGGGGGGGGG",
+    ],
+    // 6, checkers
+    &[
+        "0 100 MTWLE",
+        "R.R.R.R.R.R.R.R.",
+        ".R.R.R.R.R.R.R.R",
+        "R.R.R.R.R.R.R.R.",
+        ".R.R.R.R.R.R.R.R",
+        "Y.Y.Y.Y.Y.Y.Y.Y.",
+        ".Y.Y.Y.Y.Y.Y.Y.Y",
+        "Y.Y.Y.Y.Y.Y.Y.Y.",
+        ".Y.Y.Y.Y.Y.Y.Y.Y",
+        "G.G.G.G.G.G.G.G.",
+        ".G.G.G.G.G.G.G.G",
+        "G.G.G.G.G.G.G.G.",
+        ".G.G.G.G.G.G.G.G",
+    ],
+    // 7, pyramid
+    &[
+        "4 90 MTWNC",
+        ".......RR.......",
+        "......RRRR......",
+        ".....YYYYYY.....",
+        "....YYYYYYYY....",
+        "...GGGGGGGGGG...",
+        "..GGGGGGGGGGGG..",
+        ".GGGGGGGGGGGGGG.",
+        "GGGGGGGGGGGGGGGG",
+    ],
+    // 8, diamond
+    &[
+        "6 100 MTSLF",
+        ".......RR.......",
+        "......RRRR......",
+        ".....RYYYYR.....",
+        "....RYYYYYYR....",
+        "...RYYGGGGYYR...",
+        "..RYYGGXXGGYYR..",
+        "...RYYGGGGYYR...",
+        "....RYYYYYYR....",
+        ".....RYYYYR.....",
+        "......RRRR......",
+        ".......RR.......",
+    ],
+    // 9, columns
+    &[
+        "6 100 MTWNLE",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+        "R.Y.G.R..R.G.Y.R",
+    ],
+    // 10, invader
+    &[
+        "3 95 MTWCF",
+        "..G..........G..",
+        "...G........G...",
+        "..GGGGGGGGGGGG..",
+        ".GGXGGGGGGGGXGG.",
+        "GGGGGGGGGGGGGGGG",
+        "G.GGGGGGGGGGGG.G",
+        "G.G..........G.G",
+        "...GG......GG...",
+    ],
+    // 11, hearts
+    &[
+        "0 100 MTWNSL",
+        "................",
+        ".RR.RR....RR.RR.",
+        "RRR2RRR..RRR2RRR",
+        "RRR2RRR..RRR2RRR",
+        ".RRRRR....RRRRR.",
+        "..RRR......RRR..",
+        "...R........R...",
+        "................",
+        ".YY.YY....YY.YY.",
+        "YYYYYYY..YYYYYYY",
+        ".YYYYY....YYYYY.",
+        "..YYY......YYY..",
+        "...Y........Y...",
+    ],
+    // 12, frame
+    &[
+        "4 110 MTWCE",
+        "RRRRRRRRRRRRRRRR",
+        "R..............R",
+        "R.YYYYYYYYYYYY.R",
+        "R.Y..........Y.R",
+        "R.Y.GGGGGGGG.Y.R",
+        "R.Y.G......G.Y.R",
+        "R.Y.G.3333.G.Y.R",
+        "R.Y.G......G.Y.R",
+        "R.Y.GGGGGGGG.Y.R",
+        "R.Y..........Y.R",
+        "R.YYYYYYYYYYYY.R",
+        "R..............R",
+        "RRRRRRRRRRRRRRRR",
+    ],
+    // 13, zigzag
+    &[
+        "8 120 MTNSLF",
+        "RR......RR......",
+        ".RR......RR.....",
+        "..RR......RR....",
+        "...RR......RR...",
+        "....RR......RR..",
+        ".....RR......RR.",
+        "......RR......RR",
+        "SS.....RR......S",
+        "YY......YY......",
+        ".YY......YY.....",
+        "..YY......YY....",
+        "...YY......YY...",
+        "....YY......YY..",
+        ".....YY......YY.",
+        "......YY......YY",
+        "YY.....YY......Y",
+        "GG......GG......",
+        ".GG......GG.....",
+        "..GG......GG....",
+        "...GG......GG...",
+    ],
+    // 14, cross
+    &[
+        "0 100 MTWNCF",
+        "RR............RR",
+        "YRR..........RRY",
+        ".YRR........RRY.",
+        "..YRR......RRY..",
+        "...YRR....RRY...",
+        "....YRR..RRY....",
+        ".....YRXXRY.....",
+        "......YRRY......",
+        "......GGGG......",
+        ".....GG..GG.....",
+        "....GG....GG....",
+        "...GG......GG...",
+        "..GG........GG..",
+        ".GG..........GG.",
+        "GG............GG",
+    ],
+    // 15, stairs
+    &[
+        "10 115 MTWSLCE",
+        "R...............",
+        "YY..............",
+        "GGG.............",
+        "RRRR............",
+        "YYYYY...........",
+        "GGGGGG..........",
+        "RRRRRRR.........",
+        "YYYYYYYY........",
+        "GGGGGGGGG.......",
+        "RRRRRRRRRR......",
+        "YYYYYYYYYYY.....",
+        "GGGGGGGGGGGG....",
+        "RRRRRRRRRRRRR...",
+        "YYYYYYYYYYYYYY..",
+        "GGGGGGGGGGGGGGG.",
+        "RRRRRRRRRRRRRRRR",
+    ],
+    // 16, rain
+    &[
+        "0 100 MTWNSLF",
+        "R...Y...G...R...",
+        "..G...R...Y...G.",
+        "Y...G...R...Y...",
+        "...S...Y...G...S",
+        ".Y...G...R...Y..",
+        "G...R...Y...G...",
+        "..R...Y...G...R.",
+        "Y...G...R...Y...",
+        "S..Y...G...R...Y",
+        ".G...R...Y...G..",
+        "R...Y...G...R...",
+        "..G...R...Y...G.",
+    ],
+    // 17, fortress
+    &[
+        "2 115 MTNLCF",
+        "S.S.S.S..S.S.S.S",
+        "RRRRRRR..RRRRRRR",
+        "RYYYYYR..RYYYYYR",
+        "RY...YR..RY...YR",
+        "RY...YRXXRY...YR",
+        "RYYYYYYYYYYYYYYR",
+        "RYYGGGGGGGGGGYYR",
+        "RYYG........GYYR",
+        "RYYG........GYYR",
+        "RYYGGGG..GGGGYYR",
+        "RRRRRRR..RRRRRRR",
+    ],
+    // 18, waves
+    &[
+        "6 105 MTWNSLCE",
+        "RR....RRRR....RR",
+        "RR....RRRR....RR",
+        "..RRRR....RRRR..",
+        "..RRRR....RRRR..",
+        "RR....RRRR....RR",
+        "RR....RRRR....RR",
+        "..YYYY....YYYY..",
+        "..YYYY....YYYY..",
+        "22....2222....22",
+        "22....2222....22",
+        "..2222....2222..",
+        "..2222....2222..",
+        "GG....GGGG....GG",
+        "GG....GGGG....GG",
+        "..GGGG....GGGG..",
+        "..GGGG....GGGG..",
+        "GG....GGGG....GG",
+        "GG....GGGG....GG",
+    ],
+    // 19, bridges
+    &[
+        "0 110 MTWNSLCF",
+        "SSSSRRRRRRRRSSSS",
+        "................",
+        ".YYYY..YY..YYYY.",
+        ".Y..Y..YY..Y..Y.",
+        ".YYYY..YY..YYYY.",
+        "................",
+        "GGGGGGGGGGGGGGGG",
+        "G..............G",
+        "G.XX.XX..XX.XX.G",
+        "G..............G",
+        "GGGGGGGGGGGGGGGG",
+    ],
+    // 20, finale
+    &[
+        "15 130 MTWNSLCEF",
+        "S2GRYGRYGRYGRY2S",
+        "YYGGRRGGYYRRYYRR",
+        "GRYGRYGRYGRYGRRY",
+        "RRGGYYRRYYRRYYGG",
+        "YGRYGRYGRYGRYGRR",
+        "YYGGRRGGYYRRYYRR",
+        "RYGRYGRYGRYGRYGR",
+        "RRGGYYRRYYRRYYGG",
+        "GRYGRYGRYGRYGRRY",
+        "YYGGRRGGYYRRYYRR",
+        "YGRYGRYGRYGRYGRR",
+        "RRGGYYRRYYRRYYGG",
+        "3333333333333333",
+        "YYGGRRGGYYRRYYRR",
+        "GRYGRYGRYGRYGRRY",
+        "RRGGYYRRYYRRYYGG",
+        "YGRYGRYGRYGRYGRR",
+        "YYGGRRGGYYRRYYRR",
+        "RYGRYGRXXRYGRYGR",
+        "RRGGYYRRYYRRYYGG",
+        "GRYGRYGRYGRYGRRY",
+        "YYGGRRGGYYRRYYRR",
+        "YGRYGRYGRYGRYGRR",
+        "RRGGYYRRYYRRYYGG",
+        "RYGRYGRYGRYGRYGR",
+    ],
+];
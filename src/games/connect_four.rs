@@ -0,0 +1,52 @@
+use embassy_embedded_hal::shared_bus::blocking::spi::SpiDevice;
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_rp::{
+    gpio::Output, spi::Spi
+};
+
+use mipidsi::interface::SpiInterface;
+use mipidsi::models::ST7735s;
+
+use embedded_graphics::{
+    pixelcolor::Rgb565, prelude::*, primitives::{
+        Circle, PrimitiveStyle, Rectangle
+    }
+};
+
+use crate::games::board::Rules;
+
+const BOARD_COLOR: Rgb565 = Rgb565::BLUE;
+
+// 7 columns of 6, four in a row wins, the cpu looks up to 6 moves ahead
+pub const RULES: Rules = Rules {
+    title: "Connect Four",
+    cols: 7,
+    rows: 6,
+    line: 4,
+    gravity: true,
+    depths: [1, 3, 6],
+    colors: [Rgb565::RED, Rgb565::YELLOW],
+    background: BOARD_COLOR,
+    draw_board,
+    draw_piece,
+};
+
+fn draw_board(area: Rectangle, _size: i32, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+    area.into_styled(PrimitiveStyle::with_fill(BOARD_COLOR))
+        .draw(screen)
+        .unwrap();
+}
+
+// empty cells are holes in the board
+fn draw_piece(corner: Point, size: i32, player: u8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+    let color = match player {
+        1 => RULES.colors[0],
+        2 => RULES.colors[1],
+        _ => Rgb565::BLACK,
+    };
+    Circle::new(corner + Point::new(2, 2), size as u32 - 3)
+        .into_styled(PrimitiveStyle::with_fill(color))
+        .draw(screen)
+        .unwrap();
+}
@@ -0,0 +1,629 @@
+use core::fmt;
+
+use embassy_embedded_hal::shared_bus::blocking::spi::SpiDevice;
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_rp::{
+    clocks::RoscRng, gpio::Output, spi::Spi
+};
+
+use mipidsi::interface::SpiInterface;
+use mipidsi::models::ST7735s;
+
+use embedded_graphics::{
+    mono_font::{ascii::{FONT_10X20, FONT_6X10}, iso_8859_14::FONT_5X8, MonoTextStyle}, pixelcolor::Rgb565, prelude::*, primitives::{
+        Circle, PrimitiveStyle, Rectangle
+    }, text::Text
+};
+use embassy_futures::select::{select, Either};
+use embassy_time::{Duration, Instant, Timer};
+
+use heapless::{
+    Vec, String
+};
+use rand::Rng;
+
+use crate::{menu::selector::Menu, INPUT_SIGNAL};
+use crate::set_current;
+use crate::storage;
+
+use {defmt_rtt as _, panic_probe as _};
+use defmt::*;
+
+use rust_pico_console::{Input, MenuOption};
+
+// columns, rows, mines and the size of a cell in pixels, every board fills the screen under the hud
+const BOARDS: [(u8, u8, u16, i32); 3] = [(8, 8, 10, 14), (12, 14, 25, 10), (16, 17, 40, 8)];
+const HUD: i32 = 20;
+// one u16 per row, the large board is the biggest one
+const MAX_ROWS: usize = 17;
+const MAX_CELLS: usize = 16 * MAX_ROWS;
+// magic, then the best time in seconds of every board, u16::MAX when there is none yet
+const TIMES_MAGIC: u8 = 0x6d;
+const TIMES_BYTES: usize = 1 + 2 * BOARDS.len();
+// points lost for stepping on a mine in versus
+const MINE_PENALTY: u16 = 10;
+const OPTIONS: usize = 2;
+const NUMBER_COLORS: [Rgb565; 8] = [Rgb565::CSS_DODGER_BLUE, Rgb565::CSS_LIME_GREEN, Rgb565::RED, Rgb565::MAGENTA, Rgb565::CSS_ORANGE, Rgb565::CYAN, Rgb565::WHITE, Rgb565::CSS_GRAY];
+const PLAYER_COLORS: [Rgb565; 2] = [Rgb565::YELLOW, Rgb565::CYAN];
+
+pub struct Minesweeper {
+    board: usize,
+    versus: bool,
+    mines: [u16; MAX_ROWS],
+    revealed: [u16; MAX_ROWS],
+    flagged: [u16; MAX_ROWS],
+    // mines go down on the first reveal, away from the cursor
+    placed: bool,
+    cursor: (u8, u8),
+    // safe cells still hidden, the board is cleared at 0
+    safe_left: u16,
+    // the mine that ended a solo game
+    exploded: Option<(u8, u8)>,
+    // versus players take turns on the same board
+    turn: usize,
+    scores: [u16; 2],
+    start: Instant,
+    seconds: u16,
+    best: [u16; 3],
+}
+
+impl Minesweeper {
+    pub fn new() -> Minesweeper {
+        let mut game = Minesweeper {
+            board: 0,
+            versus: false,
+            mines: [0; MAX_ROWS],
+            revealed: [0; MAX_ROWS],
+            flagged: [0; MAX_ROWS],
+            placed: false,
+            cursor: (0, 0),
+            safe_left: 0,
+            exploded: None,
+            turn: 0,
+            scores: [0; 2],
+            start: Instant::now(),
+            seconds: 0,
+            best: [u16::MAX; 3],
+        };
+        game.load_best();
+        game
+    }
+
+    pub fn init(&mut self) {
+        let (cols, rows, mines, _) = BOARDS[self.board];
+        self.mines = [0; MAX_ROWS];
+        self.revealed = [0; MAX_ROWS];
+        self.flagged = [0; MAX_ROWS];
+        self.placed = false;
+        self.cursor = (cols / 2, rows / 2);
+        self.safe_left = cols as u16 * rows as u16 - mines;
+        self.exploded = None;
+        self.turn = 0;
+        self.scores = [0; 2];
+        self.seconds = 0;
+    }
+
+    fn load_best(&mut self) {
+        let mut data = [0u8; TIMES_BYTES];
+        if storage::read(storage::MINESWEEPER_TIMES, &mut data) == false || data[0] != TIMES_MAGIC {
+            return;
+        }
+        for board in 0..BOARDS.len() {
+            self.best[board] = u16::from_be_bytes([data[1 + board * 2], data[2 + board * 2]]);
+        }
+    }
+
+    // keeps the time if it beats the best one for this board
+    fn save_best(&mut self) -> bool {
+        if self.versus || self.seconds >= self.best[self.board] {
+            return false;
+        }
+        self.best[self.board] = self.seconds;
+        let mut data = [0u8; TIMES_BYTES];
+        data[0] = TIMES_MAGIC;
+        for board in 0..BOARDS.len() {
+            data[1 + board * 2..3 + board * 2].copy_from_slice(&self.best[board].to_be_bytes());
+        }
+        storage::write(storage::MINESWEEPER_TIMES, &data)
+    }
+
+    fn has(cells: &[u16; MAX_ROWS], cell: (u8, u8)) -> bool {
+        (cells[cell.1 as usize] & (1 << cell.0)) != 0
+    }
+
+    fn around(&self, cell: (u8, u8)) -> Vec<(u8, u8), 8> {
+        let (cols, rows, _, _) = BOARDS[self.board];
+        let mut cells: Vec<(u8, u8), 8> = Vec::new();
+        for dy in -1i8..=1 {
+            for dx in -1i8..=1 {
+                let x = cell.0 as i8 + dx;
+                let y = cell.1 as i8 + dy;
+                if (dx != 0 || dy != 0) && x >= 0 && y >= 0 && x < cols as i8 && y < rows as i8 {
+                    match cells.push((x as u8, y as u8)) {
+                        Ok(_) => {}
+                        Err(_) => {}
+                    }
+                }
+            }
+        }
+        cells
+    }
+
+    fn count(&self, cell: (u8, u8)) -> u8 {
+        self.around(cell).iter().filter(|c| Self::has(&self.mines, **c)).count() as u8
+    }
+
+    // the first reveal and its neighbours never hold a mine
+    fn place_mines(&mut self, safe: (u8, u8)) {
+        let (cols, rows, mines, _) = BOARDS[self.board];
+        let mut rng = RoscRng;
+        let mut placed = 0;
+        while placed < mines {
+            let cell = (rng.gen_range(0..cols), rng.gen_range(0..rows));
+            if Self::has(&self.mines, cell) || (cell.0 as i8 - safe.0 as i8).abs() <= 1 && (cell.1 as i8 - safe.1 as i8).abs() <= 1 {
+                continue;
+            }
+            self.mines[cell.1 as usize] |= 1 << cell.0;
+            placed += 1;
+        }
+        self.placed = true;
+        self.start = Instant::now();
+    }
+
+    // opens a hidden cell, empty cells open everything around them, Err with the cell when it was a mine
+    fn reveal(&mut self, cell: (u8, u8), screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> Result<u16, (u8, u8)> {
+        if Self::has(&self.revealed, cell) || Self::has(&self.flagged, cell) {
+            return Ok(0);
+        }
+        if self.placed == false {
+            self.place_mines(cell);
+        }
+        self.revealed[cell.1 as usize] |= 1 << cell.0;
+        self.draw_cell(cell, screen);
+        if Self::has(&self.mines, cell) {
+            return Err(cell);
+        }
+        let mut opened = 0;
+        let mut stack: Vec<(u8, u8), MAX_CELLS> = Vec::new();
+        match stack.push(cell) {
+            Ok(_) => {}
+            Err(_) => {}
+        }
+        while let Some(current) = stack.pop() {
+            opened += 1;
+            if self.count(current) != 0 {
+                continue;
+            }
+            for next in self.around(current) {
+                if Self::has(&self.revealed, next) || Self::has(&self.flagged, next) {
+                    continue;
+                }
+                self.revealed[next.1 as usize] |= 1 << next.0;
+                self.draw_cell(next, screen);
+                match stack.push(next) {
+                    Ok(_) => {}
+                    Err(_) => {}
+                }
+            }
+        }
+        self.safe_left -= opened;
+        Ok(opened)
+    }
+
+    // on an open number with as many flags around it, opens all the other neighbours, Err with a mine it set off
+    fn chord(&mut self, cell: (u8, u8), screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> Result<u16, (u8, u8)> {
+        let around = self.around(cell);
+        // mines found in versus count as flags
+        let marked = around.iter().filter(|c| Self::has(&self.flagged, **c) || Self::has(&self.revealed, **c) && Self::has(&self.mines, **c)).count() as u8;
+        if marked != self.count(cell) {
+            return Ok(0);
+        }
+        let mut opened = 0;
+        let mut hit = None;
+        for next in around {
+            match self.reveal(next, screen) {
+                Ok(count) => opened += count,
+                Err(mine) => hit = Some(mine),
+            }
+        }
+        match hit {
+            Some(mine) => Err(mine),
+            None => Ok(opened),
+        }
+    }
+
+    // reveals or chords under the cursor, scores it in versus and hands the turn over
+    fn open(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let cell = self.cursor;
+        if Self::has(&self.flagged, cell) {
+            return;
+        }
+        let result = if Self::has(&self.revealed, cell) {
+            self.chord(cell, screen)
+        } else {
+            self.reveal(cell, screen)
+        };
+        if self.versus {
+            match result {
+                Ok(0) => return,
+                Ok(opened) => self.scores[self.turn] += opened,
+                Err(_) => self.scores[self.turn] = self.scores[self.turn].saturating_sub(MINE_PENALTY),
+            }
+            self.turn ^= 1;
+        } else if let Err(mine) = result {
+            info!("boom");
+            // the mine that went off, a chord sets it off from the number next to it
+            self.exploded = Some(mine);
+        }
+        self.draw_hud(screen);
+        self.draw_cursor(screen);
+    }
+
+    fn flag(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let cell = self.cursor;
+        if Self::has(&self.revealed, cell) {
+            return;
+        }
+        self.flagged[cell.1 as usize] ^= 1 << cell.0;
+        self.draw_cell(cell, screen);
+        self.draw_cursor(screen);
+        self.draw_hud(screen);
+    }
+
+    fn move_cursor(&mut self, dx: i8, dy: i8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let (cols, rows, _, _) = BOARDS[self.board];
+        let x = (self.cursor.0 as i8 + dx).clamp(0, cols as i8 - 1) as u8;
+        let y = (self.cursor.1 as i8 + dy).clamp(0, rows as i8 - 1) as u8;
+        self.draw_cell(self.cursor, screen);
+        self.cursor = (x, y);
+        self.draw_cursor(screen);
+    }
+
+    // mines the players haven't found yet, going by the flags
+    fn mines_left(&self) -> i16 {
+        let (cols, rows, mines, _) = BOARDS[self.board];
+        let mut marked = 0;
+        for y in 0..rows {
+            for x in 0..cols {
+                if Self::has(&self.flagged, (x, y)) || Self::has(&self.revealed, (x, y)) && Self::has(&self.mines, (x, y)) {
+                    marked += 1;
+                }
+            }
+        }
+        mines as i16 - marked
+    }
+
+    fn elapsed(&self) -> u16 {
+        if self.placed == false {
+            return 0;
+        }
+        (Instant::now() - self.start).as_secs().min(u16::MAX as u64) as u16
+    }
+
+    fn origin(&self) -> Point {
+        let (cols, rows, _, cell) = BOARDS[self.board];
+        Point::new((128 - cols as i32 * cell) / 2, HUD + (160 - HUD - rows as i32 * cell) / 2)
+    }
+
+    fn draw_cell(&self, cell: (u8, u8), screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let size = BOARDS[self.board].3;
+        let corner = self.origin() + Point::new(cell.0 as i32 * size, cell.1 as i32 * size);
+        let revealed = Self::has(&self.revealed, cell);
+        let mine = Self::has(&self.mines, cell);
+        // a solo game shows every mine once it is lost
+        let shown = revealed || self.exploded.is_some() && mine;
+        Rectangle::new(corner, Size::new(size as u32, size as u32))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let inner = Rectangle::new(corner + Point::new(1, 1), Size::new(size as u32 - 1, size as u32 - 1));
+        if shown && mine {
+            inner.into_styled(PrimitiveStyle::with_fill(if self.exploded == Some(cell) || self.versus { Rgb565::RED } else { Rgb565::CSS_DIM_GRAY }))
+                .draw(screen)
+                .unwrap();
+            Circle::new(corner + Point::new(2, 2), size as u32 - 3)
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(screen)
+                .unwrap();
+        } else if revealed {
+            let count = self.count(cell);
+            if count > 0 {
+                let mut temp: String<2> = String::new();
+                fmt::write(&mut temp, format_args!("{}", count)).unwrap();
+                if size >= 10 {
+                    Text::new(&temp, corner + Point::new((size - 6) / 2 + 1, size / 2 + 4), MonoTextStyle::new(&FONT_6X10, NUMBER_COLORS[count as usize - 1]))
+                        .draw(screen).unwrap();
+                } else {
+                    Text::new(&temp, corner + Point::new(2, 7), MonoTextStyle::new(&FONT_5X8, NUMBER_COLORS[count as usize - 1]))
+                        .draw(screen).unwrap();
+                }
+            }
+        } else {
+            inner.into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_GRAY))
+                .draw(screen)
+                .unwrap();
+            if Self::has(&self.flagged, cell) {
+                // a lost game crosses out the wrong flags
+                let wrong = self.exploded.is_some() && !mine;
+                Rectangle::new(corner + Point::new(size / 2 - 1, 2), Size::new(size as u32 / 2 - 1, size as u32 / 2 - 1))
+                    .into_styled(PrimitiveStyle::with_fill(if wrong { Rgb565::BLACK } else { Rgb565::RED }))
+                    .draw(screen)
+                    .unwrap();
+                Rectangle::new(corner + Point::new(size / 2 - 1, 2), Size::new(1, size as u32 - 4))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                    .draw(screen)
+                    .unwrap();
+            }
+        }
+    }
+
+    fn draw_cursor(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let size = BOARDS[self.board].3;
+        let corner = self.origin() + Point::new(self.cursor.0 as i32 * size, self.cursor.1 as i32 * size);
+        Rectangle::new(corner, Size::new(size as u32 + 1, size as u32 + 1))
+            .into_styled(PrimitiveStyle::with_stroke(PLAYER_COLORS[self.turn], 1))
+            .draw(screen)
+            .unwrap();
+    }
+
+    fn draw_time(&self, seconds: u16, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(88, 2), Size::new(40, 10))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<20> = String::new();
+        fmt::write(&mut temp, format_args!("{:02}:{:02}", seconds / 60, seconds % 60)).unwrap();
+        Text::new(&temp, Point::new(92, 10), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_GRAY))
+            .draw(screen).unwrap();
+    }
+
+    // mines left and the time, versus shows both scores and whose turn it is instead of the time
+    fn draw_hud(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, HUD as u32 - 2))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<20> = String::new();
+        if self.versus {
+            for player in 0..2 {
+                temp.clear();
+                fmt::write(&mut temp, format_args!("P{} {}", player + 1, self.scores[player])).unwrap();
+                Text::new(&temp, Point::new(2 + player as i32 * 44, 10), MonoTextStyle::new(&FONT_6X10, PLAYER_COLORS[player]))
+                    .draw(screen).unwrap();
+            }
+            Rectangle::new(Point::new(2 + self.turn as i32 * 44, 13), Size::new(temp.len() as u32 * 6, 2))
+                .into_styled(PrimitiveStyle::with_fill(PLAYER_COLORS[self.turn]))
+                .draw(screen)
+                .unwrap();
+            temp.clear();
+            fmt::write(&mut temp, format_args!("*{}", self.mines_left())).unwrap();
+            Text::new(&temp, Point::new(98, 10), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_GRAY))
+                .draw(screen).unwrap();
+        } else {
+            fmt::write(&mut temp, format_args!("Mines {}", self.mines_left())).unwrap();
+            Text::new(&temp, Point::new(2, 10), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+                .draw(screen).unwrap();
+            self.draw_time(self.elapsed(), screen);
+        }
+    }
+
+    fn redraw(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        self.draw_hud(screen);
+        let (cols, rows, _, _) = BOARDS[self.board];
+        for y in 0..rows {
+            for x in 0..cols {
+                self.draw_cell((x, y), screen);
+            }
+        }
+        self.draw_cursor(screen);
+    }
+
+    // arrows move the cursor, left+shoot opens or chords, right+shoot flags, versus takes the controller of whoever's turn it is
+    fn handle_input(&mut self, input: &Input, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let first = self.turn == 0;
+        match input {
+            Input::Back => return false,
+            Input::Up if first => self.move_cursor(0, -1, screen),
+            Input::Down if first => self.move_cursor(0, 1, screen),
+            Input::Left if first => self.move_cursor(-1, 0, screen),
+            Input::Right if first => self.move_cursor(1, 0, screen),
+            Input::Left_Shoot if first => self.open(screen),
+            Input::Right_Shoot if first => self.flag(screen),
+            Input::Up2 if !first => self.move_cursor(0, -1, screen),
+            Input::Down2 if !first => self.move_cursor(0, 1, screen),
+            Input::Left2 if !first => self.move_cursor(-1, 0, screen),
+            Input::Right2 if !first => self.move_cursor(1, 0, screen),
+            Input::Left2_Shoot if !first => self.open(screen),
+            Input::Right2_Shoot if !first => self.flag(screen),
+            _ => {}
+        }
+        true
+    }
+
+    // pre-game settings, up and down pick a row, left and right change it, false when the player leaves
+    async fn options(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let mut selected = 0;
+        self.draw_options(selected, screen);
+        loop {
+            Timer::after(Duration::from_millis(100)).await;
+            INPUT_SIGNAL.reset();
+            match INPUT_SIGNAL.wait().await {
+                Input::Up | Input::Up2 => {
+                    selected = if selected > 0 { selected - 1 } else { OPTIONS - 1 };
+                }
+                Input::Down | Input::Down2 => {
+                    selected = if selected + 1 < OPTIONS { selected + 1 } else { 0 };
+                }
+                input @ (Input::Left | Input::Left2 | Input::Right | Input::Right2) => {
+                    let forward = input == Input::Right || input == Input::Right2;
+                    match selected {
+                        0 => {
+                            let count = BOARDS.len();
+                            self.board = if forward { (self.board + 1) % count } else { (self.board + count - 1) % count };
+                        }
+                        _ => self.versus = !self.versus,
+                    }
+                }
+                Input::Select => return true,
+                Input::Back => return false,
+                _ => continue,
+            }
+            self.draw_options(selected, screen);
+        }
+    }
+
+    fn draw_options(&self, selected: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        Text::new("Minesweeper", Point::new(9, 30), MonoTextStyle::new(&FONT_10X20, Rgb565::RED))
+            .draw(screen).unwrap();
+        let (cols, rows, mines, _) = BOARDS[self.board];
+        for row in 0..OPTIONS {
+            let mut temp: String<24> = String::new();
+            match row {
+                0 => fmt::write(&mut temp, format_args!("Board: < {}x{} >", cols, rows)).unwrap(),
+                _ => fmt::write(&mut temp, format_args!("Mode: < {} >", if self.versus { "Versus" } else { "Solo" })).unwrap(),
+            }
+            Text::new(&temp, Point::new(4, 55 + row as i32 * 15), MonoTextStyle::new(&FONT_6X10, if row == selected { Rgb565::WHITE } else { Rgb565::CSS_GRAY }))
+                .draw(screen).unwrap();
+        }
+        let mut temp: String<24> = String::new();
+        fmt::write(&mut temp, format_args!("{} mines", mines)).unwrap();
+        Text::new(&temp, Point::new(4, 100), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_DARK_SEA_GREEN))
+            .draw(screen).unwrap();
+        temp.clear();
+        let best = self.best[self.board];
+        if best == u16::MAX {
+            fmt::write(&mut temp, format_args!("Best: --:--")).unwrap();
+        } else {
+            fmt::write(&mut temp, format_args!("Best: {:02}:{:02}", best / 60, best % 60)).unwrap();
+        }
+        Text::new(&temp, Point::new(4, 112), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_DARK_SEA_GREEN))
+            .draw(screen).unwrap();
+        Text::new("e: play q: exit", Point::new(19, 150), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
+            .draw(screen).unwrap();
+    }
+
+    fn draw_results(&self, best: bool, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 120), Size::new(128, 40))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<24> = String::new();
+        if self.versus {
+            for player in 0..2 {
+                temp.clear();
+                fmt::write(&mut temp, format_args!("P{}: {} points", player + 1, self.scores[player])).unwrap();
+                Text::new(&temp, Point::new(10, 133 + player as i32 * 14), MonoTextStyle::new(&FONT_6X10, PLAYER_COLORS[player]))
+                    .draw(screen).unwrap();
+            }
+            return;
+        }
+        fmt::write(&mut temp, format_args!("Time: {:02}:{:02}", self.seconds / 60, self.seconds % 60)).unwrap();
+        Text::new(&temp, Point::new(10, 133), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+            .draw(screen).unwrap();
+        temp.clear();
+        let record = self.best[self.board];
+        if best {
+            fmt::write(&mut temp, format_args!("New best time!")).unwrap();
+        } else if self.exploded.is_some() {
+            fmt::write(&mut temp, format_args!("{} safe cells left", self.safe_left)).unwrap();
+        } else {
+            fmt::write(&mut temp, format_args!("Best: {:02}:{:02}", record / 60, record % 60)).unwrap();
+        }
+        Text::new(&temp, Point::new(10, 147), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
+            .draw(screen).unwrap();
+    }
+
+    pub async fn game_loop(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if self.options(screen).await == false {
+            set_current(0);
+            return;
+        }
+        self.init();
+        self.redraw(screen);
+        let mut shown = 0;
+        loop {
+            match select(INPUT_SIGNAL.wait(), Timer::after(Duration::from_millis(250))).await {
+                Either::First(input) => {
+                    if self.handle_input(&input, screen) == false {
+                        // create pause menu
+                        let paused = self.elapsed();
+                        let mut pause_menu: Menu<'_> = Menu::init("Pause menu", &[MenuOption::Resume, MenuOption::Restart, MenuOption::Exit], screen);
+                        let result: MenuOption = pause_menu.menu_loop(screen).await;
+                        match result {
+                            MenuOption::Resume | MenuOption::None => {
+                                // the clock stops while paused
+                                if self.placed {
+                                    self.start = Instant::now() - Duration::from_secs(paused as u64);
+                                }
+                            }
+                            MenuOption::Restart => self.init(),
+                            MenuOption::Exit => {
+                                set_current(0);
+                                return;
+                            }
+                            _ => {}
+                        }
+                        self.redraw(screen);
+                        Timer::after(Duration::from_millis(100)).await;
+                        INPUT_SIGNAL.reset();
+                    }
+                }
+                Either::Second(_) => {
+                    let seconds = self.elapsed();
+                    if !self.versus && seconds != shown {
+                        shown = seconds;
+                        self.draw_time(seconds, screen);
+                    }
+                }
+            }
+
+            if self.exploded.is_none() && self.safe_left > 0 {
+                continue;
+            }
+            info!("game over!");
+            self.seconds = self.elapsed();
+            if self.exploded.is_some() {
+                self.redraw(screen);
+            }
+            let best = self.exploded.is_none() && self.save_best();
+            let title = if self.versus {
+                if self.scores[0] > self.scores[1] { "P1 wins!" } else if self.scores[1] > self.scores[0] { "P2 wins!" } else { "Draw!" }
+            } else if self.exploded.is_some() {
+                "Boom!"
+            } else {
+                "Cleared!"
+            };
+            let mut end_menu: Menu<'_> = Menu::init(title, &[MenuOption::Restart, MenuOption::Options, MenuOption::Exit], screen);
+            self.draw_results(best, screen);
+            let result: MenuOption = end_menu.menu_loop(screen).await;
+            match result {
+                MenuOption::Restart | MenuOption::None => {}
+                MenuOption::Options => {
+                    if self.options(screen).await == false {
+                        set_current(0);
+                        return;
+                    }
+                }
+                MenuOption::Exit => {
+                    set_current(0);
+                    return;
+                }
+                _ => {}
+            }
+            self.init();
+            self.redraw(screen);
+            shown = 0;
+            Timer::after(Duration::from_millis(100)).await;
+            INPUT_SIGNAL.reset();
+        }
+    }
+}
@@ -1,4 +1,15 @@
 pub mod snake;
+pub mod snake_maps;
 pub mod spaceinvaders;
+pub mod spaceinvaders_waves;
 pub mod sokoban;
-pub mod breakout;
\ No newline at end of file
+pub mod breakout;
+pub mod breakout_levels;
+pub mod tetris;
+pub mod pong;
+pub mod tron;
+pub mod minesweeper;
+pub mod board;
+pub mod connect_four;
+pub mod tictactoe;
+pub mod asteroids;
\ No newline at end of file
@@ -0,0 +1,532 @@
+use core::fmt;
+
+use embassy_embedded_hal::shared_bus::blocking::spi::SpiDevice;
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_rp::{
+    clocks::RoscRng, gpio::Output, spi::Spi
+};
+
+use mipidsi::interface::SpiInterface;
+use mipidsi::models::ST7735s;
+
+use embedded_graphics::{
+    mono_font::{ascii::{FONT_10X20, FONT_6X10}, MonoTextStyle}, pixelcolor::Rgb565, prelude::*, primitives::{
+        PrimitiveStyle, Rectangle
+    }, text::Text
+};
+use embassy_futures::select::{select, Either};
+use embassy_time::{Duration, Instant, Timer};
+
+use heapless::{
+    Deque, String
+};
+use rand::Rng;
+
+use crate::{menu::selector::Menu, INPUT_SIGNAL};
+use crate::set_current;
+
+use {defmt_rtt as _, panic_probe as _};
+use defmt::*;
+
+use rust_pico_console::{Input, MenuOption};
+use rust_pico_console::physics::{self, Ball, Hit, Rect, MIN_SPEED, ONE, SPEEDUP};
+
+// the score line at the top, the court is the rest of the screen
+const HUD: i32 = 12;
+const PADDLE_WIDTH: i32 = 24;
+const PADDLE_DEPTH: i32 = 3;
+// space between a paddle and the end of the court behind it
+const PADDLE_GAP: i32 = 4;
+// pixels a paddle moves for every input
+const STEP: i32 = 2;
+const BALL_SIZE: i32 = 2 * ONE;
+const WIN_SCORE: u8 = 11;
+// milliseconds between frames
+const FRAME: u64 = 10;
+// frames the cpu waits before serving
+const SERVE_DELAY: u8 = 50;
+// how many frames old the ball the cpu reacts to is, for every cpu level
+const CPU_DELAYS: [usize; 3] = [30, 15, 5];
+const OPTIONS: usize = 2;
+const COLORS: [Rgb565; 2] = [Rgb565::CSS_LIGHT_BLUE, Rgb565::CSS_ORANGE];
+
+// the court is worked out along the paddles (u) and across them (v), the orientation only changes how it is drawn
+// player 1 defends the far end of the court, player 2 the near end
+pub struct Pong {
+    ball: Ball,
+    paddles: [i32; 2],
+    // how far the paddles moved during the last frame, it puts spin on the ball
+    moved: [i32; 2],
+    scores: [u8; 2],
+    // the player holding the ball before a serve, None while the ball is in play
+    serving: Option<usize>,
+    serve_wait: u8,
+    horizontal: bool,
+    // 0 when a person plays the second paddle, otherwise the cpu level
+    cpu: usize,
+    // what the ball did over the last frames, the cpu sees the oldest one
+    history: Deque<Ball, 32>,
+}
+
+impl Pong {
+    pub fn new() -> Pong {
+        Pong {
+            ball: Ball::new(0, 0, 0, 0, 0),
+            paddles: [0; 2],
+            moved: [0; 2],
+            scores: [0; 2],
+            serving: Some(0),
+            serve_wait: 0,
+            horizontal: false,
+            cpu: 0,
+            history: Deque::new(),
+        }
+    }
+
+    pub fn init(&mut self) {
+        self.scores = [0; 2];
+        self.paddles = [(self.along() - PADDLE_WIDTH) / 2; 2];
+        self.moved = [0; 2];
+        self.serve();
+    }
+
+    // length of the court along the paddles
+    fn along(&self) -> i32 {
+        if self.horizontal { 160 - HUD } else { 128 }
+    }
+
+    // length of the court from one end to the other
+    fn across(&self) -> i32 {
+        if self.horizontal { 128 } else { 160 - HUD }
+    }
+
+    // where the front of a paddle is
+    fn paddle_v(&self, p: usize) -> i32 {
+        if p == 0 { self.across() - PADDLE_GAP - PADDLE_DEPTH } else { PADDLE_GAP }
+    }
+
+    // the serve changes hands every two points, and every point from 10 all
+    fn server(&self) -> usize {
+        let total = self.scores[0] as usize + self.scores[1] as usize;
+        if self.scores[0] >= WIN_SCORE - 1 && self.scores[1] >= WIN_SCORE - 1 {
+            total % 2
+        } else {
+            (total / 2) % 2
+        }
+    }
+
+    // the game ends at 11, with a lead of two
+    fn winner(&self) -> Option<usize> {
+        for p in 0..2 {
+            if self.scores[p] >= WIN_SCORE && self.scores[p] >= self.scores[1 - p] + 2 {
+                return Some(p);
+            }
+        }
+        None
+    }
+
+    fn serve(&mut self) {
+        let server = self.server();
+        self.serving = Some(server);
+        self.serve_wait = SERVE_DELAY;
+        self.history.clear();
+        self.hold_ball(server);
+    }
+
+    // the ball sits in the middle of the serving paddle
+    fn hold_ball(&mut self, p: usize) {
+        let v = if p == 0 { self.paddle_v(0) - 2 } else { self.paddle_v(1) + PADDLE_DEPTH };
+        self.ball = Ball::new((self.paddles[p] + PADDLE_WIDTH / 2 - 1) * ONE, v * ONE, 0, 0, p);
+    }
+
+    fn launch(&mut self, p: usize) {
+        let offset = RoscRng.gen_range(-PADDLE_WIDTH / 2..PADDLE_WIDTH / 2) * ONE;
+        let (vx, vy) = physics::paddle_bounce(offset, PADDLE_WIDTH * ONE / 2, 0, MIN_SPEED);
+        self.ball.vx = vx;
+        self.ball.vy = if p == 0 { vy } else { -vy };
+        self.serving = None;
+    }
+
+    fn move_paddle(&mut self, p: usize, delta: i32) {
+        let pos = (self.paddles[p] + delta).clamp(0, self.along() - PADDLE_WIDTH);
+        // a paddle can move several times between frames, the spin counts all of it
+        self.moved[p] += pos - self.paddles[p];
+        self.paddles[p] = pos;
+    }
+
+    // a rectangle of the court on the screen
+    fn rect(&self, u: i32, v: i32, size_u: u32, size_v: u32) -> Rectangle {
+        if self.horizontal {
+            Rectangle::new(Point::new(v, HUD + u), Size::new(size_v, size_u))
+        } else {
+            Rectangle::new(Point::new(u, HUD + v), Size::new(size_u, size_v))
+        }
+    }
+
+    // the cpu follows where the ball will cross its paddle, seen with a delay, and waits in the middle otherwise
+    fn think(&mut self) {
+        if self.cpu == 0 {
+            return;
+        }
+        match self.history.push_back(self.ball) { Ok(_) => {} Err(_) => {} }
+        while self.history.len() > CPU_DELAYS[self.cpu - 1] {
+            self.history.pop_front();
+        }
+        if self.serving.is_some() {
+            return;
+        }
+        let seen = match self.history.front() {
+            Some(ball) => *ball,
+            None => self.ball,
+        };
+        let target = if seen.vy < 0 {
+            // straight line to the paddle, folded back into the court at every side it bounces off
+            let frames = (seen.y - (self.paddle_v(1) + PADDLE_DEPTH) * ONE) / -seen.vy;
+            let range = (self.along() * ONE - BALL_SIZE).max(1);
+            let folded = (seen.x + seen.vx * frames).rem_euclid(2 * range);
+            let u = if folded > range { 2 * range - folded } else { folded };
+            u / ONE - PADDLE_WIDTH / 2
+        } else {
+            (self.along() - PADDLE_WIDTH) / 2
+        };
+        let delta = (target - self.paddles[1]).clamp(-1, 1);
+        self.move_paddle(1, delta);
+    }
+
+    // moves the ball, bouncing off the sides and the paddles, Some(player) when a player scored
+    fn update_ball(&mut self) -> Option<usize> {
+        let (along, across) = (self.along(), self.across());
+        let ball = &mut self.ball;
+        let paddles = [
+            Rect { x: self.paddles[0] * ONE, y: (across - PADDLE_GAP - PADDLE_DEPTH) * ONE, width: PADDLE_WIDTH * ONE, height: PADDLE_DEPTH * ONE },
+            Rect { x: self.paddles[1] * ONE, y: PADDLE_GAP * ONE, width: PADDLE_WIDTH * ONE, height: PADDLE_DEPTH * ONE },
+        ];
+        // the ball bounces at most a few times a frame, what is left of the move after a bounce goes the new way
+        let mut left = ONE;
+        for _ in 0..3 {
+            let dx = ball.vx * left / ONE;
+            let dy = ball.vy * left / ONE;
+            let mut first: Option<(Hit, Option<usize>)> = None;
+            let mut earlier = |hit: Option<Hit>, paddle: Option<usize>| {
+                if let Some(hit) = hit {
+                    if first.map_or(true, |(best, _)| hit.time < best.time) {
+                        first = Some((hit, paddle));
+                    }
+                }
+            };
+            earlier(physics::sweep(ball, BALL_SIZE, dx, dy, &Rect { x: -8 * ONE, y: -8 * ONE, width: 8 * ONE, height: (across + 16) * ONE }), None);
+            earlier(physics::sweep(ball, BALL_SIZE, dx, dy, &Rect { x: along * ONE, y: -8 * ONE, width: 8 * ONE, height: (across + 16) * ONE }), None);
+            if ball.vy > 0 {
+                earlier(physics::sweep(ball, BALL_SIZE, dx, dy, &paddles[0]), Some(0));
+            } else {
+                earlier(physics::sweep(ball, BALL_SIZE, dx, dy, &paddles[1]), Some(1));
+            }
+            match first {
+                None => {
+                    ball.advance(dx, dy, ONE);
+                    break;
+                }
+                Some((hit, paddle)) => {
+                    ball.advance(dx, dy, hit.time);
+                    left -= left * hit.time / ONE;
+                    match paddle {
+                        None => ball.bounce(hit.normal),
+                        Some(p) => {
+                            ball.owner = p;
+                            let speed = physics::speed(ball.vx, ball.vy) + SPEEDUP;
+                            // the bounce angle depends on where the ball lands on the paddle and how the paddle moves
+                            let offset = ball.x + BALL_SIZE / 2 - (self.paddles[p] * ONE + PADDLE_WIDTH * ONE / 2);
+                            let (vx, vy) = physics::paddle_bounce(offset, PADDLE_WIDTH * ONE / 2, self.moved[p] * ONE, speed);
+                            ball.vx = vx;
+                            ball.vy = if p == 0 { vy } else { -vy };
+                        }
+                    }
+                }
+            }
+        }
+        self.moved = [0; 2];
+        if self.ball.y + BALL_SIZE < 0 {
+            Some(0)
+        } else if self.ball.y > across * ONE {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    fn draw_ball(&self, color: Rgb565, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        self.rect(self.ball.x / ONE, self.ball.y / ONE, 2, 2)
+            .into_styled(PrimitiveStyle::with_fill(color))
+            .draw(screen)
+            .unwrap();
+    }
+
+    fn draw_paddles(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        for p in 0..2 {
+            // the whole lane is cleared, a paddle never moves more than a few pixels a frame
+            self.rect(0, self.paddle_v(p), self.along() as u32, PADDLE_DEPTH as u32)
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(screen)
+                .unwrap();
+            self.rect(self.paddles[p], self.paddle_v(p), PADDLE_WIDTH as u32, PADDLE_DEPTH as u32)
+                .into_styled(PrimitiveStyle::with_fill(COLORS[p]))
+                .draw(screen)
+                .unwrap();
+        }
+    }
+
+    // dashed line across the middle of the court
+    fn draw_net(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let middle = self.across() / 2;
+        for u in (0..self.along()).step_by(8) {
+            self.rect(u, middle, 4, 1)
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_GRAY))
+                .draw(screen)
+                .unwrap();
+        }
+    }
+
+    fn name(&self, p: usize) -> &'static str {
+        match p {
+            0 => "P1",
+            _ if self.cpu > 0 => "CPU",
+            _ => "P2",
+        }
+    }
+
+    // both scores, the one who serves next is marked
+    fn draw_scores(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, HUD as u32))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<20> = String::new();
+        for p in 0..2 {
+            temp.clear();
+            let serve = if self.server() == p { "*" } else { "" };
+            fmt::write(&mut temp, format_args!("{} {}{}", self.name(p), self.scores[p], serve)).unwrap();
+            Text::new(&temp, Point::new(2 + p as i32 * 70, 9), MonoTextStyle::new(&FONT_6X10, COLORS[p]))
+                .draw(screen).unwrap();
+        }
+    }
+
+    fn redraw(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        self.draw_scores(screen);
+        self.draw_net(screen);
+        self.draw_paddles(screen);
+        self.draw_ball(Rgb565::WHITE, screen);
+    }
+
+    fn handle_input(&mut self, input: &Input) -> bool {
+        let human = self.cpu == 0;
+        match input {
+            Input::Back => return false,
+            Input::Left => self.move_paddle(0, -STEP),
+            Input::Right => self.move_paddle(0, STEP),
+            Input::Left2 if human => self.move_paddle(1, -STEP),
+            Input::Right2 if human => self.move_paddle(1, STEP),
+            Input::LeftLeft => {
+                self.move_paddle(0, -STEP);
+                if human { self.move_paddle(1, -STEP) }
+            }
+            Input::RightLeft => {
+                self.move_paddle(0, STEP);
+                if human { self.move_paddle(1, -STEP) }
+            }
+            Input::LeftRight => {
+                self.move_paddle(0, -STEP);
+                if human { self.move_paddle(1, STEP) }
+            }
+            Input::RightRight => {
+                self.move_paddle(0, STEP);
+                if human { self.move_paddle(1, STEP) }
+            }
+            Input::Up if self.serving == Some(0) => self.launch(0),
+            Input::Up2 if human && self.serving == Some(1) => self.launch(1),
+            _ => {}
+        }
+        true
+    }
+
+    // pre-game settings, up and down pick a row, left and right change it, false when the players leave
+    async fn options(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let mut selected = 0;
+        self.draw_options(selected, screen);
+        loop {
+            Timer::after(Duration::from_millis(100)).await;
+            INPUT_SIGNAL.reset();
+            match INPUT_SIGNAL.wait().await {
+                Input::Up | Input::Up2 => {
+                    selected = if selected > 0 { selected - 1 } else { OPTIONS - 1 };
+                }
+                Input::Down | Input::Down2 => {
+                    selected = if selected + 1 < OPTIONS { selected + 1 } else { 0 };
+                }
+                input @ (Input::Left | Input::Left2 | Input::Right | Input::Right2) => {
+                    let forward = input == Input::Right || input == Input::Right2;
+                    match selected {
+                        0 => self.horizontal = !self.horizontal,
+                        _ => {
+                            let levels = CPU_DELAYS.len() + 1;
+                            self.cpu = if forward { (self.cpu + 1) % levels } else { (self.cpu + levels - 1) % levels };
+                        }
+                    }
+                }
+                Input::Select => return true,
+                Input::Back => return false,
+                _ => continue,
+            }
+            self.draw_options(selected, screen);
+        }
+    }
+
+    fn draw_options(&self, selected: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        Text::new("Pong", Point::new(44, 30), MonoTextStyle::new(&FONT_10X20, Rgb565::RED))
+            .draw(screen).unwrap();
+        for row in 0..OPTIONS {
+            let mut temp: String<24> = String::new();
+            match row {
+                0 => fmt::write(&mut temp, format_args!("Court: < {} >", if self.horizontal { "Wide" } else { "Tall" })).unwrap(),
+                _ => match self.cpu {
+                    0 => fmt::write(&mut temp, format_args!("P2: < Human >")).unwrap(),
+                    // the reaction delay in milliseconds, a frame is 10 ms
+                    level => fmt::write(&mut temp, format_args!("P2: < CPU {}ms >", CPU_DELAYS[level - 1] * 10)).unwrap(),
+                },
+            }
+            Text::new(&temp, Point::new(4, 60 + row as i32 * 15), MonoTextStyle::new(&FONT_6X10, if row == selected { Rgb565::WHITE } else { Rgb565::CSS_GRAY }))
+                .draw(screen).unwrap();
+        }
+        Text::new("e: play q: exit", Point::new(19, 150), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
+            .draw(screen).unwrap();
+    }
+
+    fn draw_results(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 120), Size::new(128, 40))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<20> = String::new();
+        for p in 0..2 {
+            temp.clear();
+            fmt::write(&mut temp, format_args!("{}: {}", self.name(p), self.scores[p])).unwrap();
+            Text::new(&temp, Point::new(10, 135 + p as i32 * 15), MonoTextStyle::new(&FONT_6X10, COLORS[p]))
+                .draw(screen).unwrap();
+        }
+    }
+
+    pub async fn game_loop(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if self.options(screen).await == false {
+            set_current(0);
+            return;
+        }
+        self.init();
+        self.redraw(screen);
+        // frames keep to a fixed beat, moving the paddles doesn't speed up the ball
+        let mut next_frame = Instant::now();
+        loop {
+            match select(INPUT_SIGNAL.wait(), Timer::at(next_frame)).await {
+                Either::First(input) => {
+                    if self.handle_input(&input) == false {
+                        // create pause menu
+                        let mut pause_menu: Menu<'_> = Menu::init("Pause menu", &[MenuOption::Resume, MenuOption::Restart, MenuOption::Exit], screen);
+                        let result: MenuOption = pause_menu.menu_loop(screen).await;
+                        match result {
+                            MenuOption::Resume | MenuOption::None => {}
+                            MenuOption::Restart => self.init(),
+                            MenuOption::Exit => {
+                                set_current(0);
+                                return;
+                            }
+                            _ => {}
+                        }
+                        self.redraw(screen);
+                        Timer::after(Duration::from_millis(100)).await;
+                        INPUT_SIGNAL.reset();
+                        next_frame = Instant::now();
+                    }
+                    continue;
+                }
+                Either::Second(_) => {
+                    next_frame = (next_frame + Duration::from_millis(FRAME)).max(Instant::now());
+                }
+            }
+
+            self.draw_ball(Rgb565::BLACK, screen);
+            self.think();
+            let scored = match self.serving {
+                Some(p) => {
+                    self.hold_ball(p);
+                    // the cpu serves on its own after a moment
+                    if p == 1 && self.cpu > 0 {
+                        self.serve_wait = self.serve_wait.saturating_sub(1);
+                        if self.serve_wait == 0 {
+                            self.launch(1);
+                        }
+                    }
+                    self.moved = [0; 2];
+                    None
+                }
+                None => self.update_ball(),
+            };
+            // the ball may have wiped part of the net
+            let middle = self.across() / 2 * ONE;
+            if (self.ball.y - middle).abs() < 4 * ONE {
+                self.draw_net(screen);
+            }
+            self.draw_paddles(screen);
+
+            match scored {
+                Some(p) => {
+                    self.scores[p] += 1;
+                    match self.winner() {
+                        None => {
+                            self.serve();
+                            self.draw_scores(screen);
+                        }
+                        Some(winner) => {
+                            info!("game over!");
+                            let title = match winner {
+                                0 => "P1 wins!",
+                                _ if self.cpu > 0 => "CPU wins!",
+                                _ => "P2 wins!",
+                            };
+                            let mut end_menu: Menu<'_> = Menu::init(title, &[MenuOption::Restart, MenuOption::Options, MenuOption::Exit], screen);
+                            self.draw_results(screen);
+                            let result: MenuOption = end_menu.menu_loop(screen).await;
+                            match result {
+                                MenuOption::Restart | MenuOption::None => {}
+                                MenuOption::Options => {
+                                    if self.options(screen).await == false {
+                                        set_current(0);
+                                        return;
+                                    }
+                                }
+                                MenuOption::Exit => {
+                                    set_current(0);
+                                    return;
+                                }
+                                _ => {}
+                            }
+                            self.init();
+                            self.redraw(screen);
+                            Timer::after(Duration::from_millis(100)).await;
+                            INPUT_SIGNAL.reset();
+                            next_frame = Instant::now();
+                        }
+                    }
+                }
+                None => {}
+            }
+            self.draw_ball(Rgb565::WHITE, screen);
+        }
+    }
+}
@@ -1,7 +1,5 @@
 use core::fmt;
 
-#[allow(static_mut_refs)]
-
 use embassy_embedded_hal::shared_bus::blocking::spi::SpiDevice;
 
 use embassy_sync::blocking_mutex::raw::NoopRawMutex;
@@ -9,7 +7,7 @@ use embassy_rp::{
     clocks::RoscRng, gpio::Output, spi::Spi
 };
 
-use mipidsi::interface::SpiInterface;  
+use mipidsi::interface::SpiInterface;
 use mipidsi::models::ST7735s;
 
 use embedded_graphics::{
@@ -18,16 +16,19 @@ use embedded_graphics::{
     }, text::Text
 };
 use embassy_futures::select::{select, Either};
-use embassy_time::{Duration, Timer};
+use embassy_time::{Duration, Instant, Timer};
 
 use heapless::{
     Deque, String, Vec
 };
 use rand::seq::SliceRandom;
+use rand::RngCore;
 
 
 use crate::INPUT_SIGNAL;
-use crate::CURRENT;
+use crate::set_current;
+use rust_pico_console::snake_ai::{self, Grid, Rng};
+use crate::games::snake_maps::MAPS;
 
 use {defmt_rtt as _, panic_probe as _};
 use defmt::*;
@@ -36,546 +37,899 @@ use rust_pico_console::Input;
 use rust_pico_console::MenuOption;
 use crate::Menu;
 
-const OFFSET_X: u8 = 1;
-const OFFSET_Y: u8 = 7;
-pub struct Snake<'a> {
-    head_1: (u8, u8),
-    second_1: (u8, u8),
-    tail_1: (u8, u8),
-    facing_1: u8,
-    updated_1: bool,
-    active_1: bool,
-    head_2: (u8, u8),
-    second_2: (u8, u8),
-    tail_2: (u8, u8),
-    facing_2: u8,
-    updated_2: bool,
+// top left corner of the board on the screen
+const BOARD_X: i32 = 5;
+const BOARD_Y: i32 = 35;
+// pixels per cell and cells per side, every board is 120 px wide
+const BOARDS: [(u8, u8); 4] = [(8, 15), (6, 20), (5, 24), (4, 30)];
+// rows of the options screen
+const OPTIONS: usize = 7;
+// apples to win a race, seconds in a timed round
+const APPLE_GOALS: [u16; 4] = [10, 15, 25, 50];
+const TIME_GOALS: [u16; 4] = [60, 120, 180, 300];
+// ticks a dead snake waits before it comes back, when the mode lets it
+const RESPAWN_TICKS: u8 = 8;
+// milliseconds between two ticks, the game speeds up as the longest snake grows
+const BASE_TICK: u64 = 250;
+const MIN_TICK: u64 = 100;
+const TICK_STEP: u64 = 5;
+// an item shows up about once every this many ticks and lies on the board for ITEM_TICKS
+const ITEM_CHANCE: u32 = 30;
+const ITEM_TICKS: u8 = 40;
+// how long the timed items last, in ticks
+const SLOW_TICKS: u8 = 20;
+const GHOST_TICKS: u8 = 20;
+// cells a shrink item cuts off, snakes never get shorter than 4
+const SHRINK: usize = 4;
+const HEAD_COLORS: [Rgb565; 2] = [Rgb565::CSS_ORANGE, Rgb565::BLUE];
+const BODY_COLORS: [Rgb565; 2] = [Rgb565::CSS_LIME_GREEN, Rgb565::YELLOW];
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+enum Mode {
+    // the round ends once one snake is left
+    Standing,
+    // first to eat the goal in apples, dead snakes come back
+    Race,
+    // best score when the time runs out, dead snakes come back
+    Timed,
+}
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+enum Item {
+    // the whole game runs at half speed for a while
+    Slow,
+    // worth five apples
+    Golden,
+    // cuts the tail of the snake that eats it
+    Shrink,
+    // the snake passes through bodies for a while, walls still stop it
+    Ghost,
+}
+
+fn item_color(item: Item) -> Rgb565 {
+    match item {
+        Item::Slow => Rgb565::CSS_DEEP_SKY_BLUE,
+        Item::Golden => Rgb565::CSS_GOLD,
+        Item::Shrink => Rgb565::CSS_MAGENTA,
+        Item::Ghost => Rgb565::WHITE,
+    }
+}
+
+#[derive(Clone)]
+#[derive(Copy)]
+struct Player {
+    head: (u8, u8),
+    second: (u8, u8),
+    tail: (u8, u8),
+    start: (u8, u8),
+    facing: u8,
+    updated: bool,
+    active: bool,
     score: u64,
-    active_2: bool,
+    eaten: u16,
+    respawn: u8,
+    // ticks left as a ghost
+    ghost: u8,
+}
+
+impl Player {
+    fn new(start: (u8, u8)) -> Player {
+        Player {
+            head: start,
+            second: (start.0 - 1, start.1),
+            tail: (start.0 - 3, start.1),
+            start,
+            facing: 3,
+            updated: false,
+            active: true,
+            score: 0,
+            eaten: 0,
+            respawn: 0,
+            ghost: 0,
+        }
+    }
+}
+
+pub struct Snake<'a> {
+    players: [Player; 2],
+    bodies: [&'a mut Deque::<(u8, u8), 1025>; 2],
     apple: (u8, u8),
+    // the item on the board, where it lies and the ticks before it goes away
+    item: Option<(Item, (u8, u8), u8)>,
+    // ticks left in slow motion
+    slow: u8,
+    // the effects shown on the hud, so it is only cleared once they run out
+    effects_shown: bool,
     frame: &'a mut Vec::<u32, 32>,
-    body_1: &'a mut Deque::<(u8, u8), 1025>,
-    body_2: &'a mut Deque::<(u8, u8), 1025>,
     apples: &'a mut Vec::<u32, 32>,
+    // obstacles of the map, one row of bits per line like frame
+    walls: [u32; 32],
     apples_count: u16,
     draw_init: bool,
+    start: Instant,
+    // settings from the options screen
+    board: usize,
+    borders: bool,
+    map: usize,
+    mode: Mode,
+    goal: usize,
+    // head-on crashes kill both snakes instead of only the shorter one
+    both_die: bool,
+    // the second snake is played by the cpu at this level, 0 when a person plays it
+    cpu: u8,
+    rng: Rng,
+    pitch: u8,
+    size: u8,
 }
 
 fn setval(value: u32, col: u8, set: bool) -> u32 {
     if set {
-        value | (1 << (32 - col))
+        value | (1 << col)
     } else {
-        value & !(1 << (32 - col))
+        value & !(1 << col)
     }
 }
 
 fn checkval(value: u32, col: u8) -> bool {
-    (value & (1 << (32 - col))) != 0
+    (value & (1 << col)) != 0
 }
 
 
 impl <'a> Snake<'a> {
     pub fn new(frame: &'a  mut Vec<u32, 32>, body_1: &'a mut Deque<(u8, u8), 1025>, body_2: &'a mut Deque<(u8, u8), 1025>, apples: &'a mut Vec<u32, 32>) -> Snake <'a>{
         Snake {
-            head_1: (6, 3),
-            second_1: (5, 3),
-            tail_1: (2, 3),
-            facing_1: 3,
-            updated_1: false,
-            active_1: true,
-            head_2: (6, 10),
-            second_2: (5, 10),
-            tail_2: (2, 10),
-            facing_2: 3,
-            updated_2: false,
-            score: 0,
-            active_2: true,
+            players: [Player::new((6, 3)), Player::new((6, 10))],
+            bodies: [body_1, body_2],
             apple: (10, 3),
+            item: None,
+            slow: 0,
+            effects_shown: false,
             frame,
-            body_1,
-            body_2,
             apples,
+            walls: [0; 32],
             apples_count: 0,
             draw_init: false,
+            start: Instant::now(),
+            board: 2,
+            borders: false,
+            map: 0,
+            mode: Mode::Standing,
+            goal: 1,
+            both_die: false,
+            cpu: 0,
+            rng: Rng::new(1),
+            pitch: 5,
+            size: 24,
         }
     }
+
+    // sets up the board from the chosen options, also used to restart
     pub fn init(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
         // info!("frame: {:?}", self.frame);
         self.draw_init = false;
-        self.frame[3] = setval(self.frame[3], 3, true);
-        self.frame[3] = setval(self.frame[3], 4, true);
-        self.frame[3] = setval(self.frame[3], 5, true);
-        self.frame[3] = setval(self.frame[3], 6, true);
-        self.body_1.push_front((3, 3)).unwrap();
-        self.body_1.push_front((4, 3)).unwrap();
-        self.body_1.push_front((5, 3)).unwrap();
-        self.body_1.push_front((6, 3)).unwrap();
-        self.updated_1 = true;
-
-        self.frame[10] = setval(self.frame[10], 3, true);
-        self.frame[10] = setval(self.frame[10], 4, true);
-        self.frame[10] = setval(self.frame[10], 5, true);
-        self.frame[10] = setval(self.frame[10], 6, true);
-        self.body_2.push_front((3, 10)).unwrap();
-        self.body_2.push_front((4, 10)).unwrap();
-        self.body_2.push_front((5, 10)).unwrap();
-        self.body_2.push_front((6, 10)).unwrap();
-        self.updated_2 = true;
-
-        for i in 0..25 {
-            Rectangle::new(Point::new(OFFSET_X as i32 * 5 - 1 + 5 * i, (OFFSET_Y * 5 - 1) as i32), Size::new(1, 121))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_DARK_SEA_GREEN))
-                .draw(screen)
-                .unwrap();
-            Rectangle::new(Point::new(OFFSET_X as i32 * 5 - 1, (OFFSET_Y * 5 - 1) as i32 + 5 * i), Size::new(120, 1))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_DARK_SEA_GREEN))
-                .draw(screen)
-                .unwrap();
+        (self.pitch, self.size) = BOARDS[self.board];
+        for value in self.frame.iter_mut() {
+            *value = 0;
+        }
+        for value in self.apples.iter_mut() {
+            *value = 0;
+        }
+        self.walls = [0; 32];
+        self.apples_count = 0;
+        self.item = None;
+        self.slow = 0;
+        self.effects_shown = false;
+
+        let mut starts = [(6, 3), (6, 10)];
+        let map = MAPS[self.map].1;
+        let top = (self.size as usize - map.len()) / 2;
+        for (row, line) in map.iter().enumerate() {
+            let left = (self.size as usize - line.len()) / 2;
+            for (col, cell) in line.chars().enumerate() {
+                let (x, y) = ((left + col) as u8, (top + row) as u8);
+                match cell {
+                    '#' => self.walls[y as usize] = setval(self.walls[y as usize], x, true),
+                    '1' => starts[0] = (x, y),
+                    '2' => starts[1] = (x, y),
+                    _ => {}
+                }
+            }
+        }
+        for p in 0..2 {
+            self.players[p] = Player::new(starts[p]);
+            self.bodies[p].clear();
+            self.spawn(p);
         }
+
+        self.generate_apple();
+        self.start = Instant::now();
+        self.rng = Rng::new(RoscRng.next_u32());
+
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        self.draw_board(screen);
     }
 
-    fn update_frame(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
-        if self.active_1 {
-            match self.facing_1 {
-                0 => self.head_1.1 = if self.head_1.1 > 0 { self.head_1.1 - 1 } else { 23 },
-                1 => self.head_1.1 = if self.head_1.1 < 23 { self.head_1.1 + 1 } else { 0 },
-                2 => self.head_1.0 = if self.head_1.0 > 0 { self.head_1.0 - 1 } else { 23 },
-                3 => self.head_1.0 = if self.head_1.0 < 23 { self.head_1.0 + 1 } else { 0 },
-                _ => {
-                    self.head_1.1 = self.head_1.1;    
-                },
-            }
+    // puts a snake on its starting spot, the body trails three cells to the left of the head
+    fn spawn(&mut self, p: usize) {
+        let (x, y) = self.players[p].start;
+        for i in (0..4).rev() {
+            self.frame[y as usize] = setval(self.frame[y as usize], x - i, true);
+            self.bodies[p].push_front((x - i, y)).unwrap();
         }
+        let player = &mut self.players[p];
+        player.head = (x, y);
+        player.second = (x - 1, y);
+        player.tail = (x - 3, y);
+        player.facing = 3;
+        player.active = true;
+        player.updated = true;
+    }
 
-        if self.active_2 {
-            match self.facing_2 {
-                0 => self.head_2.1 = if self.head_2.1 > 0 { self.head_2.1 - 1 } else { 23 },
-                1 => self.head_2.1 = if self.head_2.1 < 23 { self.head_2.1 + 1 } else { 0 },
-                2 => self.head_2.0 = if self.head_2.0 > 0 { self.head_2.0 - 1 } else { 23 },
-                3 => self.head_2.0 = if self.head_2.0 < 23 { self.head_2.0 + 1 } else { 0 },
-                _ => {
-                    self.head_2.1 = self.head_2.1;
-                },
-            }
+    // brings dead snakes back once their starting spot is clear, apples lying there are lost
+    fn respawn(&mut self, p: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if self.players[p].respawn > 0 {
+            self.players[p].respawn -= 1;
+            return;
         }
-        // info!("reached part 1");
-        // info!("updated 1 {}, updated 2 {}", self.updated_1, self.updated_2);
-
-        // info!("changed head {}", self.head);
-        // for (i, value) in self.frame.iter().enumerate() {
-        //     info!("index {} {:#034b}", i, value);
-        // }
-        // for value in self.body_1.iter() {
-        //     info!("part {}, {}", value.0, value.1);
-        // }
-        if self.active_1 {
-            // info!("entered if");
-            if self.head_1 != self.apple {
-                if checkval(self.frame[self.head_1.1 as usize], self.head_1.0) == true {
-                    info!("collision detected, caused by 1st player at {}. {}", self.head_1.0, self.head_1.1);
-                    self.active_1 = false;
-                    self.updated_1 = false;
-                    for value in self.body_1.iter() {
-                        // info!("body value {}", value);
-                        self.apples[value.1 as usize] = setval(self.apples[value.1 as usize], value.0, true);
-                        self.frame[value.1 as usize] = setval(self.frame[value.1 as usize], value.0, false);
-                        self.apples_count += 1;
-                    }
-                    // info!("apples");
-                    // for (i, value) in self.apples.iter().enumerate() {
-                    //     info!("index {} {:#034b}", i, value);
-                    // }
-                    // info!("frame");
-                    // for (i, value) in self.frame.iter().enumerate() {
-                    //     info!("index {} {:#034b}", i, value);
-                    // }
-                    // return false;
-                } else if checkval(self.apples[self.head_1.1 as usize], self.head_1.0) {
-                    self.apples[self.head_1.1 as usize] = setval(self.apples[self.head_1.1 as usize], self.head_1.0, false);
-                    self.score += 100;
-                    Rectangle::new(Point::new(34, 2), Size::new(80, 8))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                        .draw(screen)
-                        .unwrap();
-                    let mut temp: String<20> = String::new();
-                    fmt::write(&mut temp, format_args!("{}", self.score)).unwrap();
-                    Text::new( &temp, Point::new(35, 8), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
-                        .draw(screen).unwrap();
-                    self.apples_count -= 1;
-                } else {
-                    match self.body_1.back() {
-                        Some(t) => self.tail_1 = *t,
-                        None => (),
-                    }
-                    self.frame[self.tail_1.1 as usize] = setval(self.frame[self.tail_1.1 as usize], self.tail_1.0, false);
-                    self.body_1.pop_back();
-                }
-            } else {
-                self.score += 100;
-                Rectangle::new(Point::new(34, 2), Size::new(80, 8))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                    .draw(screen)
-                    .unwrap();
-                let mut temp: String<20> = String::new();
-                fmt::write(&mut temp, format_args!("{}", self.score)).unwrap();
-                Text::new( &temp, Point::new(35, 8), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
-                    .draw(screen).unwrap();
-                self.generate_apple();
+        let (x, y) = self.players[p].start;
+        if (0..4).any(|i| self.blocked((x - i, y))) {
+            return;
+        }
+        for i in 0..4 {
+            if checkval(self.apples[y as usize], x - i) {
+                self.apples[y as usize] = setval(self.apples[y as usize], x - i, false);
+                self.apples_count -= 1;
             }
+        }
+        self.spawn(p);
+        if self.bodies[p].iter().any(|part| *part == self.apple) {
+            self.generate_apple();
+            self.draw_cell(self.apple, Rgb565::RED, screen);
+        }
+        for part in self.bodies[p].iter() {
+            self.draw_cell(*part, BODY_COLORS[p], screen);
+        }
+        self.draw_cell(self.players[p].head, HEAD_COLORS[p], screen);
+    }
 
-            match self.body_1.front() {
-                Some(t) => self.second_1 = *t,
-                None => (),
-            }
-            self.frame[self.head_1.1 as usize] = setval(self.frame[self.head_1.1 as usize], self.head_1.0, true);
-            self.body_1.push_front(self.head_1).unwrap();
-        }
-        // for (i, value) in self.apples.iter().enumerate() {
-        //     info!("index {} {:#034b}", i, value);
-        // }
-        // info!("frame");
-        // for (i, value) in self.frame.iter().enumerate() {
-        //     info!("index {} {:#034b}", i, value);
-        // }
-
-        
-        if self.active_2 {
-            // info!("entered active 2 if");
-            if self.head_2 != self.apple {
-                if checkval(self.frame[self.head_2.1 as usize], self.head_2.0) == true {
-                    info!("collision detected, caused by 2nd player at {}, {}", self.head_2.0, self.head_2.1);
-                    self.active_2 = false;
-                    self.updated_2 = false;
-                    for value in self.body_2.iter() {
-                        // info!("body value {}", value);
-                        self.apples[value.1 as usize] = setval(self.apples[value.1 as usize], value.0, true);
-                        self.frame[value.1 as usize] = setval(self.frame[value.1 as usize], value.0, false);
-                        self.apples_count += 1;
+    // the cell a snake moves into, past the edge it wraps around or stays put against the border walls
+    fn next_cell(&self, head: (u8, u8), facing: u8) -> (u8, u8) {
+        // against a wall the head stays on its own body, so it counts as a crash
+        snake_ai::step(head, facing, self.size, self.borders).unwrap_or(head)
+    }
+
+    // the cpu snake turns before anyone moves, it sees the board like the players do
+    fn think(&mut self) {
+        let player = self.players[1];
+        if self.cpu == 0 || !player.active {
+            return;
+        }
+        let grid = Grid {
+            frame: &self.frame,
+            walls: &self.walls,
+            apples: &self.apples,
+            apple: self.apple,
+            size: self.size,
+            borders: self.borders,
+        };
+        let tail = *self.bodies[1].back().unwrap_or(&player.head);
+        let length = self.bodies[1].len() as u16;
+        self.players[1].facing = snake_ai::think(&grid, player.head, player.facing, tail, length, self.cpu, &mut self.rng);
+    }
+
+    fn blocked(&self, cell: (u8, u8)) -> bool {
+        checkval(self.frame[cell.1 as usize], cell.0) || checkval(self.walls[cell.1 as usize], cell.0)
+    }
+
+    // ghosts can share cells with bodies, the cell only empties once no snake is left on it
+    fn release(&mut self, cell: (u8, u8)) {
+        if !self.bodies.iter().any(|body| body.iter().any(|part| *part == cell)) {
+            self.frame[cell.1 as usize] = setval(self.frame[cell.1 as usize], cell.0, false);
+        }
+    }
+
+    // the body of a crashed snake turns into apples
+    fn kill(&mut self, p: usize) {
+        info!("collision detected, caused by player {} at {}, {}", p + 1, self.players[p].head.0, self.players[p].head.1);
+        self.players[p].active = false;
+        self.players[p].updated = false;
+        self.players[p].respawn = RESPAWN_TICKS;
+        self.players[p].ghost = 0;
+        while let Some(value) = self.bodies[p].pop_front() {
+            self.apples[value.1 as usize] = setval(self.apples[value.1 as usize], value.0, true);
+            self.release(value);
+            self.apples_count += 1;
+        }
+    }
+
+    fn eat(&mut self, p: usize, points: u64, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        self.players[p].score += points;
+        self.players[p].eaten += 1;
+        self.draw_scores(screen);
+    }
+
+    fn advance(&mut self, p: usize, cell: (u8, u8), screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        // a head that stayed put ran into a border wall, not even ghosts get through
+        let crashed = cell == self.players[p].head || checkval(self.walls[cell.1 as usize], cell.0)
+            || (self.players[p].ghost == 0 && checkval(self.frame[cell.1 as usize], cell.0));
+        self.players[p].head = cell;
+        let mut apple = false;
+        let mut grow = false;
+        if crashed {
+            self.kill(p);
+            return;
+        } else if cell == self.apple {
+            self.eat(p, 100, screen);
+            apple = true;
+            grow = true;
+        } else if checkval(self.apples[cell.1 as usize], cell.0) {
+            self.apples[cell.1 as usize] = setval(self.apples[cell.1 as usize], cell.0, false);
+            self.apples_count -= 1;
+            self.eat(p, 100, screen);
+            grow = true;
+        }
+        match self.item {
+            Some((item, spot, _)) if spot == cell => {
+                self.item = None;
+                match item {
+                    Item::Slow => self.slow = SLOW_TICKS,
+                    Item::Golden => {
+                        self.eat(p, 500, screen);
+                        grow = true;
                     }
-                    
-                    // info!("apples");
-                    // for (i, value) in self.apples.iter().enumerate() {
-                    //     info!("index {} {:#034b}", i, value);
-                    // }
-                    // info!("frame");
-                    // for (i, value) in self.frame.iter().enumerate() {
-                    //     info!("index {} {:#034b}", i, value);
-                    // }
-                    // return false;
-                } else if checkval(self.apples[self.head_2.1 as usize], self.head_2.0) {
-                    self.apples[self.head_2.1 as usize] = setval(self.apples[self.head_2.1 as usize], self.head_2.0, false);
-                    self.score += 100;
-                    Rectangle::new(Point::new(34, 2), Size::new(80, 8))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                        .draw(screen)
-                        .unwrap();
-                    let mut temp: String<20> = String::new();
-                    fmt::write(&mut temp, format_args!("{}", self.score)).unwrap();
-                    Text::new( &temp, Point::new(35, 8), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
-                        .draw(screen).unwrap();
-                    self.apples_count -= 1;
-                } else {
-                    match self.body_2.back() {
-                        Some(t) => self.tail_2 = *t,
-                        None => (),
+                    Item::Shrink => {
+                        for _ in 0..SHRINK {
+                            if self.bodies[p].len() <= 4 {
+                                break;
+                            }
+                            match self.bodies[p].pop_back() {
+                                Some(t) => {
+                                    self.release(t);
+                                    if !self.blocked(t) {
+                                        self.draw_cell(t, Rgb565::BLACK, screen);
+                                    }
+                                }
+                                None => (),
+                            }
+                        }
                     }
-                    self.frame[self.tail_2.1 as usize] = setval(self.frame[self.tail_2.1 as usize], self.tail_2.0, false);
-                    self.body_2.pop_back();
+                    Item::Ghost => self.players[p].ghost = GHOST_TICKS,
                 }
-            } else {
-                self.score += 100;
-                Rectangle::new(Point::new(34, 2), Size::new(80, 8))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                    .draw(screen)
-                    .unwrap();
-                let mut temp: String<20> = String::new();
-                fmt::write(&mut temp, format_args!("{}", self.score)).unwrap();
-                Text::new( &temp, Point::new(35, 8), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
-                    .draw(screen).unwrap();
-                self.generate_apple();
             }
-            match self.body_2.front() {
-                Some(t) => self.second_2 = *t,
+            _ => {}
+        }
+        if !grow {
+            match self.bodies[p].pop_back() {
+                Some(t) => {
+                    self.players[p].tail = t;
+                    self.release(t);
+                }
                 None => (),
             }
-            self.frame[self.head_2.1 as usize] = setval(self.frame[self.head_2.1 as usize], self.head_2.0, true);
-            self.body_2.push_front(self.head_2).unwrap();
         }
+        match self.bodies[p].front() {
+            Some(t) => self.players[p].second = *t,
+            None => (),
+        }
+        self.frame[cell.1 as usize] = setval(self.frame[cell.1 as usize], cell.0, true);
+        self.bodies[p].push_front(cell).unwrap();
+        if apple {
+            self.generate_apple();
+        }
+    }
 
-        // info!("frame");
-        // for (i, value) in self.frame.iter().enumerate() {
-        //     info!("index {} {:#034b}", i, value);
-        // }
-        // loop{}
-        // apple logic?
-        // if apple => don't remove tail
+    fn update_frame(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        self.think();
+        let mut next = [(0, 0); 2];
+        for p in 0..2 {
+            if self.players[p].active {
+                next[p] = self.next_cell(self.players[p].head, self.players[p].facing);
+            }
+        }
 
+        // heads meeting in the same cell or swapping places, the shorter snake loses
+        let [first, second] = self.players;
+        if first.active && second.active && (next[0] == next[1] || (next[0] == second.head && next[1] == first.head)) {
+            let lengths = [self.bodies[0].len(), self.bodies[1].len()];
+            for p in 0..2 {
+                if self.both_die || lengths[p] <= lengths[1 - p] {
+                    self.kill(p);
+                }
+            }
+        }
 
-        
-        // info!("head_1 value {}", self.head_1);
-        // info!("changed frame (head_1 added) to {:#034b} at index {}", self.frame[self.head_1.1 as usize], self.head_1.0);
-        // info!("tail_1 value {}", self.tail_1);
-        // info!("changed frame (tail_1 removed) from {:#034b} at index {}", self.frame[self.tail_1.1 as usize], self.tail_1.0);
+        for p in 0..2 {
+            if self.players[p].active {
+                self.advance(p, next[p], screen);
+            }
+        }
+        // info!("head_1 value {}", self.players[0].head);
+        // info!("head_2 value {}", self.players[1].head);
 
-        // info!("head_2 value{}", self.head_2);
-        // info!("changed frame (head_2 added) to {:#034b} at index {}", self.frame[self.head_2.1 as usize], self.head_2.0);
-        // info!("tail_2 value {}", self.tail_2);
-        // info!("changed frame (tail_2 removed) from {:#034b} at index {}", self.frame[self.tail_2.1 as usize], self.tail_2.0);
-        // info!("deque len {}" , self.body.len());
-        return self.active_1 || self.active_2;
-    }
-    
-    fn generate_apple(&mut self) {
-        let mut empty_spaces: Vec<(u8, u8), 576> = Vec::new();
-        for i in 0..23 as u8 {
-            for j in 0..23 as u8 {
-                if checkval(self.frame[i as usize], j) == false && checkval(self.apples[i as usize], j) == false{
-                    empty_spaces.push((i, j)).unwrap();
+        if self.mode != Mode::Standing {
+            for p in 0..2 {
+                if !self.players[p].active {
+                    self.respawn(p, screen);
                 }
             }
         }
-        let mut rng = RoscRng;
-        match empty_spaces.choose(&mut rng) {
-            Some(t) => self.apple = *t,
-            None => {} 
-        }
+        self.update_items(screen);
     }
 
-    fn draw(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
-        if self.draw_init == false {
-            let mut temp: String<20> = String::new();
-            fmt::write(&mut temp, format_args!("Score: {}", self.score)).unwrap();
-            Text::new(&temp, Point::new(0, 8), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
-                .draw(screen).unwrap();
-            self.draw_init = true;
+    // timed effects run out, items on the board expire and new ones show up now and then
+    fn update_items(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        self.slow = self.slow.saturating_sub(1);
+        for player in self.players.iter_mut() {
+            player.ghost = player.ghost.saturating_sub(1);
         }
-        if self.active_1 == false || self.active_2 == false {
-            for (index, value) in self.apples.iter().enumerate() {
-                for j in 0..31 {
-                    if checkval(*value, j) {
-                        Rectangle::new(Point::new((j + OFFSET_X) as i32 * 5, (index as u8 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                            .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
-                            .draw(screen)
-                            .unwrap();
+        match self.item {
+            Some((_, spot, 0)) => {
+                self.item = None;
+                if !self.blocked(spot) && !checkval(self.apples[spot.1 as usize], spot.0) {
+                    self.draw_cell(spot, Rgb565::BLACK, screen);
+                }
+            }
+            Some((item, spot, ticks)) => self.item = Some((item, spot, ticks - 1)),
+            None => {
+                if self.rng.below(ITEM_CHANCE) == 0 {
+                    let item = [Item::Slow, Item::Golden, Item::Shrink, Item::Ghost][self.rng.below(4) as usize];
+                    match self.empty_cell() {
+                        Some(spot) => self.item = Some((item, spot, ITEM_TICKS)),
+                        None => {}
                     }
                 }
             }
         }
-        Rectangle::new(Point::new((self.apple.0 + OFFSET_X) as i32 * 5, (self.apple.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-            .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
-            .draw(screen)
-            .unwrap();
+        if self.slow > 0 || self.players.iter().any(|player| player.ghost > 0) || self.effects_shown {
+            self.draw_effects(screen);
+        }
+    }
 
-        if self.active_1 {
-            Rectangle::new(Point::new((self.head_1.0 + OFFSET_X) as i32 * 5, (self.head_1.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
-                .draw(screen)
-                .unwrap();
+    // milliseconds until the next tick
+    fn tick(&self) -> u64 {
+        let longest = self.bodies.iter().map(|body| body.len()).max().unwrap_or(0);
+        let tick = BASE_TICK - (longest.saturating_sub(4) as u64 * TICK_STEP).min(BASE_TICK - MIN_TICK);
+        if self.slow > 0 { tick * 2 } else { tick }
+    }
 
-            Rectangle::new(Point::new((self.second_1.0 + OFFSET_X) as i32 * 5, (self.second_1.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_LIME_GREEN))
-                .draw(screen)
-                .unwrap();
-            
-            if self.head_1 != self.tail_1 {
-                Rectangle::new(Point::new((self.tail_1.0 + OFFSET_X) as i32 * 5, (self.tail_1.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                .draw(screen)
-                .unwrap();
+    // the winner once the round is over, None inside for a draw
+    fn finished(&self) -> Option<Option<usize>> {
+        let [first, second] = self.players;
+        let leader = |a: u64, b: u64| if a > b { Some(0) } else if b > a { Some(1) } else { None };
+        match self.mode {
+            Mode::Standing => match (first.active, second.active) {
+                (true, true) => None,
+                (true, false) => Some(Some(0)),
+                (false, true) => Some(Some(1)),
+                (false, false) => Some(None),
+            },
+            Mode::Race => {
+                let goal = APPLE_GOALS[self.goal];
+                if first.eaten >= goal || second.eaten >= goal {
+                    Some(leader(first.eaten as u64, second.eaten as u64))
+                } else {
+                    None
+                }
+            }
+            Mode::Timed => {
+                if self.elapsed() >= TIME_GOALS[self.goal] {
+                    Some(leader(first.score, second.score))
+                } else {
+                    None
+                }
             }
-            match self.body_1.back() {
-                Some(t) => self.tail_1 = *t,
-                None => (),
-            };
-            self.updated_1 = false;
         }
-        if self.active_2 {
-            Rectangle::new(Point::new((self.head_2.0 + OFFSET_X) as i32 * 5, (self.head_2.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
-                .draw(screen)
-                .unwrap();
+    }
 
-            Rectangle::new(Point::new((self.second_2.0 + OFFSET_X) as i32 * 5, (self.second_2.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::YELLOW))
-                .draw(screen)
-                .unwrap();
+    fn elapsed(&self) -> u16 {
+        (Instant::now() - self.start).as_secs().min(u16::MAX as u64) as u16
+    }
 
-            if self.head_2 != self.tail_2 {
-                Rectangle::new(Point::new((self.tail_2.0 + OFFSET_X) as i32 * 5, (self.tail_2.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                    .draw(screen)
-                    .unwrap();
-            }
-            match self.body_2.back() {
-                Some(t) => self.tail_2 = *t,
-                None => (),
-            };
-            self.updated_2 = false;
+    fn generate_apple(&mut self) {
+        match self.empty_cell() {
+            Some(t) => self.apple = t,
+            None => {}
         }
-        // info!("the tail is {}", self.tail);
-
+    }
 
-        // info!("changed tail to {}", self.tail);
+    // a random cell without snakes, walls, apples or items
+    fn empty_cell(&self) -> Option<(u8, u8)> {
+        let mut empty_spaces: Vec<(u8, u8), 900> = Vec::new();
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let item = match self.item {
+                    Some((_, spot, _)) => spot == (x, y),
+                    None => false,
+                };
+                if !self.blocked((x, y)) && checkval(self.apples[y as usize], x) == false && self.apple != (x, y) && !item {
+                    empty_spaces.push((x, y)).unwrap();
+                }
+            }
+        }
+        let mut rng = RoscRng;
+        empty_spaces.choose(&mut rng).copied()
     }
 
-    async fn redraw(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
-        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
-            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+    fn draw_cell(&self, cell: (u8, u8), color: Rgb565, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let pitch = self.pitch as i32;
+        Rectangle::new(Point::new(BOARD_X + cell.0 as i32 * pitch, BOARD_Y + cell.1 as i32 * pitch), Size::new(pitch as u32 - 1, pitch as u32 - 1))
+            .into_styled(PrimitiveStyle::with_fill(color))
             .draw(screen)
             .unwrap();
-        for i in 0..25 {
-            Rectangle::new(Point::new(OFFSET_X as i32 * 5 - 1 + 5 * i, (OFFSET_Y * 5 - 1) as i32), Size::new(1, 121))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_DARK_SEA_GREEN))
+    }
+
+    // grid lines, the border walls and the obstacles of the map
+    fn draw_board(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let pitch = self.pitch as i32;
+        let length = self.size as u32 * pitch as u32 + 1;
+        for i in 0..=self.size as i32 {
+            let color = if self.borders && (i == 0 || i == self.size as i32) { Rgb565::WHITE } else { Rgb565::CSS_DARK_SEA_GREEN };
+            Rectangle::new(Point::new(BOARD_X - 1 + pitch * i, BOARD_Y - 1), Size::new(1, length))
+                .into_styled(PrimitiveStyle::with_fill(color))
                 .draw(screen)
                 .unwrap();
-            Rectangle::new(Point::new(OFFSET_X as i32 * 5 - 1, (OFFSET_Y * 5 - 1) as i32 + 5 * i), Size::new(120, 1))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_DARK_SEA_GREEN))
+            Rectangle::new(Point::new(BOARD_X - 1, BOARD_Y - 1 + pitch * i), Size::new(length, 1))
+                .into_styled(PrimitiveStyle::with_fill(color))
                 .draw(screen)
                 .unwrap();
         }
-        
-        if self.active_1 && self.active_2 == false {
-            for (index, value) in self.apples.iter().enumerate() {
-                for j in 0..31 {
-                    if checkval(*value, j) {
-                        Rectangle::new(Point::new((j + OFFSET_X) as i32 * 5, (index as u8 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                            .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
-                            .draw(screen)
-                            .unwrap();
-                    }
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if checkval(self.walls[y as usize], x) {
+                    self.draw_cell((x, y), Rgb565::CSS_GRAY, screen);
                 }
             }
         }
-        Rectangle::new(Point::new((self.apple.0 + OFFSET_X) as i32 * 5, (self.apple.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-            .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
-            .draw(screen)
-            .unwrap();
+    }
 
-        if self.active_1 {
-            for part in self.body_1.iter() {
-                if *part != self.head_1 && *part != self.second_1 && *part != self.tail_1 {
-                    Rectangle::new(Point::new((part.0 + OFFSET_X) as i32 * 5, (part.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
-                        .draw(screen)
-                        .unwrap();    
+    fn draw_apples(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        for (index, value) in self.apples.iter().enumerate() {
+            for j in 0..self.size {
+                if checkval(*value, j) {
+                    self.draw_cell((j, index as u8), Rgb565::RED, screen);
                 }
             }
-            Rectangle::new(Point::new((self.head_1.0 + OFFSET_X) as i32 * 5, (self.head_1.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
-                .draw(screen)
-                .unwrap();
+        }
+    }
 
-            Rectangle::new(Point::new((self.second_1.0 + OFFSET_X) as i32 * 5, (self.second_1.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_LIME_GREEN))
+    // one score per player in their snake's colour, and the goal of the round under them
+    fn draw_scores(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 22))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<24> = String::new();
+        for p in 0..2 {
+            temp.clear();
+            fmt::write(&mut temp, format_args!("{} {}", self.name(p), self.players[p].score)).unwrap();
+            Text::new(&temp, Point::new(p as i32 * 64, 8), MonoTextStyle::new(&FONT_5X8, BODY_COLORS[p]))
+                .draw(screen).unwrap();
+        }
+        temp.clear();
+        match self.mode {
+            Mode::Standing => fmt::write(&mut temp, format_args!("last snake standing")).unwrap(),
+            Mode::Race => fmt::write(&mut temp, format_args!("apples {} - {} of {}", self.players[0].eaten, self.players[1].eaten, APPLE_GOALS[self.goal])).unwrap(),
+            Mode::Timed => {
+                let left = TIME_GOALS[self.goal].saturating_sub(self.elapsed());
+                fmt::write(&mut temp, format_args!("time {}:{:02}", left / 60, left % 60)).unwrap()
+            }
+        }
+        Text::new(&temp, Point::new(0, 19), MonoTextStyle::new(&FONT_5X8, Rgb565::CSS_GRAY))
+            .draw(screen).unwrap();
+    }
+
+    // a bar for every running effect under the scores, shorter as it runs out
+    fn draw_effects(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 22), Size::new(128, 11))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let effects = [
+            ("slow", self.slow, item_color(Item::Slow)),
+            ("G1", self.players[0].ghost, BODY_COLORS[0]),
+            ("G2", self.players[1].ghost, BODY_COLORS[1]),
+        ];
+        self.effects_shown = false;
+        for (index, (label, ticks, color)) in effects.iter().enumerate() {
+            if *ticks == 0 {
+                continue;
+            }
+            self.effects_shown = true;
+            let x = index as i32 * 42;
+            Text::new(label, Point::new(x, 30), MonoTextStyle::new(&FONT_5X8, *color))
+                .draw(screen).unwrap();
+            Rectangle::new(Point::new(x + 21, 25), Size::new(*ticks as u32, 4))
+                .into_styled(PrimitiveStyle::with_fill(*color))
                 .draw(screen)
                 .unwrap();
+        }
+    }
 
-            if self.head_1 != self.tail_1 {
-                Rectangle::new(Point::new((self.tail_1.0 + OFFSET_X) as i32 * 5, (self.tail_1.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                    .draw(screen)
-                    .unwrap();
-            }
+    fn draw(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if self.draw_init == false {
+            self.draw_scores(screen);
+            self.draw_init = true;
+        }
+        if self.players.iter().any(|player| !player.active) {
+            self.draw_apples(screen);
+        }
+        self.draw_cell(self.apple, Rgb565::RED, screen);
+        match self.item {
+            Some((item, spot, _)) => self.draw_cell(spot, item_color(item), screen),
+            None => {}
         }
 
-        if self.active_2 {
-            for part in self.body_2.iter() {
-                if *part != self.head_2 && *part != self.second_2 && *part != self.tail_2 {
-                    Rectangle::new(Point::new((part.0 + OFFSET_X) as i32 * 5, (part.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::YELLOW))
-                        .draw(screen)
-                        .unwrap();    
+        for p in 0..2 {
+            let player = self.players[p];
+            if player.active {
+                self.draw_cell(player.head, HEAD_COLORS[p], screen);
+                self.draw_cell(player.second, BODY_COLORS[p], screen);
+                if player.head != player.tail && !self.blocked(player.tail) {
+                    self.draw_cell(player.tail, Rgb565::BLACK, screen);
                 }
+                match self.bodies[p].back() {
+                    Some(t) => self.players[p].tail = *t,
+                    None => (),
+                };
+                self.players[p].updated = false;
             }
-            Rectangle::new(Point::new((self.head_2.0 + OFFSET_X) as i32 * 5, (self.head_2.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
-                .draw(screen)
-                .unwrap();
+        }
+        // info!("the tail is {}", self.tail);
+    }
 
-            Rectangle::new(Point::new((self.second_2.0 + OFFSET_X) as i32 * 5, (self.second_2.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::YELLOW))
-                .draw(screen)
-                .unwrap();
+    async fn redraw(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        self.draw_board(screen);
+        self.draw_init = false;
+
+        self.draw_apples(screen);
+        self.draw_cell(self.apple, Rgb565::RED, screen);
+        match self.item {
+            Some((item, spot, _)) => self.draw_cell(spot, item_color(item), screen),
+            None => {}
+        }
+        self.effects_shown = true;
 
-            if self.head_2 != self.tail_2 {
-                Rectangle::new(Point::new((self.tail_2.0 + OFFSET_X) as i32 * 5, (self.tail_2.1 + OFFSET_Y) as i32 * 5), Size::new(4, 4))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                    .draw(screen)
-                    .unwrap();
+        for p in 0..2 {
+            if self.players[p].active {
+                for part in self.bodies[p].iter() {
+                    self.draw_cell(*part, BODY_COLORS[p], screen);
+                }
+                self.draw_cell(self.players[p].head, HEAD_COLORS[p], screen);
             }
         }
     }
-    
+
+    // snakes can't turn back onto themselves, and only turn once per tick
+    fn turn(&mut self, p: usize, facing: u8) {
+        let player = &mut self.players[p];
+        if player.facing != facing ^ 1 && player.updated == false {
+            player.facing = facing;
+            player.updated = true;
+        }
+    }
+
     fn handle_input(&mut self, input: &Input) -> bool {
         match input {
             Input::Select => {}
             Input::Back => {
                 return false
             }
-            Input::Up => if self.facing_1 != 1 && self.updated_1 == false { self.facing_1 = 0; self.updated_1 = true },
-            Input::Down => if self.facing_1 != 0 && self.updated_1 == false { self.facing_1 = 1; self.updated_1 = true },
-            Input::Left => if self.facing_1 != 3 && self.updated_1 == false { self.facing_1 = 2; self.updated_1 = true },
-            Input::Right => if self.facing_1 != 2 && self.updated_1 == false { self.facing_1 = 3; self.updated_1 = true },
-            
-            Input::Up2 => if self.facing_2 != 1 && self.updated_2 == false { self.facing_2 = 0; self.updated_2 = true },
-            Input::Down2 => if self.facing_2 != 0 && self.updated_2 == false { self.facing_2 = 1; self.updated_2 = true },
-            Input::Left2 => if self.facing_2 != 3 && self.updated_2 == false { self.facing_2 = 2; self.updated_2 = true },
-            Input::Right2 => if self.facing_2 != 2 && self.updated_2 == false { self.facing_2 = 3; self.updated_2 = true },
+            Input::Up => self.turn(0, 0),
+            Input::Down => self.turn(0, 1),
+            Input::Left => self.turn(0, 2),
+            Input::Right => self.turn(0, 3),
+
+            // the cpu snake ignores the second controller
+            Input::Up2 if self.cpu == 0 => self.turn(1, 0),
+            Input::Down2 if self.cpu == 0 => self.turn(1, 1),
+            Input::Left2 if self.cpu == 0 => self.turn(1, 2),
+            Input::Right2 if self.cpu == 0 => self.turn(1, 3),
             _ => {}
         }
         true
     }
 
+    // pre-game settings, up and down pick a row, left and right change it, false when the players leave
+    async fn options(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let mut selected = 0;
+        self.draw_options(selected, screen);
+        loop {
+            Timer::after(Duration::from_millis(100)).await;
+            INPUT_SIGNAL.reset();
+            match INPUT_SIGNAL.wait().await {
+                Input::Up | Input::Up2 => {
+                    selected = if selected > 0 { selected - 1 } else { OPTIONS - 1 };
+                }
+                Input::Down | Input::Down2 => {
+                    selected = if selected + 1 < OPTIONS { selected + 1 } else { 0 };
+                }
+                input @ (Input::Left | Input::Left2 | Input::Right | Input::Right2) => {
+                    let forward = input == Input::Right || input == Input::Right2;
+                    match selected {
+                        0 => self.board = cycle(self.board, BOARDS.len(), forward),
+                        1 => self.borders = !self.borders,
+                        2 => self.map = cycle(self.map, MAPS.len(), forward),
+                        3 => {
+                            let modes = [Mode::Standing, Mode::Race, Mode::Timed];
+                            let index = modes.iter().position(|mode| *mode == self.mode).unwrap_or(0);
+                            self.mode = modes[cycle(index, modes.len(), forward)];
+                        }
+                        4 => self.goal = cycle(self.goal, APPLE_GOALS.len(), forward),
+                        5 => self.both_die = !self.both_die,
+                        _ => self.cpu = cycle(self.cpu as usize, snake_ai::HARD as usize + 1, forward) as u8,
+                    }
+                }
+                Input::Select => return true,
+                Input::Back => return false,
+                _ => continue,
+            }
+            self.draw_options(selected, screen);
+        }
+    }
+
+    fn draw_options(&self, selected: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        Text::new("Snake", Point::new(39, 30), MonoTextStyle::new(&FONT_10X20, Rgb565::RED))
+            .draw(screen).unwrap();
+        for row in 0..OPTIONS {
+            let mut temp: String<24> = String::new();
+            match row {
+                0 => fmt::write(&mut temp, format_args!("Board: < {}x{} >", BOARDS[self.board].1, BOARDS[self.board].1)).unwrap(),
+                1 => fmt::write(&mut temp, format_args!("Edges: < {} >", if self.borders { "Walls" } else { "Wrap" })).unwrap(),
+                2 => fmt::write(&mut temp, format_args!("Map: < {} >", MAPS[self.map].0)).unwrap(),
+                3 => fmt::write(&mut temp, format_args!("Mode: < {} >", match self.mode {
+                    Mode::Standing => "Survive",
+                    Mode::Race => "Race",
+                    Mode::Timed => "Timed",
+                })).unwrap(),
+                4 => match self.mode {
+                    Mode::Standing => fmt::write(&mut temp, format_args!("Goal: -")).unwrap(),
+                    Mode::Race => fmt::write(&mut temp, format_args!("Goal: < {} apples >", APPLE_GOALS[self.goal])).unwrap(),
+                    Mode::Timed => fmt::write(&mut temp, format_args!("Goal: < {}:{:02} >", TIME_GOALS[self.goal] / 60, TIME_GOALS[self.goal] % 60)).unwrap(),
+                },
+                5 => fmt::write(&mut temp, format_args!("Head-on: < {} >", if self.both_die { "Both" } else { "Shorter" })).unwrap(),
+                _ => fmt::write(&mut temp, format_args!("P2: < {} >", match self.cpu {
+                    0 => "Human",
+                    snake_ai::EASY => "CPU easy",
+                    snake_ai::NORMAL => "CPU normal",
+                    _ => "CPU hard",
+                })).unwrap(),
+            }
+            Text::new(&temp, Point::new(4, 50 + row as i32 * 13), MonoTextStyle::new(&FONT_6X10, if row == selected { Rgb565::WHITE } else { Rgb565::CSS_GRAY }))
+                .draw(screen).unwrap();
+        }
+        Text::new("e: play q: exit", Point::new(19, 150), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
+            .draw(screen).unwrap();
+    }
+
+    fn name(&self, p: usize) -> &'static str {
+        match p {
+            0 => "P1",
+            _ if self.cpu > 0 => "CPU",
+            _ => "P2",
+        }
+    }
+
+    fn draw_results(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 120), Size::new(128, 40))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<24> = String::new();
+        for p in 0..2 {
+            temp.clear();
+            fmt::write(&mut temp, format_args!("{}: {} ({} apples)", self.name(p), self.players[p].score, self.players[p].eaten)).unwrap();
+            Text::new(&temp, Point::new(4, 135 + p as i32 * 15), MonoTextStyle::new(&FONT_6X10, BODY_COLORS[p]))
+                .draw(screen).unwrap();
+        }
+    }
+
     pub async fn game_loop(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if self.options(screen).await == false {
+            set_current(0);
+            return;
+        }
+        self.init(screen);
+        let mut seconds = 0;
+        // ticks keep to a fixed beat, turning doesn't move the snakes any sooner
+        let mut next_tick = Instant::now() + Duration::from_millis(self.tick());
         loop {
-            match select(INPUT_SIGNAL.wait(), Timer::after(Duration::from_millis(250))).await {
+            match select(INPUT_SIGNAL.wait(), Timer::at(next_tick)).await {
                 Either::First(input) => {
-                    if !(self.updated_1 || self.updated_2) {
-                        if self.handle_input(&input) == false {
-                            // create pause menu
-                            let mut pause_menu: Menu<'_> = Menu::init("Pause menu", &[MenuOption::Resume, MenuOption::Exit], screen);
-                            let result: MenuOption = pause_menu.menu_loop(screen).await;
-                            info!("obtained result... somehow?");
-                            match result {
-                                MenuOption::Resume | MenuOption::None => {
-                                    self.redraw(screen).await;
-                                    Timer::after(Duration::from_millis(100)).await;
-                                    INPUT_SIGNAL.reset();
-                                },
-                                MenuOption::Exit => {
-                                    unsafe { CURRENT = 0 };
-                                    return;
-                                }
-                                _ => {}
+                    if self.handle_input(&input) == false {
+                        // the clock stops while the game is paused
+                        let paused = Instant::now();
+                        // create pause menu
+                        let mut pause_menu: Menu<'_> = Menu::init("Pause menu", &[MenuOption::Resume, MenuOption::Exit], screen);
+                        let result: MenuOption = pause_menu.menu_loop(screen).await;
+                        info!("obtained result... somehow?");
+                        self.start += Instant::now() - paused;
+                        match result {
+                            MenuOption::Resume | MenuOption::None => {
+                                self.redraw(screen).await;
+                                Timer::after(Duration::from_millis(100)).await;
+                                INPUT_SIGNAL.reset();
+                                next_tick = Instant::now() + Duration::from_millis(self.tick());
+                            },
+                            MenuOption::Exit => {
+                                set_current(0);
+                                return;
                             }
+                            _ => {}
                         }
                     }
+                    continue;
                 }
-                _ => {}
+                Either::Second(_) => {
+                    // the slow item and the speed ramp both go through tick()
+                    next_tick = (next_tick + Duration::from_millis(self.tick())).max(Instant::now());
+                }
+            }
+            self.update_frame(screen);
+            if self.mode == Mode::Timed && self.elapsed() != seconds {
+                seconds = self.elapsed();
+                self.draw_scores(screen);
             }
-            if self.update_frame(screen) == true {
-                self.draw(screen);
-            } else {
-                info!("game over!");
-                // create pause menu
-                let mut end_menu: Menu<'_> = Menu::init("Game over!", &[MenuOption::Restart, MenuOption::Exit], screen);
-                let result: MenuOption = end_menu.menu_loop(screen).await;
-                info!("obtained result... somehow?");
-                match result {
-                    MenuOption::Restart | MenuOption::None => {
-                        unsafe { CURRENT = 1 }; 
-                        Timer::after(Duration::from_millis(100)).await;
-                        INPUT_SIGNAL.reset();
-                        return;
-                    },
-                    MenuOption::Exit => {
-                        unsafe { CURRENT = 0 };
-                        return;
+            match self.finished() {
+                None => self.draw(screen),
+                Some(winner) => {
+                    info!("game over!");
+                    let title = match winner {
+                        Some(0) => "P1 wins!",
+                        Some(_) if self.cpu > 0 => "CPU wins!",
+                        Some(_) => "P2 wins!",
+                        None => "Draw!",
+                    };
+                    let mut end_menu: Menu<'_> = Menu::init(title, &[MenuOption::Restart, MenuOption::Options, MenuOption::Exit], screen);
+                    self.draw_results(screen);
+                    let result: MenuOption = end_menu.menu_loop(screen).await;
+                    info!("obtained result... somehow?");
+                    match result {
+                        MenuOption::Restart | MenuOption::None => {
+                            self.init(screen);
+                            Timer::after(Duration::from_millis(100)).await;
+                            INPUT_SIGNAL.reset();
+                        },
+                        MenuOption::Options => {
+                            if self.options(screen).await == false {
+                                set_current(0);
+                                return;
+                            }
+                            self.init(screen);
+                        }
+                        MenuOption::Exit => {
+                            set_current(0);
+                            return;
+                        }
+                        _ => {}
                     }
-                    _ => {}
+                    next_tick = Instant::now() + Duration::from_millis(self.tick());
                 }
             }
         }
     }
-        
-}
\ No newline at end of file
+
+}
+
+// steps through a list of choices, wrapping at both ends
+fn cycle(value: usize, count: usize, forward: bool) -> usize {
+    if forward {
+        (value + 1) % count
+    } else {
+        (value + count - 1) % count
+    }
+}
@@ -0,0 +1,76 @@
+// Snake obstacle maps, a name and one string per row, '#' is a wall and '.' is open ground
+// maps are centred on the board, so on the bigger boards there is open ground around them,
+// none of them is wider or taller than the smallest board (15x15)
+// '1' and '2' mark where the heads of the snakes start, facing right with their bodies trailing
+// three cells to the left, maps without them use the usual starting spots
+pub const MAPS: [(&str, &[&str]); 5] = [
+    ("Open", &[]),
+    ("Pillars", &[
+        "...............",
+        ".##.........##.",
+        ".##.........##.",
+        "....1..........",
+        "...............",
+        "...............",
+        "......###......",
+        "......###......",
+        "......###......",
+        "...............",
+        "...............",
+        "....2..........",
+        ".##.........##.",
+        ".##.........##.",
+        "...............",
+    ]),
+    ("Cross", &[
+        "...............",
+        "....1..........",
+        ".......#.......",
+        ".......#.......",
+        ".......#.......",
+        ".......#.......",
+        ".......#.......",
+        "...#########...",
+        ".......#.......",
+        ".......#.......",
+        ".......#.......",
+        ".......#.......",
+        ".......#.......",
+        "....2..........",
+        "...............",
+    ]),
+    ("Rooms", &[
+        "...............",
+        ".#####...#####.",
+        ".#...........#.",
+        ".#...1.......#.",
+        ".#...........#.",
+        "...............",
+        "...............",
+        "...............",
+        "...............",
+        "...............",
+        ".#...........#.",
+        ".#...2.......#.",
+        ".#...........#.",
+        ".#####...#####.",
+        "...............",
+    ]),
+    ("Tunnels", &[
+        "...............",
+        "...............",
+        "....1..........",
+        "...............",
+        "###########....",
+        "...............",
+        "...............",
+        "...............",
+        "...............",
+        "...............",
+        "....###########",
+        "...............",
+        "....2..........",
+        "...............",
+        "...............",
+    ]),
+];
@@ -1,5 +1,3 @@
-#[allow(static_mut_refs)]
-
 use embassy_embedded_hal::shared_bus::blocking::spi::SpiDevice;
 
 use embassy_sync::blocking_mutex::raw::NoopRawMutex;
@@ -14,14 +12,16 @@ use embedded_graphics::{
         PrimitiveStyle, Rectangle
     }, text::Text
 };
-use embassy_time::{Duration, Timer};
+use embassy_time::{Duration, Instant, Timer};
+use embassy_futures::select::{select, Either};
 
 use heapless::{
     Vec, String
 };
 
-use crate::{menu::selector::Menu, INPUT_SIGNAL};
-use crate::CURRENT;
+use crate::{menu::selector::Menu, INPUT_SIGNAL, EXPORT_SIGNAL, EXPORT_LEN};
+use crate::set_current;
+use crate::storage;
 
 use {defmt_rtt as _, panic_probe as _};
 use defmt::*;
@@ -30,20 +30,150 @@ use rust_pico_console::{Input, MenuOption};
 use core::fmt;
 
 const OFFSET_X: i32 = 28;
+// the board is drawn under the counters, levels up to 32x32 cells
+const BOARD_WIDTH: i32 = 128;
+const BOARD_HEIGHT: i32 = 160 - OFFSET_X;
+const CELL_SIZE: i32 = 9;
+// smaller cells than this are hard to see, bigger levels use the camera unless the overview is on
+const MIN_CELL_SIZE: i32 = 6;
+const OVERVIEW_CELL_SIZE: i32 = 4;
+// how close a player gets to the edge of the screen before the camera moves
+const CAMERA_MARGIN: u8 = 2;
+const LEVELS: u8 = 13;
+// the last levels are bigger than the screen
+const LARGE_LEVELS_START: u8 = 12;
+// the editor has no camera, 15 columns of 9 pixel cells don't fit the 128 pixel wide screen
+const EDIT_ROWS: u8 = 14;
+const EDIT_COLS: u8 = 14;
+const LEVEL_MAGIC: u8 = 0x5b;
+// magic, rows, cols, then two cells per byte
+const LEVEL_BYTES: usize = 3 + (EDIT_ROWS as usize * EDIT_COLS as usize + 1) / 2;
+const REPLAY_MAGIC: u8 = 0x5c;
+const SOLUTION_LEN: usize = 1000;
+// magic, moves, pushes, seconds and the length of the solution, then the LURD string
+const REPLAY_HEADER: usize = 9;
+const REPLAY_BYTES: usize = REPLAY_HEADER + SOLUTION_LEN;
+
+// cell types, the game only keeps floor, walls and boxes in the frame
+// goals and player starts are only stored in saved levels and in the editor
+const FLOOR: u8 = 0;
+const WALL: u8 = 1;
+const BOX: u8 = 2;
+const GOAL: u8 = 3;
+const BOX_ON_GOAL: u8 = 4;
+const PLAYER1: u8 = 5;
+const PLAYER2: u8 = 6;
+const CELL_TYPES: u8 = 7;
+// boxes owned by a player, only used in versus levels
+const BOX1: u8 = 7;
+const BOX2: u8 = 8;
+
+// levels bigger than the screen, one string per row
+// '#' wall, '$' box, '.' goal, '*' box on a goal, '1' and '2' player starts
+const LARGE_LEVELS: [&[&str]; 2] = [
+    &[
+        "######################",
+        "#1   #      #       2#",
+        "# $  #  ##  #  ###   #",
+        "#    $      $    #   #",
+        "###  ####  ###   # $ #",
+        "#      #     #       #",
+        "# .  . #  $  #####  ##",
+        "#      #     #      .#",
+        "####  ####  ##  ##   #",
+        "#         .     #    #",
+        "#  ###  ######  #    #",
+        "#  #            #    #",
+        "#  #  ####      ###  #",
+        "#     #  #   $  .  . #",
+        "######################",
+    ],
+    &[
+        "########################",
+        "#1    #         #     2#",
+        "#  $  #  $   $  #      #",
+        "#     ###  #  ###      #",
+        "##  #      #      #  ###",
+        "#   #  ##########    #  ",
+        "# $ #  #        #    #  ",
+        "#      #  .  .  #    ## ",
+        "####   #        #     # ",
+        "   #   ###.  .##   $  # ",
+        "   #     #    #       # ",
+        "####  $  #.  .#  #  ### ",
+        "#        #    #  #    # ",
+        "#  ####  ##  ##  ##   # ",
+        "#     #               # ",
+        "#  $  #   $      #    # ",
+        "# .   #          #    # ",
+        "###########  ######## # ",
+        "          #          .# ",
+        "          ############# ",
+    ],
+];
 
+// versus levels, one string per row
+// '#' wall, '1' and '2' player starts, 'a' and 'b' boxes of player 1 and 2, 'A' and 'B' their goals
+const VERSUS_LEVELS: [&[&str]; 3] = [
+    &[
+        "#############",
+        "#     #     #",
+        "# a a # b b #",
+        "#  #  #  #  #",
+        "# A A # B B #",
+        "#  1  #  2  #",
+        "#############",
+    ],
+    &[
+        "#############",
+        "## A  #  B ##",
+        "#  #a # b#  #",
+        "# a  A#B  b #",
+        "#  a# # #b  #",
+        "#A  1 # 2  B#",
+        "#############",
+    ],
+    &[
+        "#############",
+        "#A    #    B#",
+        "#A## ### ##B#",
+        "#  a  #  b  #",
+        "# a # # # b #",
+        "##   1#2   ##",
+        " ########### ",
+    ],
+];
 
 pub struct Sokoban<'a> {
     player1: (u8, u8),
     player2: (u8, u8),
     level: u8,
-    frame: &'a mut Vec<Vec<u8, 15>, 15>,
-    destinations: &'a mut Vec<(u8, u8), 20>,
+    frame: &'a mut Vec<Vec<u8, 32>, 32>,
+    destinations: &'a mut Vec<(u8, u8), 32>,
     correct_boxes: u8,
     moves: u16,
+    pushes: u16,
+    start: Instant,
+    seconds: u16,
+    solution: String<SOLUTION_LEN>,
+    recorded: bool,
+    last_player: u8,
+    cell_size: i32,
+    view: (u8, u8),
+    overview: bool,
+    cursor: (u8, u8),
+    edit_slot: u8,
+    edit_buffer: [u8; LEVEL_BYTES],
+    versus: bool,
+    goal_owners: Vec<u8, 32>,
+    player_goals: [u8; 2],
+    player_boxes: [u8; 2],
+    player_moves: [u16; 2],
+    player_pushes: [u16; 2],
 }
 
 impl <'a> Sokoban<'a> {
-    pub fn new(frame: &'a mut Vec<Vec<u8, 15>, 15>, destinations: &'a mut Vec<(u8, u8), 20>) -> Sokoban <'a> {
+    pub fn new(frame: &'a mut Vec<Vec<u8, 32>, 32>, destinations: &'a mut Vec<(u8, u8), 32>) -> Sokoban <'a> {
         Sokoban {
             player1: (0, 0),
             player2: (0, 0),
@@ -51,7 +181,25 @@ impl <'a> Sokoban<'a> {
             frame,
             destinations,
             correct_boxes: 0,
-            moves: 0
+            moves: 0,
+            pushes: 0,
+            start: Instant::now(),
+            seconds: 0,
+            solution: String::new(),
+            recorded: true,
+            last_player: 1,
+            cell_size: CELL_SIZE,
+            view: (0, 0),
+            overview: false,
+            cursor: (0, 0),
+            edit_slot: 0,
+            edit_buffer: [0; LEVEL_BYTES],
+            versus: false,
+            goal_owners: Vec::new(),
+            player_goals: [0; 2],
+            player_boxes: [0; 2],
+            player_moves: [0; 2],
+            player_pushes: [0; 2],
         }
     } 
     pub fn init(&mut self) {
@@ -61,14 +209,30 @@ impl <'a> Sokoban<'a> {
         }
         macro_rules! row {
             ($($val:expr),*) => {{
-                let mut r: Vec<u8, 15> = Vec::new();
+                let mut r: Vec<u8, 32> = Vec::new();
                 $(r.push($val).unwrap();)*
                 self.frame.push(r).unwrap();
             }}
         }
         self.moves = 0;
+        self.pushes = 0;
+        self.start = Instant::now();
+        self.seconds = 0;
+        self.solution.clear();
+        self.recorded = true;
+        self.last_player = 1;
+        self.view = (0, 0);
         self.correct_boxes = 0;
         self.destinations.clear();
+        self.goal_owners.clear();
+        self.player_goals = [0; 2];
+        self.player_boxes = [0; 2];
+        self.player_moves = [0; 2];
+        self.player_pushes = [0; 2];
+        if self.versus {
+            self.parse(VERSUS_LEVELS[self.level as usize - 1]);
+            return;
+        }
         match self.level {
             1 => {
                 // 1 - wall
@@ -321,184 +485,384 @@ impl <'a> Sokoban<'a> {
                 self.destinations.push((8, 11)).unwrap();
                 self.destinations.push((7, 11)).unwrap();
             }
-            _ => {}
+            LARGE_LEVELS_START..=LEVELS => {
+                self.parse(LARGE_LEVELS[(self.level - LARGE_LEVELS_START) as usize]);
+            }
+            0 => {
+                // level being tested from the editor
+                let data = self.edit_buffer;
+                self.load(&data, false);
+            }
+            _ => {
+                let mut data = [0u8; LEVEL_BYTES];
+                if storage::read(storage::SOKOBAN_LEVELS + self.level - LEVELS - 1, &mut data) == false || self.load(&data, false) == false {
+                    warn!("custom level {} is empty", self.level - LEVELS);
+                }
+            }
+        }
+    }
+
+    fn load(&mut self, data: &[u8], edit: bool) -> bool {
+        if data[0] != LEVEL_MAGIC || data[1] > EDIT_ROWS || data[2] > EDIT_COLS {
+            return false;
+        }
+        let rows = data[1] as usize;
+        let cols = data[2] as usize;
+        self.frame.clear();
+        self.destinations.clear();
+        for i in 0..rows {
+            let mut row: Vec<u8, 32> = Vec::new();
+            for j in 0..cols {
+                let index = i * cols + j;
+                let cell = if index % 2 == 0 { data[3 + index / 2] >> 4 } else { data[3 + index / 2] & 0x0f };
+                if edit {
+                    row.push(cell).unwrap();
+                    continue;
+                }
+                row.push(match cell {
+                    WALL => WALL,
+                    BOX => BOX,
+                    GOAL => {
+                        self.destinations.push((i as u8, j as u8)).unwrap();
+                        FLOOR
+                    }
+                    BOX_ON_GOAL => {
+                        self.destinations.push((i as u8, j as u8)).unwrap();
+                        self.correct_boxes += 1;
+                        BOX
+                    }
+                    PLAYER1 => {
+                        self.player1 = (i as u8, j as u8);
+                        FLOOR
+                    }
+                    PLAYER2 => {
+                        self.player2 = (i as u8, j as u8);
+                        FLOOR
+                    }
+                    _ => FLOOR
+                }).unwrap();
+            }
+            self.frame.push(row).unwrap();
+        }
+        true
+    }
+
+    // reads a level from its text rows, see LARGE_LEVELS and VERSUS_LEVELS
+    fn parse(&mut self, rows: &[&str]) {
+        for (i, line) in rows.iter().enumerate() {
+            let mut row: Vec<u8, 32> = Vec::new();
+            for (j, c) in line.chars().enumerate() {
+                row.push(match c {
+                    '#' => WALL,
+                    '$' => BOX,
+                    'a' => BOX1,
+                    'b' => BOX2,
+                    '.' | '*' => {
+                        self.destinations.push((i as u8, j as u8)).unwrap();
+                        self.goal_owners.push(0).unwrap();
+                        if c == '*' { BOX } else { FLOOR }
+                    }
+                    'A' | 'B' => {
+                        let owner = if c == 'A' { 1 } else { 2 };
+                        self.destinations.push((i as u8, j as u8)).unwrap();
+                        self.goal_owners.push(owner).unwrap();
+                        self.player_goals[owner as usize - 1] += 1;
+                        FLOOR
+                    }
+                    '1' => {
+                        self.player1 = (i as u8, j as u8);
+                        FLOOR
+                    }
+                    '2' => {
+                        self.player2 = (i as u8, j as u8);
+                        FLOOR
+                    }
+                    _ => FLOOR
+                }).unwrap();
+            }
+            self.frame.push(row).unwrap();
+        }
+    }
+
+    fn pack(&self, data: &mut [u8]) {
+        data.fill(0);
+        data[0] = LEVEL_MAGIC;
+        data[1] = self.frame.len() as u8;
+        data[2] = self.frame[0].len() as u8;
+        for (i, row) in self.frame.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                let index = i * row.len() + j;
+                data[3 + index / 2] |= if index % 2 == 0 { cell << 4 } else { *cell };
+            }
+        }
+    }
+
+    fn level_name(&self) -> String<20> {
+        let mut temp: String<20> = String::new();
+        if self.versus {
+            fmt::write(&mut temp, format_args!("Versus: {}", self.level)).unwrap();
+            return temp;
         }
+        match self.level {
+            0 => fmt::write(&mut temp, format_args!("Test level")).unwrap(),
+            1..=LEVELS => fmt::write(&mut temp, format_args!("Level: {}", self.level)).unwrap(),
+            _ => fmt::write(&mut temp, format_args!("Custom: {}", self.level - LEVELS)).unwrap(),
+        }
+        temp
     }
     
     async fn draw_init(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        self.fit();
+        self.follow();
         Rectangle::new(Point::new(0, 0), Size::new(128, 160))
                 .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
                 .draw(screen)
                 .unwrap();
-        let mut temp: String<20> = String::new();
-        fmt::write(&mut temp, format_args!("Level: {}", self.level)).unwrap();
+        let temp: String<20> = self.level_name();
         Text::new( &temp, Point::new(10, 10), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
             .draw(screen).unwrap();
-        temp.clear();
-        fmt::write(&mut temp, format_args!("Moves: {}", self.moves)).unwrap();
-        Text::new(&temp, Point::new(10, 20), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
-            .draw(screen).unwrap();
-        for destination in self.destinations.iter() {
-            Rectangle::new(Point::new(destination.1 as i32 * 9, destination.0 as i32 * 9 + OFFSET_X), Size::new(8, 8))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
-                .draw(screen)
-                .unwrap();
-        }
-        Rectangle::new(Point::new(self.player1.1 as i32 * 9, self.player1.0 as i32 * 9 + OFFSET_X), Size::new(8, 8))
-            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
-            .draw(screen)
-            .unwrap();
-        Rectangle::new(Point::new(self.player2.1 as i32 * 9, self.player2.0 as i32 * 9 + OFFSET_X), Size::new(8, 8))
-            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
-            .draw(screen)
-            .unwrap();
-        for (i, row) in self.frame.iter().enumerate() {
-            for (j, item) in row.iter().enumerate() {
-                match item {
-                    1 => {
-                        // wall, gray
-                        Rectangle::new(Point::new(j as i32 * 9, i as i32 * 9 + OFFSET_X), Size::new(8, 8))
-                            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_GRAY))
-                            .draw(screen)
-                            .unwrap();
-                    }
-                    2 => {
-                        // box, brown
-                        Rectangle::new(Point::new(j as i32 * 9, i as i32 * 9 + OFFSET_X), Size::new(8, 8))
-                            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_BROWN))
-                            .draw(screen)
-                            .unwrap();
-                    }
-                    _ => {}
-                }
-            }
-        
-        }
+        self.draw_counters(screen);
+        self.draw_time(self.elapsed(), screen);
+        self.draw_board(screen);
     }
     
     fn handle_input(&mut self, input: &Input, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
         match input {
             Input::Select => {
+                // switch between the camera and the whole level, for levels bigger than the screen
+                self.overview = !self.overview;
+                if self.fit() {
+                    self.follow();
+                    self.draw_board(screen);
+                }
                 return true;
             }
             Input::Back => {
                 return false;
             }
             Input::Up => {
-                self.move_player(1, -1, 0, screen);
+                self.step(1, -1, 0, screen);
                 return true;
             }
             Input::Down => {
-                self.move_player(1, 1, 0, screen);
+                self.step(1, 1, 0, screen);
                 return true;
             }
             Input::Left => {
-                self.move_player(1, 0, -1, screen);
+                self.step(1, 0, -1, screen);
                 return true;
             }
             Input::Right => {
-                self.move_player(1, 0, 1, screen);
+                self.step(1, 0, 1, screen);
                 return true;
             } 
             Input::Up2 => {
-                self.move_player(2, -1, 0, screen);
+                self.step(2, -1, 0, screen);
                 return true;
             }
             Input::Down2 => {
-                self.move_player(2, 1, 0, screen);
+                self.step(2, 1, 0, screen);
                 return true;
             }
             Input::Left2 => {
-                self.move_player(2, 0, -1, screen);
+                self.step(2, 0, -1, screen);
                 return true;
             } 
             Input::Right2 => {
-                self.move_player(2, 0, 1, screen);
+                self.step(2, 0, 1, screen);
                 return true;
             }
             _ => { return true }
         }
     }
     
-    fn move_player(&mut self, p: u8, x: i8, y: i8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
-        let (player, other) = match p {
-            1 => {
-                (&mut self.player1, self.player2)
-            }
-            2 => {
-                (&mut self.player2, self.player1)  
+    fn step(&mut self, p: u8, x: i8, y: i8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if let Some(push) = self.move_player(p, x, y, screen) {
+            self.record(p, x, y, push);
+            if self.follow() {
+                self.draw_board(screen);
             }
-            _ => {
-                (&mut self.player1, self.player2)
+        }
+    }
+
+    // lowercase letters are moves and uppercase letters are pushes,
+    // a player number marks who moves from there on, the run starts with player 1
+    fn record(&mut self, p: u8, x: i8, y: i8, push: bool) {
+        let mut step = match (x, y) {
+            (-1, _) => 'u',
+            (1, _) => 'd',
+            (_, -1) => 'l',
+            _ => 'r',
+        };
+        if push {
+            step = step.to_ascii_uppercase();
+        }
+        if p != self.last_player {
+            self.last_player = p;
+            if self.solution.push(if p == 1 { '1' } else { '2' }).is_err() {
+                self.recorded = false;
             }
+        }
+        if self.solution.push(step).is_err() {
+            self.recorded = false;
+        }
+    }
+
+    // returns whether the player pushed a box, None if they could not move
+    fn move_player(&mut self, p: u8, x: i8, y: i8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> Option<bool> {
+        let (mut player, other) = match p {
+            2 => (self.player2, self.player1),
+            _ => (self.player1, self.player2),
         };
-        if self.frame[(player.0 as i8 + x) as usize][(player.1 as i8 + y) as usize] == 0 && (player.0 + x as u8, player.1 + y as u8) != other {
-            Rectangle::new(Point::new(player.1 as i32 * 9, player.0 as i32 * 9 + OFFSET_X), Size::new(8, 8))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                .draw(screen)
-                .unwrap();
-            self.frame[player.0 as usize][player.1 as usize] = 0;
-            player.0 += x as u8;
-            player.1 += y as u8;
+        let next = self.frame[(player.0 as i8 + x) as usize][(player.1 as i8 + y) as usize];
+        let mut result = None;
+        if next == FLOOR && ((player.0 as i8 + x) as u8, (player.1 as i8 + y) as u8) != other {
+            self.draw_cell(player.0, player.1, Rgb565::BLACK, screen);
+            player.0 = (player.0 as i8 + x) as u8;
+            player.1 = (player.1 as i8 + y) as u8;
             self.moves += 1;
-        } else if self.frame[(player.0 as i8 + x) as usize][(player.1 as i8 + y) as usize] == 2 && self.frame[(player.0 as i8 + 2 * x) as usize][(player.1 as i8 + 2 * y) as usize] == 0 {
-            self.frame[player.0 as usize][player.1 as usize] = 0;
-            self.frame[(player.0 as i8 + 2 * x) as usize][(player.1 as i8 + 2 * y) as usize] = 2;
-            Rectangle::new(Point::new(player.1 as i32 * 9, player.0 as i32 * 9 + OFFSET_X), Size::new(8, 8))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                .draw(screen)
-                .unwrap();
-            player.0 += x as u8;
-            player.1 += y as u8;
+            self.player_moves[p as usize - 1] += 1;
+            result = Some(false);
+        } else if can_push(self.versus, p, next)
+            && self.frame[(player.0 as i8 + 2 * x) as usize][(player.1 as i8 + 2 * y) as usize] == FLOOR
+            && ((player.0 as i8 + 2 * x) as u8, (player.1 as i8 + 2 * y) as u8) != other {
+            self.frame[(player.0 as i8 + x) as usize][(player.1 as i8 + y) as usize] = FLOOR;
+            self.frame[(player.0 as i8 + 2 * x) as usize][(player.1 as i8 + 2 * y) as usize] = next;
+            self.draw_cell(player.0, player.1, Rgb565::BLACK, screen);
+            player.0 = (player.0 as i8 + x) as u8;
+            player.1 = (player.1 as i8 + y) as u8;
             self.moves += 1;
-            Rectangle::new(Point::new((player.1 as i32 + y as i32) * 9, (player.0 as i32 + x as i32) * 9 + OFFSET_X), Size::new(8, 8))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_BROWN))
-                .draw(screen)
-                .unwrap();
+            self.player_moves[p as usize - 1] += 1;
+            self.pushes += 1;
+            self.player_pushes[p as usize - 1] += 1;
+            result = Some(true);
+            self.draw_cell((player.0 as i8 + x) as u8, (player.1 as i8 + y) as u8, box_color(next), screen);
+        }
+        if p == 2 {
+            self.player2 = player;
+        } else {
+            self.player1 = player;
         }
         self.correct_boxes = 0;
-        for destination in self.destinations.iter() {
-            if self.frame[destination.0 as usize][destination.1 as usize] == 2 {
-                Rectangle::new(Point::new(destination.1 as i32 * 9, destination.0 as i32 * 9 + OFFSET_X), Size::new(8, 8))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::GREEN))
-                    .draw(screen)
-                    .unwrap();
+        self.player_boxes = [0; 2];
+        for index in 0..self.destinations.len() {
+            let destination = self.destinations[index];
+            let owner = self.goal_owners.get(index).copied().unwrap_or(0);
+            let cell = self.frame[destination.0 as usize][destination.1 as usize];
+            // a shared goal takes any box, an owned goal only takes the boxes of its owner
+            let correct = match owner {
+                0 => cell == BOX,
+                1 => cell == BOX1,
+                _ => cell == BOX2,
+            };
+            if correct {
+                self.draw_cell(destination.0, destination.1, Rgb565::GREEN, screen);
                 self.correct_boxes += 1;
-            } else {
-                Rectangle::new(Point::new(destination.1 as i32 * 9, destination.0 as i32 * 9 + OFFSET_X), Size::new(8, 8))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
-                    .draw(screen)
-                    .unwrap();
+                if owner > 0 {
+                    self.player_boxes[owner as usize - 1] += 1;
+                }
+            } else if cell == FLOOR {
+                self.draw_cell(destination.0, destination.1, goal_color(owner), screen);
             }
         }
-        Rectangle::new(Point::new(self.player1.1 as i32 * 9, self.player1.0 as i32 * 9 + OFFSET_X), Size::new(8, 8))
-            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
-            .draw(screen)
-            .unwrap();
-        Rectangle::new(Point::new(self.player2.1 as i32 * 9, self.player2.0 as i32 * 9 + OFFSET_X), Size::new(8, 8))
-            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
+        self.draw_cell(self.player1.0, self.player1.1, Rgb565::BLUE, screen);
+        self.draw_cell(self.player2.0, self.player2.1, Rgb565::CSS_ORANGE, screen);
+        self.draw_counters(screen);
+        result
+    }
+
+    fn draw_counters(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 12), Size::new(128, 10))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
             .draw(screen)
             .unwrap();
-        Rectangle::new(Point::new(48, 13), Size::new(80, 10))
+        let mut temp: String<20> = String::new();
+        if self.versus {
+            // moves and pushes of every player, in their colour
+            fmt::write(&mut temp, format_args!("{}m {}p", self.player_moves[0], self.player_pushes[0])).unwrap();
+            Text::new(&temp, Point::new(10, 20), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_LIGHT_BLUE))
+                .draw(screen).unwrap();
+            temp.clear();
+            fmt::write(&mut temp, format_args!("{}m {}p", self.player_moves[1], self.player_pushes[1])).unwrap();
+            Text::new(&temp, Point::new(70, 20), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
+                .draw(screen).unwrap();
+        } else {
+            fmt::write(&mut temp, format_args!("Moves: {}", self.moves)).unwrap();
+            Text::new(&temp, Point::new(10, 20), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+                .draw(screen).unwrap();
+            temp.clear();
+            fmt::write(&mut temp, format_args!("Push: {}", self.pushes)).unwrap();
+            Text::new(&temp, Point::new(72, 20), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+                .draw(screen).unwrap();
+        }
+    }
+
+    fn draw_time(&self, seconds: u16, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(88, 2), Size::new(40, 10))
             .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
             .draw(screen)
             .unwrap();
         let mut temp: String<20> = String::new();
-        fmt::write(&mut temp, format_args!("{}", self.moves)).unwrap();
-        Text::new( &temp, Point::new(52, 20), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+        fmt::write(&mut temp, format_args!("{:02}:{:02}", seconds / 60, seconds % 60)).unwrap();
+        Text::new(&temp, Point::new(92, 10), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_GRAY))
             .draw(screen).unwrap();
-        
+    }
+
+    fn elapsed(&self) -> u16 {
+        (Instant::now() - self.start).as_secs().min(u16::MAX as u64) as u16
     }
 
     pub async fn game_loop(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        loop {
+            match self.level_select(screen).await {
+                MenuOption::Play => {
+                    self.init();
+                    if self.play(screen).await == false {
+                        set_current(0);
+                        return;
+                    }
+                }
+                MenuOption::Edit => {
+                    self.edit(screen).await;
+                }
+                MenuOption::Replay => {
+                    if self.replay(screen).await == false {
+                        set_current(0);
+                        return;
+                    }
+                }
+                _ => {
+                    set_current(0);
+                    return;
+                }
+            }
+        }
+    }
+
+    // returns false if the player chose to exit
+    async fn play(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let custom = !self.versus && (self.level == 0 || self.level > LEVELS);
         self.draw_init(screen).await;
         loop {
-            let input = INPUT_SIGNAL.wait().await;
+            let input = match select(INPUT_SIGNAL.wait(), Timer::after(Duration::from_secs(1))).await {
+                Either::First(input) => input,
+                Either::Second(_) => {
+                    self.draw_time(self.elapsed(), screen);
+                    continue;
+                }
+            };
             if self.handle_input(&input, screen) == false {
+                // the clock stops while the game is paused
+                let paused = Instant::now();
                 // create pause menu
-                let mut pause_menu: Menu<'_> = Menu::init("Pause menu", &[MenuOption::Resume, MenuOption::Restart, MenuOption::Next, MenuOption:: Previous, MenuOption::Exit], screen);
+                let mut pause_menu: Menu<'_> = if custom || self.versus {
+                    Menu::init("Pause menu", &[MenuOption::Resume, MenuOption::Restart, MenuOption::Exit], screen)
+                } else {
+                    Menu::init("Pause menu", &[MenuOption::Resume, MenuOption::Restart, MenuOption::Next, MenuOption:: Previous, MenuOption::Exit], screen)
+                };
                 let result: MenuOption = pause_menu.menu_loop(screen).await;
                 info!("obtained result... somehow?");
+                self.start += Instant::now() - paused;
                 match result {
                     MenuOption::Resume | MenuOption::None => {
                         self.redraw(screen).await;
@@ -512,7 +876,7 @@ impl <'a> Sokoban<'a> {
                         INPUT_SIGNAL.reset();
                     }
                     MenuOption::Next => {
-                        if self.level < 11 {
+                        if self.level < LEVELS {
                             self.level += 1;
                             self.init();
                             self.draw_init(screen).await;
@@ -534,14 +898,44 @@ impl <'a> Sokoban<'a> {
                         }
                     }
                     MenuOption::Exit => {
-                        unsafe { CURRENT = 0 };
-                        return;
+                        return false;
                     }
                     _ => {}
                 }
+            } else if self.versus {
+                if let Some(winner) = (0..2).find(|&k| self.player_boxes[k] == self.player_goals[k]) {
+                    self.seconds = self.elapsed();
+                    self.draw_results(false, screen);
+                    let mut winner_menu: Menu<'_> = Menu::init(if winner == 0 { "P1 wins!" } else { "P2 wins!" }, &[MenuOption::Restart, MenuOption::Continue, MenuOption::Exit], screen);
+                    let result: MenuOption = winner_menu.menu_loop(screen).await;
+                    match result {
+                        MenuOption::Restart => {
+                            self.init();
+                            self.draw_init(screen).await;
+                        }
+                        MenuOption::Exit => {
+                            return false;
+                        }
+                        _ => {
+                            return true;
+                        }
+                    }
+                }
             } else {
                 if self.correct_boxes == self.destinations.len() as u8 {
-                    if self.level < 11 {
+                    self.seconds = self.elapsed();
+                    let best = self.save_run();
+                    self.draw_results(best, screen);
+                    if custom {
+                        let mut cleared_menu: Menu<'_> = Menu::init("Cleared!", &[MenuOption::Continue, MenuOption::Exit], screen);
+                        let result: MenuOption = cleared_menu.menu_loop(screen).await;
+                        Timer::after(Duration::from_millis(100)).await;
+                        INPUT_SIGNAL.reset();
+                        return match result {
+                            MenuOption::Exit => false,
+                            _ => true,
+                        };
+                    } else if self.level < LEVELS {
                         let mut pause_menu: Menu<'_> = Menu::init("Cleared!", &[MenuOption::Continue, MenuOption::Exit], screen);
                         let result: MenuOption = pause_menu.menu_loop(screen).await;
                         info!("obtained result... somehow?");
@@ -554,8 +948,7 @@ impl <'a> Sokoban<'a> {
                                 INPUT_SIGNAL.reset();
                             },
                             MenuOption::Exit => {
-                                unsafe { CURRENT = 0 };
-                                return;
+                                return false;
                             }
                             _ => {}
                         }
@@ -572,8 +965,7 @@ impl <'a> Sokoban<'a> {
                                 INPUT_SIGNAL.reset();
                             },
                             MenuOption::Exit => {
-                                unsafe { CURRENT = 0 };
-                                return;
+                                return false;
                             }
                             _ => {}
                         }
@@ -585,54 +977,591 @@ impl <'a> Sokoban<'a> {
         }
     }
 
-    async fn redraw(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
-        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+    // the counters of the finished run, shown under the cleared and winner menus
+    fn draw_results(&self, best: bool, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 120), Size::new(128, 40))
             .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
             .draw(screen)
             .unwrap();
         let mut temp: String<20> = String::new();
-        fmt::write(&mut temp, format_args!("Level: {}", self.level)).unwrap();
-        Text::new( &temp, Point::new(10, 10), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+        if !self.versus {
+            fmt::write(&mut temp, format_args!("{} moves {} pushes", self.moves, self.pushes)).unwrap();
+            Text::new(&temp, Point::new(10, 135), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+                .draw(screen).unwrap();
+            temp.clear();
+            fmt::write(&mut temp, format_args!("{:02}:{:02}", self.seconds / 60, self.seconds % 60)).unwrap();
+            if best {
+                temp.push_str(" new best!").unwrap();
+            }
+            Text::new(&temp, Point::new(10, 150), MonoTextStyle::new(&FONT_6X10, if best { Rgb565::GREEN } else { Rgb565::CSS_GRAY }))
+                .draw(screen).unwrap();
+            return;
+        }
+        fmt::write(&mut temp, format_args!("P1: {}m {}p", self.player_moves[0], self.player_pushes[0])).unwrap();
+        Text::new(&temp, Point::new(10, 135), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_LIGHT_BLUE))
             .draw(screen).unwrap();
         temp.clear();
-        fmt::write(&mut temp, format_args!("Moves: {}", self.moves)).unwrap();
-        Text::new(&temp, Point::new(10, 20), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+        fmt::write(&mut temp, format_args!("P2: {}m {}p", self.player_moves[1], self.player_pushes[1])).unwrap();
+        Text::new(&temp, Point::new(10, 150), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
             .draw(screen).unwrap();
+    }
 
-        for destination in self.destinations.iter() {
-            Rectangle::new(Point::new(destination.1 as i32 * 9, destination.0 as i32 * 9 + OFFSET_X), Size::new(8, 8))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
-                .draw(screen)
-                .unwrap();
+    async fn level_select(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> MenuOption {
+        let mut saved = [false; storage::SOKOBAN_LEVEL_SLOTS as usize];
+        let mut data = [0u8; LEVEL_BYTES];
+        for slot in 0..storage::SOKOBAN_LEVEL_SLOTS {
+            saved[slot as usize] = storage::read(storage::SOKOBAN_LEVELS + slot, &mut data) && data[0] == LEVEL_MAGIC;
+        }
+        if self.level == 0 || self.level > self.last_level() {
+            self.level = 1;
+        }
+        self.draw_level_select(&saved, screen);
+        loop {
+            Timer::after(Duration::from_millis(100)).await;
+            INPUT_SIGNAL.reset();
+            match INPUT_SIGNAL.wait().await {
+                Input::Up | Input::Up2 => {
+                    self.level = if self.level < self.last_level() { self.level + 1 } else { 1 };
+                    self.draw_level_select(&saved, screen);
+                }
+                Input::Down | Input::Down2 => {
+                    self.level = if self.level > 1 { self.level - 1 } else { self.last_level() };
+                    self.draw_level_select(&saved, screen);
+                }
+                Input::Left | Input::Left2 | Input::Right | Input::Right2 => {
+                    // switch between co-op and versus
+                    self.versus = !self.versus;
+                    self.level = 1;
+                    self.draw_level_select(&saved, screen);
+                }
+                Input::Select => {
+                    let mut run = [0u8; REPLAY_BYTES];
+                    let solved = self.best_run(&mut run).is_some();
+                    let custom = !self.versus && self.level > LEVELS;
+                    if self.versus || (!custom && !solved) {
+                        return MenuOption::Play;
+                    }
+                    let slot = self.level.saturating_sub(LEVELS + 1);
+                    if custom && saved[slot as usize] == false {
+                        self.edit_slot = slot;
+                        return MenuOption::Edit;
+                    }
+                    let mut level_menu: Menu<'_> = match (custom, solved) {
+                        (true, true) => Menu::init("Custom", &[MenuOption::Play, MenuOption::Edit, MenuOption::Replay, MenuOption::Export, MenuOption::Exit], screen),
+                        (true, false) => Menu::init("Custom", &[MenuOption::Play, MenuOption::Edit, MenuOption::Exit], screen),
+                        _ => Menu::init("Solved", &[MenuOption::Play, MenuOption::Replay, MenuOption::Export, MenuOption::Exit], screen),
+                    };
+                    match level_menu.menu_loop(screen).await {
+                        MenuOption::Play => return MenuOption::Play,
+                        MenuOption::Replay => return MenuOption::Replay,
+                        MenuOption::Edit => {
+                            self.edit_slot = slot;
+                            return MenuOption::Edit;
+                        }
+                        MenuOption::Export => {
+                            let exported = self.export();
+                            self.draw_level_select(&saved, screen);
+                            Text::new(if exported { "exported!" } else { "export failed" }, Point::new(20, 120), MonoTextStyle::new(&FONT_6X10, if exported { Rgb565::GREEN } else { Rgb565::RED }))
+                                .draw(screen).unwrap();
+                        }
+                        _ => self.draw_level_select(&saved, screen),
+                    }
+                }
+                Input::Back => {
+                    return MenuOption::Exit;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // moves, pushes and seconds of the best run saved for the current level
+    fn best_run(&self, data: &mut [u8; REPLAY_BYTES]) -> Option<(u16, u16, u16)> {
+        if self.versus || self.level == 0 || self.level > storage::SOKOBAN_REPLAY_SLOTS {
+            return None;
+        }
+        if storage::read(storage::SOKOBAN_REPLAYS + self.level - 1, data) == false || data[0] != REPLAY_MAGIC {
+            return None;
+        }
+        Some((
+            u16::from_be_bytes([data[1], data[2]]),
+            u16::from_be_bytes([data[3], data[4]]),
+            u16::from_be_bytes([data[5], data[6]]),
+        ))
+    }
+
+    // saves the finished run if it beats the best one, fewer moves first and then fewer pushes
+    fn save_run(&self) -> bool {
+        if self.versus || self.level == 0 || self.recorded == false {
+            return false;
+        }
+        let mut data = [0u8; REPLAY_BYTES];
+        if let Some((moves, pushes, _)) = self.best_run(&mut data) {
+            if (moves, pushes) <= (self.moves, self.pushes) {
+                return false;
+            }
+        }
+        data.fill(0);
+        data[0] = REPLAY_MAGIC;
+        data[1..3].copy_from_slice(&self.moves.to_be_bytes());
+        data[3..5].copy_from_slice(&self.pushes.to_be_bytes());
+        data[5..7].copy_from_slice(&self.seconds.to_be_bytes());
+        data[7..9].copy_from_slice(&(self.solution.len() as u16).to_be_bytes());
+        data[REPLAY_HEADER..REPLAY_HEADER + self.solution.len()].copy_from_slice(self.solution.as_bytes());
+        storage::write(storage::SOKOBAN_REPLAYS + self.level - 1, &data)
+    }
+
+    // plays the best run back, returns false if the player chose to exit
+    async fn replay(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let mut data = [0u8; REPLAY_BYTES];
+        let Some((moves, pushes, seconds)) = self.best_run(&mut data) else {
+            return true;
+        };
+        let len = (u16::from_be_bytes([data[7], data[8]]) as usize).min(SOLUTION_LEN);
+        let run = &data[REPLAY_HEADER..REPLAY_HEADER + len];
+        self.init();
+        self.draw_init(screen).await;
+        self.draw_time(seconds, screen);
+        Timer::after(Duration::from_millis(100)).await;
+        INPUT_SIGNAL.reset();
+        let mut p = 1;
+        for step in run.iter() {
+            let (x, y) = match step.to_ascii_lowercase() {
+                b'1' => {
+                    p = 1;
+                    continue;
+                }
+                b'2' => {
+                    p = 2;
+                    continue;
+                }
+                b'u' => (-1, 0),
+                b'd' => (1, 0),
+                b'l' => (0, -1),
+                b'r' => (0, 1),
+                _ => continue,
+            };
+            self.step(p, x, y, screen);
+            if let Either::First(Input::Back) = select(INPUT_SIGNAL.wait(), Timer::after(Duration::from_millis(200))).await {
+                return true;
+            }
         }
-        Rectangle::new(Point::new(self.player1.1 as i32 * 9, self.player1.0 as i32 * 9 + OFFSET_X), Size::new(8, 8))
-            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
+        // the run only counts if it solves the level the way it claims to
+        let valid = self.correct_boxes == self.destinations.len() as u8
+            && self.moves == moves
+            && self.pushes == pushes
+            && self.solution.as_bytes() == run;
+        self.seconds = seconds;
+        self.draw_results(false, screen);
+        let mut replay_menu: Menu<'_> = Menu::init(if valid { "Verified" } else { "Invalid" }, &[MenuOption::Continue, MenuOption::Exit], screen);
+        let result: MenuOption = replay_menu.menu_loop(screen).await;
+        Timer::after(Duration::from_millis(100)).await;
+        INPUT_SIGNAL.reset();
+        match result {
+            MenuOption::Exit => false,
+            _ => true,
+        }
+    }
+
+    // sends the best run to the controller, custom levels also send their map
+    fn export(&self) -> bool {
+        let mut data = [0u8; REPLAY_BYTES];
+        let Some((moves, pushes, seconds)) = self.best_run(&mut data) else {
+            return false;
+        };
+        let len = (u16::from_be_bytes([data[7], data[8]]) as usize).min(SOLUTION_LEN);
+        let mut text: String<EXPORT_LEN> = String::new();
+        if self.level > LEVELS {
+            let mut level = [0u8; LEVEL_BYTES];
+            if storage::read(storage::SOKOBAN_LEVELS + self.level - LEVELS - 1, &mut level) == false {
+                return false;
+            }
+            fmt::write(&mut text, format_args!("sokoban custom {} map ", self.level - LEVELS)).unwrap();
+            for byte in level.iter() {
+                fmt::write(&mut text, format_args!("{:02x}", byte)).unwrap();
+            }
+        } else {
+            fmt::write(&mut text, format_args!("sokoban level {}", self.level)).unwrap();
+        }
+        fmt::write(&mut text, format_args!(" moves {} pushes {} time {} lurd ", moves, pushes, seconds)).unwrap();
+        match core::str::from_utf8(&data[REPLAY_HEADER..REPLAY_HEADER + len]) {
+            Ok(run) => text.push_str(run).unwrap(),
+            Err(_) => return false,
+        }
+        EXPORT_SIGNAL.signal(text);
+        true
+    }
+
+    fn last_level(&self) -> u8 {
+        if self.versus {
+            VERSUS_LEVELS.len() as u8
+        } else {
+            LEVELS + storage::SOKOBAN_LEVEL_SLOTS
+        }
+    }
+
+    fn draw_level_select(&self, saved: &[bool], screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
             .draw(screen)
             .unwrap();
-        Rectangle::new(Point::new(self.player2.1 as i32 * 9, self.player2.0 as i32 * 9 + OFFSET_X), Size::new(8, 8))
-            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
+        Text::new("Sokoban", Point::new(29, 30), MonoTextStyle::new(&FONT_10X20, Rgb565::RED))
+            .draw(screen).unwrap();
+        let temp = self.level_name();
+        Text::new(if self.versus { "< Versus >" } else { "< Co-op >" }, Point::new(20, 60), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
+            .draw(screen).unwrap();
+        Text::new(&temp, Point::new(20, 75), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+            .draw(screen).unwrap();
+        let mut run = [0u8; REPLAY_BYTES];
+        if let Some((moves, pushes, seconds)) = self.best_run(&mut run) {
+            let mut temp: String<20> = String::new();
+            fmt::write(&mut temp, format_args!("Best: {}m {}p {}:{:02}", moves, pushes, seconds / 60, seconds % 60)).unwrap();
+            Text::new(&temp, Point::new(20, 105), MonoTextStyle::new(&FONT_6X10, Rgb565::GREEN))
+                .draw(screen).unwrap();
+        }
+        if !self.versus && self.level > LEVELS {
+            Text::new(if saved[(self.level - LEVELS - 1) as usize] { "saved" } else { "empty, edit it" }, Point::new(20, 90), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_GRAY))
+                .draw(screen).unwrap();
+        }
+        Text::new("e: select q: exit", Point::new(10, 150), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
+            .draw(screen).unwrap();
+    }
+
+    async fn edit(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let mut data = [0u8; LEVEL_BYTES];
+        if storage::read(storage::SOKOBAN_LEVELS + self.edit_slot, &mut data) == false || self.load(&data, true) == false {
+            // start from an empty room
+            self.frame.clear();
+            for i in 0..EDIT_ROWS {
+                let mut row: Vec<u8, 32> = Vec::new();
+                for j in 0..EDIT_COLS {
+                    row.push(if i == 0 || j == 0 || i == EDIT_ROWS - 1 || j == EDIT_COLS - 1 { WALL } else { FLOOR }).unwrap();
+                }
+                self.frame.push(row).unwrap();
+            }
+        }
+        self.cursor = (self.frame.len() as u8 / 2, self.frame[0].len() as u8 / 2);
+        self.draw_editor(None, screen);
+        loop {
+            Timer::after(Duration::from_millis(100)).await;
+            INPUT_SIGNAL.reset();
+            match INPUT_SIGNAL.wait().await {
+                Input::Up | Input::Up2 => self.move_cursor(-1, 0, screen),
+                Input::Down | Input::Down2 => self.move_cursor(1, 0, screen),
+                Input::Left | Input::Left2 => self.move_cursor(0, -1, screen),
+                Input::Right | Input::Right2 => self.move_cursor(0, 1, screen),
+                Input::Select | Input::Right_Shoot | Input::Right2_Shoot => self.cycle_cell(true, screen),
+                Input::Left_Shoot | Input::Left2_Shoot => self.cycle_cell(false, screen),
+                Input::Back => {
+                    let mut editor_menu: Menu<'_> = Menu::init("Editor", &[MenuOption::Resume, MenuOption::Test, MenuOption::Save, MenuOption::Exit], screen);
+                    match editor_menu.menu_loop(screen).await {
+                        MenuOption::Test => match self.validate() {
+                            Ok(_) => {
+                                let mut data = [0u8; LEVEL_BYTES];
+                                self.pack(&mut data);
+                                self.edit_buffer = data;
+                                let level = self.level;
+                                self.level = 0;
+                                self.init();
+                                self.play(screen).await;
+                                self.level = level;
+                                self.load(&data, true);
+                                self.draw_editor(None, screen);
+                            }
+                            Err(message) => self.draw_editor(Some(message), screen),
+                        },
+                        MenuOption::Save => match self.validate() {
+                            Ok(_) => {
+                                self.pack(&mut data);
+                                if storage::write(storage::SOKOBAN_LEVELS + self.edit_slot, &data) {
+                                    // the old best run belongs to the old level
+                                    storage::write(storage::SOKOBAN_REPLAYS + LEVELS + self.edit_slot, &[0]);
+                                    self.draw_editor(Some("Saved!"), screen);
+                                } else {
+                                    self.draw_editor(Some("Saving failed"), screen);
+                                }
+                            }
+                            Err(message) => self.draw_editor(Some(message), screen),
+                        },
+                        MenuOption::Exit => return,
+                        _ => self.draw_editor(None, screen),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), &'static str> {
+        let mut boxes = 0;
+        let mut goals = 0;
+        let mut player1 = None;
+        let mut player2 = None;
+        for (i, row) in self.frame.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                match *cell {
+                    BOX => boxes += 1,
+                    GOAL => goals += 1,
+                    BOX_ON_GOAL => {
+                        boxes += 1;
+                        goals += 1;
+                    }
+                    PLAYER1 => player1 = Some((i, j)),
+                    PLAYER2 => player2 = Some((i, j)),
+                    _ => {}
+                }
+            }
+        }
+        if boxes == 0 {
+            return Err("No boxes placed");
+        }
+        if boxes != goals {
+            return Err("Boxes != goals");
+        }
+        if goals > self.destinations.capacity() {
+            return Err("Too many goals");
+        }
+        let (Some(player1), Some(player2)) = (player1, player2) else {
+            return Err("Place both players");
+        };
+        // flood fill from both players, reaching the edge means the level is open
+        let rows = self.frame.len();
+        let cols = self.frame[0].len();
+        let mut visited = [[false; EDIT_COLS as usize]; EDIT_ROWS as usize];
+        let mut stack: Vec<(usize, usize), { 4 * EDIT_ROWS as usize * EDIT_COLS as usize }> = Vec::new();
+        stack.push(player1).unwrap();
+        stack.push(player2).unwrap();
+        while let Some((i, j)) = stack.pop() {
+            if visited[i][j] || self.frame[i][j] == WALL {
+                continue;
+            }
+            visited[i][j] = true;
+            if i == 0 || j == 0 || i == rows - 1 || j == cols - 1 {
+                return Err("Level not closed");
+            }
+            stack.push((i - 1, j)).unwrap();
+            stack.push((i + 1, j)).unwrap();
+            stack.push((i, j - 1)).unwrap();
+            stack.push((i, j + 1)).unwrap();
+        }
+        Ok(())
+    }
+
+    fn move_cursor(&mut self, x: i8, y: i8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let i = self.cursor.0 as i8 + x;
+        let j = self.cursor.1 as i8 + y;
+        if i < 0 || j < 0 || i >= self.frame.len() as i8 || j >= self.frame[0].len() as i8 {
+            return;
+        }
+        draw_cursor(self.cursor, Rgb565::BLACK, screen);
+        self.cursor = (i as u8, j as u8);
+        draw_cursor(self.cursor, Rgb565::WHITE, screen);
+        self.draw_editor_status(None, screen);
+    }
+
+    fn cycle_cell(&mut self, forward: bool, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let (i, j) = (self.cursor.0 as usize, self.cursor.1 as usize);
+        let cell = if forward { (self.frame[i][j] + 1) % CELL_TYPES } else { (self.frame[i][j] + CELL_TYPES - 1) % CELL_TYPES };
+        if cell == PLAYER1 || cell == PLAYER2 {
+            // each player has a single start
+            for (r, row) in self.frame.iter_mut().enumerate() {
+                for (c, item) in row.iter_mut().enumerate() {
+                    if *item == cell {
+                        *item = FLOOR;
+                        draw_edit_cell(r as u8, c as u8, FLOOR, screen);
+                    }
+                }
+            }
+        }
+        self.frame[i][j] = cell;
+        draw_edit_cell(self.cursor.0, self.cursor.1, cell, screen);
+        draw_cursor(self.cursor, Rgb565::WHITE, screen);
+        self.draw_editor_status(None, screen);
+    }
+
+    fn draw_editor(&self, message: Option<&str>, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
             .draw(screen)
             .unwrap();
+        let mut temp: String<20> = String::new();
+        fmt::write(&mut temp, format_args!("Editor: Custom {}", self.edit_slot + 1)).unwrap();
+        Text::new(&temp, Point::new(10, 10), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+            .draw(screen).unwrap();
         for (i, row) in self.frame.iter().enumerate() {
             for (j, item) in row.iter().enumerate() {
-                match item {
-                    1 => {
-                        // wall, gray
-                        Rectangle::new(Point::new(j as i32 * 9, i as i32 * 9 + OFFSET_X), Size::new(8, 8))
-                            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_GRAY))
-                            .draw(screen)
-                            .unwrap();
-                    }
-                    2 => {
-                        // box, brown
-                        Rectangle::new(Point::new(j as i32 * 9, i as i32 * 9 + OFFSET_X), Size::new(8, 8))
-                            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_BROWN))
-                            .draw(screen)
-                            .unwrap();
-                    }
+                draw_edit_cell(i as u8, j as u8, *item, screen);
+            }
+        }
+        draw_cursor(self.cursor, Rgb565::WHITE, screen);
+        self.draw_editor_status(message, screen);
+    }
+
+    fn draw_editor_status(&self, message: Option<&str>, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 12), Size::new(128, 10))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let (text, color) = match message {
+            Some(message) => (message, Rgb565::RED),
+            None => (match self.frame[self.cursor.0 as usize][self.cursor.1 as usize] {
+                WALL => "Wall",
+                BOX => "Box",
+                GOAL => "Goal",
+                BOX_ON_GOAL => "Box on goal",
+                PLAYER1 => "Player 1 start",
+                PLAYER2 => "Player 2 start",
+                _ => "Floor",
+            }, Rgb565::CSS_ORANGE),
+        };
+        Text::new(text, Point::new(10, 20), MonoTextStyle::new(&FONT_6X10, color))
+            .draw(screen).unwrap();
+    }
+
+    async fn redraw(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let temp: String<20> = self.level_name();
+        Text::new( &temp, Point::new(10, 10), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+            .draw(screen).unwrap();
+        self.draw_counters(screen);
+        self.draw_time(self.elapsed(), screen);
+        self.draw_board(screen);
+    }
+
+    fn draw_board(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, OFFSET_X - 4), Size::new(128, (BOARD_HEIGHT + 4) as u32))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        for (index, destination) in self.destinations.iter().enumerate() {
+            self.draw_cell(destination.0, destination.1, goal_color(self.goal_owners.get(index).copied().unwrap_or(0)), screen);
+        }
+        self.draw_cell(self.player1.0, self.player1.1, Rgb565::BLUE, screen);
+        self.draw_cell(self.player2.0, self.player2.1, Rgb565::CSS_ORANGE, screen);
+        for (i, row) in self.frame.iter().enumerate() {
+            for (j, item) in row.iter().enumerate() {
+                match *item {
+                    // wall, gray
+                    WALL => self.draw_cell(i as u8, j as u8, Rgb565::CSS_GRAY, screen),
+                    // box, brown or the colour of its owner
+                    BOX | BOX1 | BOX2 => self.draw_cell(i as u8, j as u8, box_color(*item), screen),
                     _ => {}
                 }
             }
         }
     }
-}
\ No newline at end of file
+
+    // cells outside of the camera are skipped
+    fn draw_cell(&self, row: u8, col: u8, color: Rgb565, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if row < self.view.0 || col < self.view.1 {
+            return;
+        }
+        let x = (col - self.view.1) as i32 * self.cell_size;
+        let y = (row - self.view.0) as i32 * self.cell_size;
+        if x + self.cell_size > BOARD_WIDTH + 1 || y + self.cell_size > BOARD_HEIGHT + 1 {
+            return;
+        }
+        Rectangle::new(Point::new(x, y + OFFSET_X), Size::new(self.cell_size as u32 - 1, self.cell_size as u32 - 1))
+            .into_styled(PrimitiveStyle::with_fill(color))
+            .draw(screen)
+            .unwrap();
+    }
+
+    // picks the biggest cell size the whole level fits in, returns true if it changed
+    fn fit(&mut self) -> bool {
+        let rows = self.frame.len().max(1) as i32;
+        let cols = self.frame.iter().map(|row| row.len()).max().unwrap_or(1).max(1) as i32;
+        let fits = (BOARD_WIDTH / cols).min(BOARD_HEIGHT / rows).min(CELL_SIZE);
+        let cell_size = if fits >= MIN_CELL_SIZE || self.overview {
+            fits.max(OVERVIEW_CELL_SIZE)
+        } else {
+            CELL_SIZE
+        };
+        let changed = cell_size != self.cell_size;
+        self.cell_size = cell_size;
+        changed
+    }
+
+    // moves the camera towards the players, returns true if it moved
+    fn follow(&mut self) -> bool {
+        let rows = self.frame.len() as u8;
+        let cols = self.frame.iter().map(|row| row.len()).max().unwrap_or(0) as u8;
+        let (active, other) = if self.last_player == 2 { (self.player2, self.player1) } else { (self.player1, self.player2) };
+        let view = (
+            camera_axis(self.view.0, active.0, other.0, (BOARD_HEIGHT / self.cell_size) as u8, rows),
+            camera_axis(self.view.1, active.1, other.1, (BOARD_WIDTH / self.cell_size) as u8, cols),
+        );
+        let moved = view != self.view;
+        self.view = view;
+        moved
+    }
+
+}
+
+fn draw_edit_cell(i: u8, j: u8, cell: u8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+    Rectangle::new(Point::new(j as i32 * 9, i as i32 * 9 + OFFSET_X), Size::new(8, 8))
+        .into_styled(PrimitiveStyle::with_fill(match cell {
+            WALL => Rgb565::CSS_GRAY,
+            BOX => Rgb565::CSS_BROWN,
+            GOAL => Rgb565::RED,
+            BOX_ON_GOAL => Rgb565::GREEN,
+            PLAYER1 => Rgb565::BLUE,
+            PLAYER2 => Rgb565::CSS_ORANGE,
+            _ => Rgb565::BLACK,
+        }))
+        .draw(screen)
+        .unwrap();
+}
+
+fn draw_cursor(cursor: (u8, u8), color: Rgb565, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+    Rectangle::new(Point::new(cursor.1 as i32 * 9 - 1, cursor.0 as i32 * 9 + OFFSET_X - 1), Size::new(10, 10))
+        .into_styled(PrimitiveStyle::with_stroke(color, 1))
+        .draw(screen)
+        .unwrap();
+}
+
+fn goal_color(owner: u8) -> Rgb565 {
+    match owner {
+        1 => Rgb565::CSS_DARK_BLUE,
+        2 => Rgb565::CSS_DARK_ORANGE,
+        _ => Rgb565::RED,
+    }
+}
+
+fn box_color(cell: u8) -> Rgb565 {
+    match cell {
+        BOX1 => Rgb565::CSS_LIGHT_BLUE,
+        BOX2 => Rgb565::CSS_GOLD,
+        _ => Rgb565::CSS_BROWN,
+    }
+}
+
+// in versus levels every player can only push their own boxes
+fn can_push(versus: bool, p: u8, cell: u8) -> bool {
+    match (versus, cell) {
+        (false, BOX) => true,
+        (true, BOX1) => p == 1,
+        (true, BOX2) => p == 2,
+        _ => false,
+    }
+}
+
+// the first visible row or column, both players stay in view when they fit on the screen,
+// otherwise the camera only follows the player who moved last
+fn camera_axis(start: u8, active: u8, other: u8, visible: u8, size: u8) -> u8 {
+    if size <= visible {
+        return 0;
+    }
+    let (low, high) = if active.abs_diff(other) + 2 * CAMERA_MARGIN < visible {
+        (active.min(other), active.max(other))
+    } else {
+        (active, active)
+    };
+    if low >= start + CAMERA_MARGIN && high + CAMERA_MARGIN < start + visible {
+        return start;
+    }
+    ((low as u16 + high as u16) / 2).saturating_sub(visible as u16 / 2).min((size - visible) as u16) as u8
+}
@@ -1,7 +1,5 @@
 use core::fmt;
 
-#[allow(static_mut_refs)]
-
 use embassy_embedded_hal::shared_bus::blocking::spi::SpiDevice;
 
 use embassy_sync::blocking_mutex::raw::NoopRawMutex;
@@ -26,17 +24,17 @@ use heapless::{
 };
 
 use rand::seq::SliceRandom;
+use rand::Rng;
 
 use crate::{menu::selector::Menu, INPUT_SIGNAL};
-use crate::CURRENT;
+use crate::games::spaceinvaders_waves::{WAVES, CLASSES};
+use crate::set_current;
 
 use {defmt_rtt as _, panic_probe as _};
 use defmt::*;
 
 use rust_pico_console::{Input, MenuOption};
 
-static mut OFFSET_X: u8 = 0;
-static mut OFFSET_Y: u8 = 50;
 const ENEMY_WIDTH: u32 = 8;
 const ENEMY_HEIGHT: u32 = 3;
 const BOSS_WIDTH: u32 = 40;
@@ -44,6 +42,51 @@ const BOSS_HEIGHT: u32 = 10;
 const PLAYER_WIDTH: u32 = 6;
 const PLAYER_HEIGHT: u32 = 3;
 const SPACING: u8 = 15;
+const MAX_ROWS: usize = 5;
+const MAX_COLS: usize = 7;
+const BUNKERS: usize = 4;
+const BUNKER_WIDTH: u8 = 14;
+const BUNKER_HEIGHT: usize = 8;
+const BUNKER_X: u8 = 9;
+const BUNKER_Y: u8 = 126;
+const BUNKER_SPACING: u8 = 32;
+const UFO_WIDTH: i16 = 12;
+const UFO_HEIGHT: u8 = 4;
+const UFO_Y: u8 = 28;
+const UFO_SPRITE: [&str; UFO_HEIGHT as usize] = [
+    "....####....",
+    "..########..",
+    "############",
+    "..##....##..",
+];
+// the ufo is worth one of these times the level
+const UFO_POINTS: [u64; 4] = [50, 100, 150, 300];
+// frames the points stay where the ufo was hit
+const BONUS_FRAMES: u8 = 80;
+// player shots are gone once they fly past this line
+const SHOT_TOP: u8 = 22;
+// frames for every pixel a boss strafes in each of its phases
+const BOSS_STRAFE: [u8; 3] = [4, 2, 1];
+// sideways speed of the shots in a spread, 1/16 px per frame
+const SPREAD: [i16; 4] = [-8, -3, 3, 8];
+// the steepest an aimed shot falls at, 1/16 px per frame
+const MAX_AIM: i16 = 32;
+const HP_BAR_X: i32 = 52;
+const HP_BAR_Y: i32 = 3;
+const HP_BAR_WIDTH: u32 = 44;
+// column of shots fired by a boss, they don't keep a column from firing
+const NO_COLUMN: u8 = u8::MAX;
+// every bunker starts as this shape, one bit per pixel once loaded
+const BUNKER_SHAPE: [&str; BUNKER_HEIGHT] = [
+    "..##########..",
+    ".############.",
+    "##############",
+    "##############",
+    "##############",
+    "####......####",
+    "###........###",
+    "###........###",
+];
 
 #[derive(Debug)]
 #[derive(Clone)]
@@ -57,6 +100,42 @@ pub enum Enemy {
     Boss2,
     None,
 }
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+enum BossPattern {
+    Spread,
+    Twin,
+    Wall,
+}
+
+// an enemy shot, x is kept in 1/16 of a pixel so aimed shots can fall at an angle
+#[derive(Clone)]
+#[derive(Copy)]
+pub struct EnemyShot {
+    x: i16,
+    y: u8,
+    dx: i16,
+    column: u8,
+    active: bool,
+}
+
+impl EnemyShot {
+    fn new(x: u8, y: u8, dx: i16, column: u8) -> EnemyShot {
+        EnemyShot { x: x as i16 * 16, y, dx, column, active: true }
+    }
+
+    fn px(&self) -> u8 {
+        (self.x / 16) as u8
+    }
+
+    // where the shot was drawn the frame before
+    fn prev_px(&self) -> i32 {
+        (self.x - self.dx) as i32 / 16
+    }
+}
+
 pub struct SpaceInvaders<'a> {
     player1_pos: u8,
     player1_pos_prev: u8,
@@ -68,10 +147,29 @@ pub struct SpaceInvaders<'a> {
     player2_projectiles: &'a mut Vec<(u8, u8, bool), 20>,
     player1_lives: u8,
     player2_lives: u8,
-    enemies: &'a mut Vec<Vec<(Enemy, u8),5>,5>,
-    last_row: &'a mut Vec<(Enemy, u8, u8, bool), 5>,
-    enemy_projectiles: &'a mut Vec<(u8, u8, u8, bool), 5>,
+    enemies: &'a mut Vec<Vec<(Enemy, u8), MAX_COLS>, MAX_ROWS>,
+    last_row: &'a mut Vec<(Enemy, u8, u8, bool), MAX_COLS>,
+    enemy_projectiles: &'a mut Vec<EnemyShot, 16>,
     projectile_cooldown: u8,
+    // frames between two enemy shots in this wave
+    fire_cooldown: u8,
+    boss_pattern: BossPattern,
+    rows: usize,
+    cols: usize,
+    // how far right the grid marches before it drops
+    max_offset: u8,
+    // where the grid is, in cells to the right and pixels down
+    offset_x: u8,
+    offset_y: u8,
+    // how far the bosses strafed away from their place in the grid
+    boss_x: i16,
+    boss_direction: i16,
+    boss_cooldown: u8,
+    boss_volley: u8,
+    boss_frame: u8,
+    boss_phase: u8,
+    // hit points of all the bosses of the wave together
+    boss_hp: u16,
     enemies_count: u8,
     level: u8,
     score: u64,
@@ -81,12 +179,22 @@ pub struct SpaceInvaders<'a> {
     step: u16,
     speed: u16,
     shift: bool,
+    // one row of bits per line of pixels, bit 0 is the left edge
+    bunkers: [[u16; BUNKER_HEIGHT]; BUNKERS],
+    ufo_active: bool,
+    ufo_x: i16,
+    ufo_direction: i16,
+    // frames until the next ufo shows up
+    ufo_timer: u16,
+    bonus_x: i16,
+    bonus_points: u64,
+    bonus_timer: u8,
 }
 
 impl <'a> SpaceInvaders<'a> {
-    pub fn new(enemies: &'a mut Vec<Vec<(Enemy, u8), 5>, 5> , 
-    last_row: &'a mut Vec<(Enemy, u8, u8, bool), 5>,
-    enemy_projectiles: &'a mut Vec<(u8, u8, u8, bool), 5>,
+    pub fn new(enemies: &'a mut Vec<Vec<(Enemy, u8), MAX_COLS>, MAX_ROWS> , 
+    last_row: &'a mut Vec<(Enemy, u8, u8, bool), MAX_COLS>,
+    enemy_projectiles: &'a mut Vec<EnemyShot, 16>,
     player1_projectiles: &'a mut Vec<(u8, u8, bool), 20>,
     player2_projectiles: &'a mut Vec<(u8, u8, bool), 20>) -> SpaceInvaders <'a> {
         SpaceInvaders { 
@@ -104,6 +212,20 @@ impl <'a> SpaceInvaders<'a> {
             last_row,
             enemy_projectiles,
             projectile_cooldown: 50,
+            fire_cooldown: 100,
+            boss_pattern: BossPattern::Spread,
+            rows: 0,
+            cols: 0,
+            max_offset: 4,
+            offset_x: 0,
+            offset_y: 50,
+            boss_x: 0,
+            boss_direction: 1,
+            boss_cooldown: 0,
+            boss_volley: 0,
+            boss_frame: 0,
+            boss_phase: 0,
+            boss_hp: 0,
             enemies_count: 0,
             level: 1, 
             score: 0,
@@ -113,377 +235,596 @@ impl <'a> SpaceInvaders<'a> {
             step: 0,
             speed: 300,
             shift: true, 
+            bunkers: [[0; BUNKER_HEIGHT]; BUNKERS],
+            ufo_active: false,
+            ufo_x: 0,
+            ufo_direction: 1,
+            ufo_timer: 1500,
+            bonus_x: 0,
+            bonus_points: 0,
+            bonus_timer: 0,
         }
     }
     pub fn init(&mut self) {
         self.draw_init = false;
-        unsafe {
-            OFFSET_X = 0;
-            OFFSET_Y = 50;
-        }
-        self.speed = self.speed - self.level as u16 * 2;
-        if self.level % 10 == 0 {
-            self.enemies[0][1] = (Enemy::Boss2, 20);
-            for i in 0..5 {
-                self.enemies[2][i] = (Enemy::Class3, 3);
-                self.last_row[i] = (Enemy::Class3, i as u8, 3, false);
-            }
-            unsafe {
-                self.lowest_enemy = 2;
+        self.offset_x = 0;
+        self.offset_y = 50;
+        self.shift = true;
+        let wave = (self.level as usize - 1) % WAVES.len();
+        let round = (self.level as usize - 1) / WAVES.len();
+        self.load(WAVES[wave], round);
+        self.reset_bunkers();
+        self.ufo_active = false;
+        self.ufo_timer = RoscRng.gen_range(1000..2500);
+        self.bonus_timer = 0;
+    }
+
+    // reads a wave from spaceinvaders_waves.rs, every round through the waves marches and fires faster
+    // and gives the enemies more hit points
+    fn load(&mut self, wave: &[&str], round: usize) {
+        let mut header = wave[0].split_whitespace();
+        let speed = header.next().and_then(|value| value.parse::<u16>().ok()).unwrap_or(300);
+        let fire = header.next().and_then(|value| value.parse::<u8>().ok()).unwrap_or(100);
+        self.boss_pattern = match header.next() {
+            Some("twin") => BossPattern::Twin,
+            Some("wall") => BossPattern::Wall,
+            _ => BossPattern::Spread,
+        };
+        self.speed = speed.saturating_sub(round as u16 * 30).max(60);
+        self.step = self.speed;
+        self.fire_cooldown = fire.saturating_sub(round as u8 * 10).max(30);
+        self.projectile_cooldown = self.fire_cooldown;
+        self.enemy_projectiles.clear();
+
+        for row in self.enemies.iter_mut() {
+            for cell in row.iter_mut() {
+                *cell = (Enemy::None, 0);
             }
-            self.enemies_count = 6;
-        } else if self.level % 5 == 0 {
-            //boss level, first row boss and 1 row class3
-            self.enemies[0][1] = (Enemy::Boss1, 20);
-            for i in 0..5 {
-                self.enemies[2][i] = (Enemy::Class3, 3);
-                self.last_row[i] = (Enemy::Class3, i as u8, 3, false);
+        }
+        self.rows = 0;
+        self.cols = 0;
+        self.enemies_count = 0;
+        let mut right = 0;
+        for (row, line) in wave[1..].iter().take(MAX_ROWS).enumerate() {
+            self.rows = row + 1;
+            for (col, cell) in line.chars().take(MAX_COLS).enumerate() {
+                self.cols = self.cols.max(col + 1);
+                let enemy = match cell {
+                    '1' => Enemy::Class1,
+                    '2' => Enemy::Class2,
+                    '3' => Enemy::Class3,
+                    'A' => Enemy::Boss1,
+                    'B' => Enemy::Boss2,
+                    _ => continue,
+                };
+                let bonus = match enemy {
+                    Enemy::Boss1 | Enemy::Boss2 => round * 5,
+                    _ => round,
+                };
+                self.enemies[row][col] = (enemy, CLASSES[class(enemy)].0.saturating_add(bonus as u8));
+                self.enemies_count += 1;
+                right = right.max(col as u8 * SPACING + enemy_size(enemy).0 as u8);
             }
-            unsafe {
-                self.lowest_enemy = 2;
+        }
+        self.max_offset = (128 - right.min(128)) / SPACING;
+        for col in 0..MAX_COLS {
+            self.update_shooter(col);
+        }
+        self.update_lowest();
+        self.boss_x = 0;
+        self.boss_direction = 1;
+        self.boss_cooldown = self.fire_cooldown;
+        self.boss_hp = self.boss_health();
+        self.boss_phase = self.boss_phase();
+    }
+
+    fn enemy_x(&self, row: usize, col: usize) -> u8 {
+        let x = ((col as u8 + self.offset_x) * SPACING) as i16;
+        match self.enemies[row][col].0 {
+            Enemy::Boss1 | Enemy::Boss2 => (x + self.boss_x) as u8,
+            _ => x as u8,
+        }
+    }
+
+    fn draw_cell(&self, row: usize, col: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let y = row as u8 * SPACING + self.offset_y;
+        draw_enemy(self.enemy_x(row, col), y, &self.enemies[row][col].0, self.boss_phase, screen);
+    }
+
+    fn boss_health(&self) -> u16 {
+        let mut health = 0;
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                if is_boss(self.enemies[row][col].0) {
+                    health += self.enemies[row][col].1 as u16;
+                }
             }
-            self.enemies_count = 6;
+        }
+        health
+    }
+
+    // bosses get angrier at two thirds and one third of their hit points, the second boss starts angry
+    fn boss_phase(&self) -> u8 {
+        let health = self.boss_health() as u32;
+        let max = self.boss_hp as u32;
+        let phase = if health * 3 > max * 2 {
+            0
+        } else if health * 3 > max {
+            1
         } else {
-            match self.level % 5 {
-                1 => {
-                    //all easy enemies
-                    for i in 0..4 {
-                        for j in 0..5 {
-                            self.enemies[i][j] = (Enemy::Class1, 1); 
-                        }
-                    }
-                    for i in 0..5 {
-                        self.last_row[i] = (Enemy::Class1, i as u8, 4, false);
-                    }
-                    unsafe {
-                        self.lowest_enemy = 3;
-                    }
-                    self.enemies_count = 20;
+            2
+        };
+        let angry = (0..self.rows).any(|row| (0..self.cols).any(|col| self.enemies[row][col].0 == Enemy::Boss2));
+        if angry {
+            (phase + 1).min(2)
+        } else {
+            phase
+        }
+    }
+
+    // keeps the bosses on screen after the grid marched
+    fn clamp_boss(&mut self) {
+        let (mut left, mut right) = (i16::MAX, i16::MIN);
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                if is_boss(self.enemies[row][col].0) {
+                    let x = ((col as u8 + self.offset_x) * SPACING) as i16;
+                    left = left.min(x);
+                    right = right.max(x + BOSS_WIDTH as i16);
                 }
-                2 => {
-                    //first row category2
-                    for i in 0..5 {
-                        self.enemies[0][i] = (Enemy::Class2, 2);
-                        self.last_row[i] = (Enemy::Class1, i as u8, 4, false);
-                    }
-                    
-                    for i in 1..4 {
-                        for j in 0..5 {
-                            self.enemies[i][j] = (Enemy::Class1, 1);
-                        }
-                    }
-                    unsafe {
-                        self.lowest_enemy = 3;
+            }
+        }
+        if left <= right {
+            self.boss_x = self.boss_x.clamp(-left, 128 - right);
+        }
+    }
+
+    // the bosses strafe along their row and fire volleys on their own timer
+    fn update_boss(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if self.boss_health() == 0 {
+            return;
+        }
+        let phase = self.boss_phase();
+        if phase != self.boss_phase {
+            self.boss_phase = phase;
+            self.draw_boss_hp(screen);
+        }
+
+        self.boss_frame = self.boss_frame.wrapping_add(1);
+        if self.boss_frame % BOSS_STRAFE[phase as usize] == 0 {
+            self.clamp_boss();
+            let step = self.boss_direction;
+            let before = self.boss_x;
+            self.boss_x += step;
+            self.clamp_boss();
+            if self.boss_x == before {
+                self.boss_direction = -step;
+            }
+            for row in 0..self.rows {
+                for col in 0..self.cols {
+                    if !is_boss(self.enemies[row][col].0) {
+                        continue;
                     }
-                    self.enemies_count = 20;
+                    // only the strip the boss moved away from needs clearing
+                    let x = self.enemy_x(row, col) as i32;
+                    let y = (row as u8 * SPACING + self.offset_y) as i32;
+                    let trail = match self.boss_x - before {
+                        0 => continue,
+                        d if d > 0 => x - 1,
+                        _ => x + BOSS_WIDTH as i32,
+                    };
+                    Rectangle::new(Point::new(trail, y), Size::new(1, BOSS_HEIGHT))
+                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                        .draw(screen)
+                        .unwrap();
+                    self.draw_cell(row, col, screen);
                 }
-                3 => {
-                    //first and second category2
-                    for i in 0..5 {
-                        self.enemies[0][i] = (Enemy::Class2, 2);
-                        self.enemies[1][i] = (Enemy::Class2, 2);
-                        self.last_row[i] = (Enemy::Class1, i as u8, 4, false);
-                    }
-                    
-                    for i in 2..4 {
-                        for j in 0..5 {
-                            self.enemies[i][j] = (Enemy::Class1, 1);
-                        }
-                    }
-                    unsafe {
-                        self.lowest_enemy = 3;
-                    }
-                    self.enemies_count = 20;
+            }
+        }
+
+        if self.boss_cooldown > 0 {
+            self.boss_cooldown -= 1;
+            return;
+        }
+        self.boss_cooldown = match phase {
+            0 => self.fire_cooldown + self.fire_cooldown / 2,
+            1 => self.fire_cooldown,
+            _ => self.fire_cooldown / 2,
+        };
+        self.boss_volley = self.boss_volley.wrapping_add(1);
+        // first the pattern alone, then pattern and aimed shots take turns, at the end both at once
+        let pattern = phase != 1 || self.boss_volley % 2 == 0;
+        let aimed = phase == 2 || (phase == 1 && self.boss_volley % 2 == 1);
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                if !is_boss(self.enemies[row][col].0) {
+                    continue;
                 }
-                4 => {
-                    // full class 2
-                    for i in 0..4 {
-                        for j in 0..5 {
-                            self.enemies[i][j] = (Enemy::Class2, 2); 
+                let left = self.enemy_x(row, col);
+                let bottom = row as u8 * SPACING + self.offset_y + BOSS_HEIGHT as u8;
+                if pattern {
+                    let shots: &[(u8, u8, i16)] = match self.boss_pattern {
+                        BossPattern::Spread => &[(1, 0, SPREAD[0]), (13, 5, SPREAD[1]), (26, 5, SPREAD[2]), (38, 0, SPREAD[3])],
+                        BossPattern::Twin => &[(1, 0, 0), (38, 0, 0)],
+                        BossPattern::Wall => &[(1, 0, 0), (10, 0, 0), (19, 0, 0), (28, 0, 0), (37, 0, 0)],
+                    };
+                    for shot in shots.iter() {
+                        match self.enemy_projectiles.push(EnemyShot::new(left + shot.0, bottom + shot.1, shot.2, NO_COLUMN)) {
+                            Ok(_) => {}
+                            Err(_) => {}
                         }
                     }
-                    for i in 0..5 {
-                        self.last_row[i] = (Enemy::Class2, i as u8, 4, false);
-                    }
-                    unsafe {
-                        self.lowest_enemy = 3;
-                    }
-                    self.enemies_count = 20;
                 }
-                _ => {},
+                if aimed {
+                    let spread: &[i16] = if phase == 2 { &[-6, 0, 6] } else { &[0] };
+                    self.aim(left + BOSS_WIDTH as u8 / 2 - 1, bottom, spread);
+                }
             }
         }
     }
 
-    fn update_frame(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> (bool, bool) {
-        //check collision
-        // info!("lives {} {}", self.player1_lives, self.player2_lives);
-        if self.player1_cooldown > 0 {
-            self.player1_cooldown -= 1;
+    // fires at the living player closest to x, every entry of spread is one shot bent that much off target
+    fn aim(&mut self, x: u8, y: u8, spread: &[i16]) {
+        let mut targets: Vec<u8, 2> = Vec::new();
+        if self.player1_lives > 0 {
+            match targets.push(self.player1_pos) { Ok(_) => {} Err(_) => {} }
         }
-        if self.player2_cooldown > 0 {
-            self.player2_cooldown -= 1;
+        if self.player2_lives > 0 {
+            match targets.push(self.player2_pos) { Ok(_) => {} Err(_) => {} }
         }
-        // info!("{} {} {} {} {}", self.enemies[0][0].1, self.enemies[0][1].1, self.enemies[0][2].1, self.enemies[0][3].1, self.enemies[0][4].1);
-        // info!("{} {} {} {} {}", self.enemies[1][0].1, self.enemies[1][1].1, self.enemies[1][2].1, self.enemies[1][3].1, self.enemies[1][4].1);
-        // info!("{} {} {} {} {}", self.enemies[2][0].1, self.enemies[2][1].1, self.enemies[2][2].1, self.enemies[2][3].1, self.enemies[2][4].1);
-        // info!("{} {} {} {} {}", self.enemies[3][0].1, self.enemies[3][1].1, self.enemies[3][2].1, self.enemies[3][3].1, self.enemies[3][4].1);
-        // info!("enemies left {}", self.enemies_count);
-        unsafe {
-            self.player1_projectiles.retain(|projectile| projectile.2);
-            for projectile in self.player1_projectiles.iter_mut() {
-                // info!("projectile coord {} {}", projectile.0, projectile.1);
-                // info!("projectile max coords {} {}", projectile.0 + 1, projectile.1 + 3);
-                // info!("first enemy coords {} {}", (OFFSET_X * SPACING as u8), OFFSET_Y);
-                // info!("first enemy max coords {} {}", (OFFSET_X * SPACING as u8 + ENEMY_WIDTH as u8), (OFFSET_Y * SPACING as u8 + ENEMY_HEIGHT as u8));
-                if projectile.1 > 45 + OFFSET_Y {
+        let target = match targets.iter().min_by_key(|pos| (**pos as i16 - x as i16).abs()) {
+            Some(pos) => *pos as i16 + PLAYER_WIDTH as i16 / 2,
+            None => return,
+        };
+        let fall = (150 - y.min(149)) as i16;
+        let dx = (target - x as i16) * 16 / fall;
+        for bend in spread.iter() {
+            match self.enemy_projectiles.push(EnemyShot::new(x, y, (dx + bend).clamp(-MAX_AIM, MAX_AIM), NO_COLUMN)) {
+                Ok(_) => {}
+                Err(_) => {}
+            }
+        }
+    }
+
+    // a shot ended, its column can fire again
+    fn release(&mut self, column: u8) {
+        if (column as usize) < MAX_COLS {
+            self.last_row[column as usize].3 = false;
+        }
+    }
+
+    fn draw_boss_hp(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(HP_BAR_X, HP_BAR_Y), Size::new(HP_BAR_WIDTH, 3))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let health = self.boss_health() as u32;
+        if health == 0 || self.boss_hp == 0 {
+            return;
+        }
+        Rectangle::new(Point::new(HP_BAR_X, HP_BAR_Y + 1), Size::new(HP_BAR_WIDTH, 1))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_GRAY))
+            .draw(screen)
+            .unwrap();
+        Rectangle::new(Point::new(HP_BAR_X, HP_BAR_Y), Size::new((HP_BAR_WIDTH * health / self.boss_hp as u32).max(1), 3))
+            .into_styled(PrimitiveStyle::with_fill(boss_color(self.boss_phase)))
+            .draw(screen)
+            .unwrap();
+    }
+
+    // the lowest enemy of a column is the one that shoots
+    fn update_shooter(&mut self, col: usize) {
+        self.last_row[col] = (Enemy::None, 0, 0, false);
+        for row in (0..self.rows).rev() {
+            if self.enemies[row][col].0 != Enemy::None {
+                self.last_row[col] = (self.enemies[row][col].0, col as u8, row as u8, false);
+                break;
+            }
+        }
+    }
+
+    fn update_lowest(&mut self) {
+        for row in (0..self.rows).rev() {
+            let height = (0..self.cols).map(|col| enemy_size(self.enemies[row][col].0).1).max().unwrap_or(0);
+            if height > 0 {
+                self.lowest_enemy = row as u8;
+                self.lowest_height = height as u8;
+                return;
+            }
+        }
+    }
+
+    // a player shot against the grid, true when it hit an enemy
+    fn shot_hits_enemy(&mut self, x: u8, y: u8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let offset_y = self.offset_y;
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let enemy = self.enemies[row][col].0;
+                let (width, height) = enemy_size(enemy);
+                if enemy == Enemy::None {
                     continue;
                 }
-                if projectile.1 < 40 {
-                    projectile.2 = false;
+                let left = self.enemy_x(row, col);
+                let top = row as u8 * SPACING + offset_y;
+                if x + 1 < left || x > left + width as u8 - 1 || y + 3 < top || y > top + height as u8 - 1 {
                     continue;
                 }
-                if self.level % 5 == 0 {
-                    if projectile.0 >= (1 + OFFSET_X) * SPACING as u8 && projectile.0 <= (1 + OFFSET_X) * SPACING as u8 + BOSS_WIDTH as u8- 1 ||
-                    projectile.0 + 1 >= (1 + OFFSET_X) * SPACING as u8 && projectile.0 + 1 <= (1 + OFFSET_X) * SPACING as u8 + BOSS_WIDTH as u8 - 1 {
-                        if projectile.1 >= OFFSET_Y && projectile.1 <= OFFSET_Y + BOSS_HEIGHT as u8 - 1 ||
-                        projectile.1 + 3 >= OFFSET_Y && projectile.1 <= OFFSET_Y + BOSS_HEIGHT as u8 - 1 {
-                            // boss hit
-                            if self.enemies[0][1].0 != Enemy::None {
-                                Rectangle::new(Point::new(projectile.0 as i32, projectile.1 as i32), Size::new(2, 4))
-                                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                    .draw(screen)
-                                    .unwrap();
-                                if self.enemies[0][1].1 == 1 {
-                                    self.enemies[0][1].0 = Enemy::None;
-                                    self.score += match self.enemies[0][1].0 {
-                                        Enemy::Boss1 => { 500 * self.level as u64 }
-                                        Enemy::Boss2 => { 1000 * self.level as u64 }
-                                        _ => { 0 }
-                                    };
-                                    Rectangle::new(Point::new(34, 12), Size::new(80, 8))
-                                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                        .draw(screen)
-                                        .unwrap();
-                                    let mut temp: String<20> = String::new();
-                                    fmt::write(&mut temp, format_args!("{}", self.score)).unwrap();
-                                    Text::new( &temp, Point::new(35, 18), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
-                                        .draw(screen).unwrap();
-                                    Rectangle::new(Point::new(1 + OFFSET_X as i32 * SPACING as i32, (1 as i32) * SPACING as i32 + OFFSET_Y as i32), Size::new(BOSS_WIDTH, BOSS_HEIGHT))
-                                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                        .draw(screen)
-                                        .unwrap();
-                                } else {
-                                    self.enemies[0][1].1 -= 1;
-                                }
-                                projectile.2 = false;
-                                continue;
-                            }
-                        }
+                Rectangle::new(Point::new(x as i32, y as i32), Size::new(2, 4))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                    .draw(screen)
+                    .unwrap();
+                if self.enemies[row][col].1 > 1 {
+                    self.enemies[row][col].1 -= 1;
+                    if is_boss(enemy) {
+                        self.draw_boss_hp(screen);
                     }
+                    return true;
                 }
-                for x in 0..5 as usize {
-                    if projectile.0 >= (x as u8 + OFFSET_X) * SPACING as u8 && projectile.0 <= (x as u8 + OFFSET_X) * SPACING as u8 + ENEMY_WIDTH as u8- 1 ||
-                    projectile.0 + 1 >= (x as u8 + OFFSET_X) * SPACING as u8 && projectile.0 + 1 <= (x as u8 + OFFSET_X) * SPACING as u8 + ENEMY_WIDTH as u8 - 1 {
-                        // collision with column x
-                        for y in 0..4 as usize {
-                            if projectile.1 >= (y as u8 * SPACING) + OFFSET_Y && projectile.1 <= (y as u8 * SPACING) + OFFSET_Y + ENEMY_HEIGHT as u8 - 1 ||
-                            projectile.1 + 3 >= (y as u8 * SPACING) + OFFSET_Y && projectile.1 <= (y as u8 * SPACING) + OFFSET_Y + ENEMY_HEIGHT as u8 - 1 {
-                                // collision with row y
-                                if self.enemies[y][x].0 != Enemy::None {
-                                    // info!("proj coordinates player 2 {} {}", projectile.0, projectile.1);
-                                    // info!("enemy coords {} {}", (x as i32 + OFFSET_X as i32) * SPACING as i32, (y as i32) * SPACING as i32 + OFFSET_Y as i32);
-                                    if self.enemies[y][x].1 == 1 {
-                                        self.enemies[y][x].0 = Enemy::None;
-                                        self.score += match self.enemies[y][x].0 {
-                                            Enemy::Class1 => { 50 * self.level as u64 }
-                                            Enemy::Class2 => { 75 * self.level as u64 }
-                                            Enemy:: Class3 => { 100 * self.level as u64 }
-                                            _ => { 0 }
-                                        };
-                                        Rectangle::new(Point::new(34, 12), Size::new(80, 8))
-                                            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                            .draw(screen)
-                                            .unwrap();
-                                        let mut temp: String<20> = String::new();
-                                        fmt::write(&mut temp, format_args!("{}", self.score)).unwrap();
-                                        Text::new( &temp, Point::new(35, 18), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
-                                            .draw(screen).unwrap();
-                                        Rectangle::new(Point::new((x as i32 + OFFSET_X as i32) * SPACING as i32, (y as i32) * SPACING as i32 + OFFSET_Y as i32), Size::new(ENEMY_WIDTH, ENEMY_HEIGHT))
-                                            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                            .draw(screen)
-                                            .unwrap();
-                                        self.enemies[y][x].1 = 0;
-                                        self.enemies_count -= 1;
-                                        let mut posy = 4;
-                                        loop {
-                                            if self.enemies[posy][x].0 != Enemy::None {
-                                                self.last_row[x] = (self.enemies[posy][x].0, x as u8, posy as u8, false);
-                                                break;
-                                            }
-                                            if posy == 0 && self.enemies[posy][x].0 == Enemy::None {
-                                                self.last_row[x] = (Enemy::None, 0, 0, false);
-                                            }
-                                            if posy > 0 {
-                                                posy -= 1;
-                                            } else {
-                                                break;
-                                            }
-                                        }
-                                    } else {
-                                        self.enemies[y][x].1 -= 1;
-                                    }
-                                    projectile.2 = false;
-                                    // exit
-                                    break;
-                                }
-                            }
-                        }
-                        // exit anyway
-                        break;
+                self.score += CLASSES[class(enemy)].2 * self.level as u64;
+                draw_score(self.score, screen);
+                Rectangle::new(Point::new(left as i32, top as i32), Size::new(width, height))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                    .draw(screen)
+                    .unwrap();
+                self.enemies[row][col] = (Enemy::None, 0);
+                self.enemies_count -= 1;
+                self.update_shooter(col);
+                if is_boss(enemy) {
+                    self.draw_boss_hp(screen);
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    fn reset_bunkers(&mut self) {
+        for bunker in self.bunkers.iter_mut() {
+            for (row, line) in BUNKER_SHAPE.iter().enumerate() {
+                bunker[row] = 0;
+                for (col, pixel) in line.chars().enumerate() {
+                    if pixel == '#' {
+                        bunker[row] |= 1 << col;
                     }
                 }
             }
+        }
+    }
 
-            self.player2_projectiles.retain(|projectile| projectile.2);
-            for projectile in self.player2_projectiles.iter_mut() {
-                // info!("projectile coord {} {}", projectile.0, projectile.1);
-                // info!("projectile max coords {} {}", projectile.0 + 1, projectile.1 + 3);
-                // info!("first enemy coords {} {}", (OFFSET_X * SPACING as u8), OFFSET_Y);
-                // info!("first enemy max coords {} {}", (OFFSET_X * SPACING as u8 + ENEMY_WIDTH as u8), (OFFSET_Y * SPACING as u8 + ENEMY_HEIGHT as u8));
-                if projectile.1 > 45 + OFFSET_Y {
+    // first bunker pixel under a 2 pixel wide projectile, searched from the side the projectile comes from
+    fn bunker_hit(&self, x: u8, y: u8, height: u8, downwards: bool) -> Option<(usize, u8, u8)> {
+        for i in 0..height {
+            let py = if downwards { y + i } else { y + height - 1 - i };
+            if py < BUNKER_Y || py >= BUNKER_Y + BUNKER_HEIGHT as u8 {
+                continue;
+            }
+            let row = (py - BUNKER_Y) as usize;
+            for px in x..x + 2 {
+                if px < BUNKER_X {
                     continue;
                 }
-                if projectile.1 < 40 {
-                    projectile.2 = false;
+                let bunker = ((px - BUNKER_X) / BUNKER_SPACING) as usize;
+                let col = (px - BUNKER_X) % BUNKER_SPACING;
+                if bunker < BUNKERS && col < BUNKER_WIDTH && self.bunkers[bunker][row] & (1 << col) != 0 {
+                    return Some((bunker, col, row as u8));
+                }
+            }
+        }
+        None
+    }
+
+    // blows a ragged hole around the pixel that was hit, only the pixels that go away are drawn
+    fn carve(&mut self, bunker: usize, col: u8, row: u8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let mut rng = RoscRng;
+        for r in row.saturating_sub(1)..=(row + 1).min(BUNKER_HEIGHT as u8 - 1) {
+            for c in col.saturating_sub(1)..=(col + 2).min(BUNKER_WIDTH - 1) {
+                let edge = r != row || c < col || c > col + 1;
+                if edge && rng.gen_bool(0.5) {
                     continue;
                 }
-                if self.level % 5 == 0 {
-                    if projectile.0 >= (1 + OFFSET_X) * SPACING as u8 && projectile.0 <= (1 + OFFSET_X) * SPACING as u8 + BOSS_WIDTH as u8- 1 ||
-                    projectile.0 + 1 >= (1 + OFFSET_X) * SPACING as u8 && projectile.0 + 1 <= (1 + OFFSET_X) * SPACING as u8 + BOSS_WIDTH as u8 - 1 {
-                        if projectile.1 >= OFFSET_Y && projectile.1 <= OFFSET_Y + BOSS_HEIGHT as u8 - 1 ||
-                        projectile.1 + 3 >= OFFSET_Y && projectile.1 <= OFFSET_Y + BOSS_HEIGHT as u8 - 1 {
-                            // boss hit
-                            if self.enemies[0][1].0 != Enemy::None {
-                                Rectangle::new(Point::new(projectile.0 as i32, projectile.1 as i32), Size::new(2, 4))
-                                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                    .draw(screen)
-                                    .unwrap();
-                                if self.enemies[0][1].1 == 1 {
-                                    self.enemies[0][1].0 = Enemy::None;
-                                    self.score += match self.enemies[0][1].0 {
-                                        Enemy::Boss1 => { 500 * self.level as u64 }
-                                        Enemy::Boss2 => { 1000 * self.level as u64 }
-                                        _ => { 0 }
-                                    };
-                                    Rectangle::new(Point::new(34, 12), Size::new(80, 8))
-                                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                        .draw(screen)
-                                        .unwrap();
-                                    let mut temp: String<20> = String::new();
-                                    fmt::write(&mut temp, format_args!("{}", self.score)).unwrap();
-                                    Text::new( &temp, Point::new(35, 18), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
-                                        .draw(screen).unwrap();
-                                    Rectangle::new(Point::new(OFFSET_X as i32 * SPACING as i32, (1 as i32) * SPACING as i32 + OFFSET_Y as i32), Size::new(BOSS_WIDTH, BOSS_HEIGHT))
-                                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                        .draw(screen)
-                                        .unwrap();
-                                } else {
-                                    self.enemies[0][1].1 -= 1;
-                                }
-                                projectile.2 = false;
-                                continue;
-                            }
-                        }
+                if self.bunkers[bunker][r as usize] & (1 << c) != 0 {
+                    self.bunkers[bunker][r as usize] &= !(1 << c);
+                    Rectangle::new(Point::new((BUNKER_X + bunker as u8 * BUNKER_SPACING + c) as i32, (BUNKER_Y + r) as i32), Size::new(1, 1))
+                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                        .draw(screen)
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    // removes the bunker pixels inside a rectangle, used when the enemies march into the bunkers
+    fn clear_bunkers(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        for (bunker, rows) in self.bunkers.iter_mut().enumerate() {
+            let left = BUNKER_X as i32 + bunker as i32 * BUNKER_SPACING as i32;
+            for (row, bits) in rows.iter_mut().enumerate() {
+                let py = BUNKER_Y as i32 + row as i32;
+                if py < y || py >= y + height {
+                    continue;
+                }
+                for col in 0..BUNKER_WIDTH as i32 {
+                    if left + col >= x && left + col < x + width {
+                        *bits &= !(1 << col);
                     }
                 }
-                for x in 0..5 as usize {
-                    if projectile.0 >= (x as u8 + OFFSET_X) * SPACING as u8 && projectile.0 <= (x as u8 + OFFSET_X) * SPACING as u8 + ENEMY_WIDTH as u8- 1 ||
-                    projectile.0 + 1 >= (x as u8 + OFFSET_X) * SPACING as u8 && projectile.0 + 1 <= (x as u8 + OFFSET_X) * SPACING as u8 + ENEMY_WIDTH as u8 - 1 {
-                        // collision with column x
-                        for y in 0..4 as usize {
-                            if projectile.1 >= (y as u8 * SPACING) + OFFSET_Y && projectile.1 <= (y as u8 * SPACING) + OFFSET_Y + ENEMY_HEIGHT as u8 - 1 ||
-                            projectile.1 + 3 >= (y as u8 * SPACING) + OFFSET_Y && projectile.1 <= (y as u8 * SPACING) + OFFSET_Y + ENEMY_HEIGHT as u8 - 1 {
-                                // collision with row y
-                                if self.enemies[y][x].0 != Enemy::None {
-                                    // info!("proj coordinates player 2 {} {}", projectile.0, projectile.1);
-                                    Rectangle::new(Point::new(projectile.0 as i32, projectile.1 as i32), Size::new(2, 4))
-                                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                        .draw(screen)
-                                        .unwrap();
-                                    if self.enemies[y][x].1 == 1 {
-                                        self.enemies[y][x].0 = Enemy::None;
-                                        self.score += match self.enemies[y][x].0 {
-                                            Enemy::Class1 => { 50 * self.level as u64 }
-                                            Enemy::Class2 => { 75 * self.level as u64 }
-                                            Enemy:: Class3 => { 100 * self.level as u64 }
-                                            _ => { 0 }
-                                        };
-                                        Rectangle::new(Point::new(34, 12), Size::new(80, 8))
-                                            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                            .draw(screen)
-                                            .unwrap();
-                                        let mut temp: String<20> = String::new();
-                                        fmt::write(&mut temp, format_args!("{}", self.score)).unwrap();
-                                        Text::new( &temp, Point::new(35, 18), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
-                                            .draw(screen).unwrap();
-                                        Rectangle::new(Point::new((x as i32 + OFFSET_X as i32) * SPACING as i32, (y as i32) * SPACING as i32 + OFFSET_Y as i32), Size::new(ENEMY_WIDTH, ENEMY_HEIGHT))
-                                            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                                            .draw(screen)
-                                            .unwrap();
-                                        self.enemies[y][x].1 = 0;
-                                        self.enemies_count -= 1;
-                                        let mut posy = 4;
-                                        loop {  
-                                            if self.enemies[posy][x].0 != Enemy::None {
-                                                self.last_row[x] = (self.enemies[posy][x].0, x as u8, posy as u8, false);
-                                                break;
-                                            }
-                                            if posy == 0 && self.enemies[posy][x].0 == Enemy::None {
-                                                self.last_row[x] = (Enemy::None, 0, 0, false);
-                                            }
-                                            if posy > 0 {
-                                                posy -= 1;
-                                            } else {
-                                                break;
-                                            }
-                                        }
-                                    } else {
-                                        self.enemies[y][x].1 -= 1;
-                                    }
-                                    projectile.2 = false;
-                                    // exit
-                                    break;
-                                }
-                            }
-                        }
-                        // exit anyway
-                        break;
+            }
+        }
+    }
+
+    // draws every run of pixels in a row as one rectangle
+    fn draw_bunkers(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        for (bunker, rows) in self.bunkers.iter().enumerate() {
+            let left = BUNKER_X as i32 + bunker as i32 * BUNKER_SPACING as i32;
+            for (row, &bits) in rows.iter().enumerate() {
+                let mut col = 0;
+                while col < BUNKER_WIDTH as i32 {
+                    if bits & (1 << col) == 0 {
+                        col += 1;
+                        continue;
+                    }
+                    let start = col;
+                    while col < BUNKER_WIDTH as i32 && bits & (1 << col) != 0 {
+                        col += 1;
                     }
+                    Rectangle::new(Point::new(left + start, BUNKER_Y as i32 + row as i32), Size::new((col - start) as u32, 1))
+                        .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_LIME_GREEN))
+                        .draw(screen)
+                        .unwrap();
                 }
             }
+        }
+    }
 
-            // check enemy projectiles collision with player ships
+    // player shots that would fly into a bunker next frame stop there and carve it
+    fn shots_into_bunkers(&mut self, player: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let count = if player == 0 { self.player1_projectiles.len() } else { self.player2_projectiles.len() };
+        for i in 0..count {
+            let shot = if player == 0 { self.player1_projectiles[i] } else { self.player2_projectiles[i] };
+            if shot.2 == false || shot.1 == 0 {
+                continue;
+            }
+            if let Some((bunker, col, row)) = self.bunker_hit(shot.0, shot.1 - 1, 4, false) {
+                if player == 0 {
+                    self.player1_projectiles[i].2 = false;
+                } else {
+                    self.player2_projectiles[i].2 = false;
+                }
+                Rectangle::new(Point::new(shot.0 as i32, shot.1 as i32), Size::new(2, 4))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                    .draw(screen)
+                    .unwrap();
+                self.carve(bunker, col, row, screen);
+            }
         }
-        // info!("reached");
-        // info!("LAST ROW");
-        // for i in 0..5 {
-        //     info!("last row {}, {}", i, self.last_row[i].3);
-        // }
-        'lowest: for i in 0..4  {
-            for j in 0..5  {
-                // info!("i j {} {}",i ,j);
-                if self.enemies[3 - i][4 - j].0 != Enemy::None {
-                    self.lowest_height = match self.enemies[3 - i][4 - j].0 {
-                        Enemy::Boss1 | Enemy::Boss2 => { BOSS_HEIGHT as u8 }
-                        _ => { ENEMY_HEIGHT as u8 }
-                    };
-                    self.lowest_enemy = 3 - i as u8;
-                    // info!("lowest {}", self.lowest_enemy);
-                    break 'lowest;
+    }
+
+    // the ufo waits a random time, then crosses the top of the screen one pixel every other frame
+    fn update_ufo(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let mut rng = RoscRng;
+        if self.bonus_timer > 0 {
+            self.bonus_timer -= 1;
+            if self.bonus_timer == 0 {
+                Rectangle::new(Point::new(self.bonus_x as i32, UFO_Y as i32 - 2), Size::new(30, 9))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                    .draw(screen)
+                    .unwrap();
+            }
+        }
+        if self.ufo_active == false {
+            if self.ufo_timer > 0 {
+                self.ufo_timer -= 1;
+            } else if self.enemies_count > 0 {
+                self.ufo_active = true;
+                self.ufo_direction = if rng.gen_bool(0.5) { 1 } else { -1 };
+                self.ufo_x = if self.ufo_direction > 0 { -UFO_WIDTH } else { 128 };
+            }
+            return;
+        }
+        if self.step % 2 == 1 {
+            return;
+        }
+        Rectangle::new(Point::new(self.ufo_x as i32, UFO_Y as i32), Size::new(UFO_WIDTH as u32, UFO_HEIGHT as u32))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        self.ufo_x += self.ufo_direction;
+        if self.ufo_x < -UFO_WIDTH || self.ufo_x > 128 {
+            self.ufo_active = false;
+            self.ufo_timer = rng.gen_range(1500..3000);
+            return;
+        }
+        draw_sprite(self.ufo_x as i32, UFO_Y as i32, &UFO_SPRITE, Rgb565::MAGENTA, screen);
+    }
+
+    // player shots hitting the ufo, or leaving the top of the screen
+    fn shots_at_ufo(&mut self, player: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let mut rng = RoscRng;
+        let count = if player == 0 { self.player1_projectiles.len() } else { self.player2_projectiles.len() };
+        for i in 0..count {
+            let shot = if player == 0 { self.player1_projectiles[i] } else { self.player2_projectiles[i] };
+            if shot.2 == false {
+                continue;
+            }
+            let hit = self.ufo_active && shot.0 as i16 + 1 >= self.ufo_x && shot.0 as i16 <= self.ufo_x + UFO_WIDTH - 1 &&
+                shot.1 - 1 <= UFO_Y + UFO_HEIGHT - 1 && shot.1 + 2 >= UFO_Y;
+            if hit == false && shot.1 > SHOT_TOP + 1 {
+                continue;
+            }
+            if player == 0 {
+                self.player1_projectiles[i].2 = false;
+            } else {
+                self.player2_projectiles[i].2 = false;
+            }
+            Rectangle::new(Point::new(shot.0 as i32, shot.1 as i32), Size::new(2, 4))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(screen)
+                .unwrap();
+            if hit {
+                Rectangle::new(Point::new(self.ufo_x as i32, UFO_Y as i32), Size::new(UFO_WIDTH as u32, UFO_HEIGHT as u32))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                    .draw(screen)
+                    .unwrap();
+                self.ufo_active = false;
+                self.ufo_timer = rng.gen_range(1500..3000);
+                self.bonus_points = UFO_POINTS.choose(&mut rng).unwrap_or(&50) * self.level as u64;
+                self.score += self.bonus_points;
+                draw_score(self.score, screen);
+                // the points show up where the ufo was hit
+                self.bonus_x = self.ufo_x.clamp(0, 100);
+                self.bonus_timer = BONUS_FRAMES;
+                self.draw_bonus(screen);
+            }
+        }
+    }
+
+    fn draw_bonus(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let mut temp: String<20> = String::new();
+        fmt::write(&mut temp, format_args!("{}", self.bonus_points)).unwrap();
+        Text::new(&temp, Point::new(self.bonus_x as i32, UFO_Y as i32 + 5), MonoTextStyle::new(&FONT_5X8, Rgb565::MAGENTA))
+            .draw(screen).unwrap();
+    }
+
+    fn update_frame(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> (bool, bool) {
+        //check collision
+        // info!("lives {} {}", self.player1_lives, self.player2_lives);
+        if self.player1_cooldown > 0 {
+            self.player1_cooldown -= 1;
+        }
+        if self.player2_cooldown > 0 {
+            self.player2_cooldown -= 1;
+        }
+        self.shots_into_bunkers(0, screen);
+        self.shots_into_bunkers(1, screen);
+        self.shots_at_ufo(0, screen);
+        self.shots_at_ufo(1, screen);
+        self.update_ufo(screen);
+        self.update_boss(screen);
+        // info!("{} {} {} {} {}", self.enemies[0][0].1, self.enemies[0][1].1, self.enemies[0][2].1, self.enemies[0][3].1, self.enemies[0][4].1);
+        // info!("{} {} {} {} {}", self.enemies[1][0].1, self.enemies[1][1].1, self.enemies[1][2].1, self.enemies[1][3].1, self.enemies[1][4].1);
+        // info!("{} {} {} {} {}", self.enemies[2][0].1, self.enemies[2][1].1, self.enemies[2][2].1, self.enemies[2][3].1, self.enemies[2][4].1);
+        // info!("{} {} {} {} {}", self.enemies[3][0].1, self.enemies[3][1].1, self.enemies[3][2].1, self.enemies[3][3].1, self.enemies[3][4].1);
+        // info!("enemies left {}", self.enemies_count);
+        let bottom = self.rows as u8 * SPACING + self.offset_y;
+        for player in 0..2 {
+            let projectiles = if player == 0 { &mut *self.player1_projectiles } else { &mut *self.player2_projectiles };
+            projectiles.retain(|projectile| projectile.2);
+            for i in 0..projectiles.len() {
+                let projectiles = if player == 0 { &mut *self.player1_projectiles } else { &mut *self.player2_projectiles };
+                let (x, y, _) = projectiles[i];
+                if y < SHOT_TOP {
+                    projectiles[i].2 = false;
+                    continue;
+                }
+                if y > bottom {
+                    continue;
+                }
+                if self.shot_hits_enemy(x, y, screen) {
+                    let projectiles = if player == 0 { &mut *self.player1_projectiles } else { &mut *self.player2_projectiles };
+                    projectiles[i].2 = false;
                 }
             }
         }
+        self.update_lowest();
         // info!("enemies: {}", self.enemies_count);
         if self.enemies_count == 0 {
             self.level += 1;
@@ -495,7 +836,7 @@ impl <'a> SpaceInvaders<'a> {
                 10 => {}
                 t => { 
                     self.last_row[t].3 = true;
-                    self.projectile_cooldown = 100;
+                    self.projectile_cooldown = self.fire_cooldown;
                 }
             }
         } else {
@@ -521,12 +862,35 @@ impl <'a> SpaceInvaders<'a> {
         // }
 
         for i in 0..self.enemy_projectiles.len() {
-            // info!("enemy proj coords {} {}", self.enemy_projectiles[i].0, self.enemy_projectiles[i].1);
-            self.enemy_projectiles[i].1 += 1;
-            if self.enemy_projectiles[i].1 >= 146 && self.enemy_projectiles[i].1 <= 149 {
+            // info!("enemy proj coords {} {}", self.enemy_projectiles[i].x, self.enemy_projectiles[i].y);
+            self.enemy_projectiles[i].y += 1;
+            self.enemy_projectiles[i].x += self.enemy_projectiles[i].dx;
+            let shot = self.enemy_projectiles[i];
+            if shot.x < 0 || shot.x > 126 * 16 {
+                // aimed shots can leave through the sides
+                self.enemy_projectiles[i].active = false;
+                self.release(shot.column);
+                Rectangle::new(Point::new(shot.prev_px(), shot.y as i32 - 1), Size::new(2, 4))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                    .draw(screen)
+                    .unwrap();
+                continue;
+            }
+            let x = shot.px();
+            if let Some((bunker, col, row)) = self.bunker_hit(x, shot.y, 4, true) {
+                self.enemy_projectiles[i].active = false;
+                self.release(shot.column);
+                Rectangle::new(Point::new(shot.prev_px(), shot.y as i32 - 1), Size::new(2, 4))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                    .draw(screen)
+                    .unwrap();
+                self.carve(bunker, col, row, screen);
+                continue;
+            }
+            if shot.y >= 146 && shot.y <= 149 {
                 if self.player1_lives > 0 {
-                    if self.enemy_projectiles[i].0 + 1 >= self.player1_pos && self.enemy_projectiles[i].0 <= self.player1_pos + PLAYER_WIDTH as u8 - 1 {
-                        self.last_row[self.enemy_projectiles[i].2 as usize].3 = false;
+                    if x + 1 >= self.player1_pos && x <= self.player1_pos + PLAYER_WIDTH as u8 - 1 {
+                        self.release(shot.column);
                         if self.player1_lives > 0 {
                             self.player1_lives -= 1;
                             Rectangle::new(Point::new(107, 4), Size::new(20, 6))
@@ -544,8 +908,8 @@ impl <'a> SpaceInvaders<'a> {
                                     .unwrap();
                             }
                         }
-                        self.enemy_projectiles[i].3 = false;
-                        Rectangle::new(Point::new(self.enemy_projectiles[i].0 as i32, self.enemy_projectiles[i].1 as i32 - 1), Size::new(2, 4))
+                        self.enemy_projectiles[i].active = false;
+                        Rectangle::new(Point::new(shot.prev_px(), shot.y as i32 - 1), Size::new(2, 4))
                             .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
                             .draw(screen)
                             .unwrap();
@@ -553,8 +917,8 @@ impl <'a> SpaceInvaders<'a> {
                 }
 
                 if self.player2_lives > 0 {
-                    if self.enemy_projectiles[i].0 + 1 >= self.player2_pos && self.enemy_projectiles[i].0 <= self.player2_pos + PLAYER_WIDTH as u8 - 1 {
-                        self.last_row[self.enemy_projectiles[i].2 as usize].3 = false;
+                    if x + 1 >= self.player2_pos && x <= self.player2_pos + PLAYER_WIDTH as u8 - 1 {
+                        self.release(shot.column);
                         if self.player2_lives > 0 {
                             self.player2_lives -= 1;
                             Rectangle::new(Point::new(107, 12), Size::new(20, 6))
@@ -573,42 +937,40 @@ impl <'a> SpaceInvaders<'a> {
                                     .unwrap();
                             }
                         }
-                        self.enemy_projectiles[i].3 = false;
-                        Rectangle::new(Point::new(self.enemy_projectiles[i].0 as i32, self.enemy_projectiles[i].1 as i32 - 1), Size::new(2, 4))
+                        self.enemy_projectiles[i].active = false;
+                        Rectangle::new(Point::new(shot.prev_px(), shot.y as i32 - 1), Size::new(2, 4))
                             .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
                             .draw(screen)
                             .unwrap();
                     } 
                 }
             }
-            if self.enemy_projectiles[i].1 > 160 {
-                self.enemy_projectiles[i].3 = false;
-                self.last_row[self.enemy_projectiles[i].2 as usize].3 = false;
+            if shot.y > 160 {
+                self.enemy_projectiles[i].active = false;
+                self.release(shot.column);
                 // info!("projectile out of scope");
             }
         }
         self.enemy_projectiles
-            .retain(|active| active.3);   
+            .retain(|shot| shot.active);   
         
         for projectile in self.player1_projectiles.iter_mut() {
             projectile.1 -= 1;
         }
         self.player1_projectiles
-            .retain(|&(_, y, _)| y >= 60);
+            .retain(|&(_, y, _)| y >= SHOT_TOP);
     
         for projectile in self.player2_projectiles.iter_mut() {
             // info!("{} {}", projectile.0, projectile.1);
             projectile.1 -= 1;
         }
         self.player2_projectiles
-            .retain(|&(_, y, _)| y >= 60);
+            .retain(|&(_, y, _)| y >= SHOT_TOP);
         
 
-        unsafe {
-            // info!("low {}", self.lowest_enemy * SPACING + OFFSET_Y);
-            if self.lowest_enemy * SPACING + OFFSET_Y + self.lowest_height >= 150 {
-                return (false, false)
-            }
+        // info!("low {}", self.lowest_enemy * SPACING + self.offset_y);
+        if self.lowest_enemy * SPACING + self.offset_y + self.lowest_height >= 150 {
+            return (false, false)
         }
         if self.player1_lives == 0 && self.player2_lives == 0 {
             return (false, false)
@@ -619,55 +981,54 @@ impl <'a> SpaceInvaders<'a> {
             (false, true)
         } else {
             // shift enemies
-            unsafe {
-                match self.shift {
-                    true => {
-                        if OFFSET_X < 4 {
-                            OFFSET_X += 1;
-                        } else {
-                            OFFSET_Y += 10;
-                            self.shift = false;
-                        }
+            match self.shift {
+                true => {
+                    if self.offset_x < self.max_offset {
+                        self.offset_x += 1;
+                    } else {
+                        self.offset_y += 10;
+                        self.shift = false;
                     }
-                    false => {
-                        if OFFSET_X > 0 {
-                            OFFSET_X -= 1;
-                        } else {
-                            OFFSET_Y += 10;
-                            self.shift = true;
-                        }
+                }
+                false => {
+                    if self.offset_x > 0 {
+                        self.offset_x -= 1;
+                    } else {
+                        self.offset_y += 10;
+                        self.shift = true;
                     }
                 }
             }
+            self.clamp_boss();
             self.step = self.speed;
             (true, true)
         }
 
     }
 
+    // picks one of the lowest enemies to fire, the more likely the higher its class fire rate, bosses fire on their own
     fn choose_enemy(&mut self) -> u8 {
-        let available: Vec<&(Enemy, u8, u8, bool), 5> = self.last_row.iter().filter(|active_projectile| active_projectile.3 == false && active_projectile.0 != Enemy::Class1 && active_projectile.0 != Enemy::None).collect();
-        // info!("AAAAAAAAAAAAAAAa");
-        // info!("available enemies {}", available.len());
-        let mut rng = RoscRng;
-        match available.choose(&mut rng) {
-            Some(t) => {
-                unsafe {
-                    if t.0 == Enemy::Boss1 || t.0 == Enemy::Boss2 {
-                        self.enemy_projectiles.push(((t.1 + OFFSET_X) * SPACING + 1, (t.2 * SPACING) + OFFSET_Y - 30, t.1,  true)).unwrap();
-                        self.enemy_projectiles.push(((t.1 + OFFSET_X) * SPACING + 13, (t.2 * SPACING) + OFFSET_Y - 25, t.1,  true)).unwrap();
-                        self.enemy_projectiles.push(((t.1 + OFFSET_X) * SPACING + 26, (t.2 * SPACING) + OFFSET_Y - 25, t.1,  true)).unwrap();
-                        self.enemy_projectiles.push(((t.1 + OFFSET_X) * SPACING + 38, (t.2 * SPACING) + OFFSET_Y - 30, t.1,  true)).unwrap();
-                    } else {
-                        self.enemy_projectiles.push(((t.1 + OFFSET_X) * SPACING + 1, (t.2 * SPACING) + OFFSET_Y + ENEMY_HEIGHT as u8, t.1,  true)).unwrap();
-                    }
-                }
-                t.1
-            },
-            None => {
-                10
+        let available: Vec<(Enemy, u8, u8, bool), MAX_COLS> = self.last_row.iter().filter(|active_projectile| active_projectile.3 == false && active_projectile.0 != Enemy::None && !is_boss(active_projectile.0) && CLASSES[class(active_projectile.0)].1 > 0).cloned().collect();
+        let total: u32 = available.iter().map(|t| CLASSES[class(t.0)].1 as u32).sum();
+        if total == 0 {
+            return 10;
+        }
+        let mut pick = RoscRng.gen_range(0..total);
+        for t in available.iter() {
+            let weight = CLASSES[class(t.0)].1 as u32;
+            if pick >= weight {
+                pick -= weight;
+                continue;
             }
+            let left = self.enemy_x(t.2 as usize, t.1 as usize);
+            let top = t.2 * SPACING + self.offset_y;
+            match self.enemy_projectiles.push(EnemyShot::new(left + 1, top + ENEMY_HEIGHT as u8, 0, t.1)) {
+                Ok(_) => {}
+                Err(_) => {}
+            }
+            return t.1;
         }
+        10
     }
 
     async fn draw(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
@@ -703,19 +1064,42 @@ impl <'a> SpaceInvaders<'a> {
             fmt::write(&mut temp, format_args!("x {}", self.player2_lives)).unwrap();
             Text::new(&temp, Point::new(110, 17), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
                 .draw(screen).unwrap();
-            
+            self.draw_bunkers(screen);
+            self.draw_boss_hp(screen);
         }
         
         let aux = self.update_frame(screen);
         if aux.0 == true {
-            Rectangle::new(Point::new( 0 , 50), Size::new(128, 150))
-                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
-                .draw(screen)
-                .unwrap();
-            for i in 0..4 as u8 {
-                for j in 0..5 as u8 {
+            let bottom = self.lowest_enemy * SPACING + self.offset_y + self.lowest_height;
+            if bottom <= BUNKER_Y {
+                // the enemies are still above the bunkers, leave them alone
+                Rectangle::new(Point::new( 0 , 50), Size::new(128, (BUNKER_Y - 50) as u32))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                    .draw(screen)
+                    .unwrap();
+            } else {
+                // enemies marching through the bunkers wear away what they cover
+                for i in 0..self.rows as u8 {
+                    for j in 0..self.cols as u8 {
+                        let (width, height) = match self.enemies[i as usize][j as usize].0 {
+                            Enemy::None => continue,
+                            Enemy::Boss1 | Enemy::Boss2 => (BOSS_WIDTH, BOSS_HEIGHT),
+                            _ => (ENEMY_WIDTH, ENEMY_HEIGHT),
+                        };
+                        let (x, y) = (self.enemy_x(i as usize, j as usize) as i32, (i * SPACING + self.offset_y) as i32);
+                        self.clear_bunkers(x, y, width as i32, height as i32);
+                    }
+                }
+                Rectangle::new(Point::new( 0 , 50), Size::new(128, 150))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                    .draw(screen)
+                    .unwrap();
+                self.draw_bunkers(screen);
+            }
+            for i in 0..self.rows as u8 {
+                for j in 0..self.cols as u8 {
                     // info!("i j {} {}",i ,j);
-                    draw_enemy(j, i, &self.enemies[i as usize][j as usize].0, screen);
+                    self.draw_cell(i as usize, j as usize, screen);
                 }
             }
         }
@@ -744,11 +1128,11 @@ impl <'a> SpaceInvaders<'a> {
             }
         }
         for projectile in self.enemy_projectiles.iter() {
-            Rectangle::new(Point::new(projectile.0 as i32, projectile.1 as i32 - 1), Size::new(2, 4))
+            Rectangle::new(Point::new(projectile.prev_px(), projectile.y as i32 - 1), Size::new(2, 4))
                 .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
                 .draw(screen)
                 .unwrap();
-            Rectangle::new(Point::new(projectile.0 as i32, projectile.1 as i32), Size::new(2, 4))
+            Rectangle::new(Point::new(projectile.px() as i32, projectile.y as i32), Size::new(2, 4))
                 .into_styled(PrimitiveStyle::with_fill(Rgb565::CYAN))
                 .draw(screen)
                 .unwrap();    
@@ -891,7 +1275,7 @@ impl <'a> SpaceInvaders<'a> {
                                 INPUT_SIGNAL.reset();
                             },
                             MenuOption::Exit => {
-                                unsafe { CURRENT = 0 };
+                                set_current(0);
                                 return;
                             }
                             _ => {}
@@ -911,7 +1295,7 @@ impl <'a> SpaceInvaders<'a> {
                         INPUT_SIGNAL.reset();
                     },
                     MenuOption::Exit => {
-                        unsafe { CURRENT = 0 };
+                        set_current(0);
                         return;
                     }
                     _ => {}
@@ -954,10 +1338,18 @@ impl <'a> SpaceInvaders<'a> {
         fmt::write(&mut temp, format_args!("x {}", self.player2_lives)).unwrap();
         Text::new(&temp, Point::new(110, 17), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
             .draw(screen).unwrap();
-        for i in 0..4 as u8 {
-            for j in 0..5 as u8 {
+        self.draw_bunkers(screen);
+        self.draw_boss_hp(screen);
+        if self.ufo_active {
+            draw_sprite(self.ufo_x as i32, UFO_Y as i32, &UFO_SPRITE, Rgb565::MAGENTA, screen);
+        }
+        if self.bonus_timer > 0 {
+            self.draw_bonus(screen);
+        }
+        for i in 0..self.rows as u8 {
+            for j in 0..self.cols as u8 {
                 // info!("i j {} {}",i ,j);
-                draw_enemy(j, i, &self.enemies[i as usize][j as usize].0, screen);
+                self.draw_cell(i as usize, j as usize, screen);
             }
         }
         for projectile in self.player1_projectiles.iter() {
@@ -981,11 +1373,11 @@ impl <'a> SpaceInvaders<'a> {
                 .unwrap();    
         }
         for projectile in self.enemy_projectiles.iter() {
-            Rectangle::new(Point::new(projectile.0 as i32, projectile.1 as i32 - 1), Size::new(2, 4))
+            Rectangle::new(Point::new(projectile.prev_px(), projectile.y as i32 - 1), Size::new(2, 4))
                 .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
                 .draw(screen)
                 .unwrap();
-            Rectangle::new(Point::new(projectile.0 as i32, projectile.1 as i32), Size::new(2, 4))
+            Rectangle::new(Point::new(projectile.px() as i32, projectile.y as i32), Size::new(2, 4))
                 .into_styled(PrimitiveStyle::with_fill(Rgb565::CYAN))
                 .draw(screen)
                 .unwrap();    
@@ -1014,42 +1406,81 @@ impl <'a> SpaceInvaders<'a> {
     }       
 }
 
-fn draw_enemy(posx: u8, posy: u8, enemy: &Enemy, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
-    unsafe {
-        match enemy {
-            Enemy::Class1 => {
-                Rectangle::new(Point::new(((posx+ OFFSET_X) * SPACING) as i32, ((posy * SPACING) + OFFSET_Y) as i32), Size::new(ENEMY_WIDTH, ENEMY_HEIGHT))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::GREEN))
-                    .draw(screen)
-                    .unwrap();
-            }
-            Enemy::Class2 => {
-                Rectangle::new(Point::new(((posx+ OFFSET_X) * SPACING) as i32, ((posy * SPACING) + OFFSET_Y) as i32), Size::new(ENEMY_WIDTH, ENEMY_HEIGHT))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
-                    .draw(screen)
-                    .unwrap();
-            }
-            Enemy::Class3 => {
-                Rectangle::new(Point::new(((posx+ OFFSET_X) * SPACING) as i32, ((posy * SPACING) + OFFSET_Y) as i32), Size::new(ENEMY_WIDTH, ENEMY_HEIGHT))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
-                    .draw(screen)
-                    .unwrap();
-            }
-            Enemy::Boss1 => {
-                Rectangle::new(Point::new(((posx+ OFFSET_X) * SPACING) as i32, ((posy * SPACING) + OFFSET_Y) as i32), Size::new(BOSS_WIDTH, BOSS_HEIGHT))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::GREEN))
-                    .draw(screen)
-                    .unwrap();
-            }
-            Enemy::Boss2 => {
-                Rectangle::new(Point::new(((posx+ OFFSET_X) * SPACING) as i32, ((posy * SPACING) + OFFSET_Y) as i32), Size::new(BOSS_WIDTH, BOSS_HEIGHT))
-                    .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_ORANGE))
-                    .draw(screen)
-                    .unwrap();
-            }
-            _ => {
-
+// draws every run of '#' in a row of the sprite as one rectangle
+fn draw_sprite(x: i32, y: i32, sprite: &[&str], color: Rgb565, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+    for (row, line) in sprite.iter().enumerate() {
+        let mut start = None;
+        for (col, pixel) in line.chars().chain(core::iter::once('.')).enumerate() {
+            match (pixel, start) {
+                ('#', None) => start = Some(col),
+                ('#', Some(_)) => {}
+                (_, Some(first)) => {
+                    Rectangle::new(Point::new(x + first as i32, y + row as i32), Size::new((col - first) as u32, 1))
+                        .into_styled(PrimitiveStyle::with_fill(color))
+                        .draw(screen)
+                        .unwrap();
+                    start = None;
+                }
+                _ => {}
             }
         }
     }
-}
\ No newline at end of file
+}
+
+fn draw_score(score: u64, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+    Rectangle::new(Point::new(34, 12), Size::new(64, 8))
+        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+        .draw(screen)
+        .unwrap();
+    let mut temp: String<20> = String::new();
+    fmt::write(&mut temp, format_args!("{}", score)).unwrap();
+    Text::new( &temp, Point::new(35, 18), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
+        .draw(screen).unwrap();
+}
+
+// index of the enemy in the CLASSES table
+fn class(enemy: Enemy) -> usize {
+    match enemy {
+        Enemy::Class1 | Enemy::None => 0,
+        Enemy::Class2 => 1,
+        Enemy::Class3 => 2,
+        Enemy::Boss1 => 3,
+        Enemy::Boss2 => 4,
+    }
+}
+
+fn enemy_size(enemy: Enemy) -> (u32, u32) {
+    match enemy {
+        Enemy::None => (0, 0),
+        Enemy::Boss1 | Enemy::Boss2 => (BOSS_WIDTH, BOSS_HEIGHT),
+        _ => (ENEMY_WIDTH, ENEMY_HEIGHT),
+    }
+}
+
+fn is_boss(enemy: Enemy) -> bool {
+    enemy == Enemy::Boss1 || enemy == Enemy::Boss2
+}
+
+// bosses turn yellow and then red as they lose hit points
+fn boss_color(phase: u8) -> Rgb565 {
+    match phase {
+        0 => Rgb565::GREEN,
+        1 => Rgb565::YELLOW,
+        _ => Rgb565::RED,
+    }
+}
+
+fn draw_enemy(x: u8, y: u8, enemy: &Enemy, phase: u8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+    let color = match enemy {
+        Enemy::Class1 => Rgb565::GREEN,
+        Enemy::Class2 => Rgb565::CSS_ORANGE,
+        Enemy::Class3 => Rgb565::RED,
+        Enemy::Boss1 | Enemy::Boss2 => boss_color(phase),
+        Enemy::None => return,
+    };
+    let (width, height) = enemy_size(*enemy);
+    Rectangle::new(Point::new(x as i32, y as i32), Size::new(width, height))
+        .into_styled(PrimitiveStyle::with_fill(color))
+        .draw(screen)
+        .unwrap();
+}
@@ -0,0 +1,141 @@
+// Space Invaders waves, one string per line
+// the first line holds the wave: frames between two steps of the march, frames between two enemy shots,
+// then how the bosses attack: 'spread' four shots in a fan, 'twin' one shot from each side, 'wall' a row of five
+// every other line is a row of up to 7 enemies, at most 5 rows, '.' empty, '1' '2' '3' the enemy classes,
+// 'A' and 'B' the two bosses, a boss is as wide as almost three cells so leave the next two empty
+// once the last wave is cleared the waves start over, faster and tougher
+pub const WAVES: [&[&str]; 15] = [
+    // 1
+    &[
+        "300 100",
+        "11111",
+        "11111",
+        "11111",
+        "11111",
+    ],
+    // 2
+    &[
+        "296 100",
+        "22222",
+        "11111",
+        "11111",
+        "11111",
+    ],
+    // 3
+    &[
+        "294 100",
+        "22222",
+        "22222",
+        "11111",
+        "11111",
+    ],
+    // 4
+    &[
+        "292 100",
+        "22222",
+        "22222",
+        "22222",
+        "22222",
+    ],
+    // 5, first boss
+    &[
+        "290 100 spread",
+        ".A...",
+        ".....",
+        "33333",
+    ],
+    // 6
+    &[
+        "280 95",
+        "1111111",
+        "1111111",
+        "1111111",
+        "1111111",
+    ],
+    // 7
+    &[
+        "270 90",
+        "2222222",
+        "1111111",
+        "1111111",
+        "1111111",
+    ],
+    // 8
+    &[
+        "260 90",
+        "3333333",
+        "2222222",
+        "1111111",
+        "1111111",
+    ],
+    // 9
+    &[
+        "250 85",
+        "3.3.3.3",
+        "2222222",
+        "2222222",
+        "1111111",
+        "1111111",
+    ],
+    // 10, second boss
+    &[
+        "240 80 twin",
+        ".B...",
+        ".....",
+        "33333",
+        "22222",
+    ],
+    // 11
+    &[
+        "230 80",
+        "3333333",
+        "3333333",
+        "2222222",
+        "2222222",
+    ],
+    // 12
+    &[
+        "220 75",
+        "2323232",
+        "3232323",
+        "2222222",
+        "1111111",
+        "1111111",
+    ],
+    // 13
+    &[
+        "210 70",
+        "3333333",
+        "3333333",
+        "3333333",
+        "2222222",
+        "2222222",
+    ],
+    // 14
+    &[
+        "200 65",
+        "33.3.33",
+        "3333333",
+        "3333333",
+        "2222222",
+        "2222222",
+    ],
+    // 15, both bosses
+    &[
+        "190 60 wall",
+        ".A..B..",
+        ".......",
+        "3333333",
+        "3333333",
+    ],
+];
+
+// for '1' '2' '3' 'A' 'B': hit points, how likely the enemy is picked when the grid fires
+// (0 never fires, bosses fire volleys of their own) and the points it is worth times the level
+pub const CLASSES: [(u8, u8, u64); 5] = [
+    (1, 0, 50),
+    (2, 20, 75),
+    (3, 40, 100),
+    (20, 0, 500),
+    (20, 0, 1000),
+];
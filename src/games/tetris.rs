@@ -0,0 +1,659 @@
+use core::fmt;
+
+use embassy_embedded_hal::shared_bus::blocking::spi::SpiDevice;
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_rp::{
+    clocks::RoscRng, gpio::Output, spi::Spi
+};
+
+use mipidsi::interface::SpiInterface;
+use mipidsi::models::ST7735s;
+
+use embedded_graphics::{
+    mono_font::{ascii::{FONT_10X20, FONT_6X10}, iso_8859_14::FONT_5X8, MonoTextStyle}, pixelcolor::Rgb565, prelude::*, primitives::{
+        PrimitiveStyle, PrimitiveStyleBuilder, Rectangle
+    }, text::Text
+};
+use embassy_futures::select::{select, Either};
+use embassy_time::{Duration, Instant, Timer};
+
+use heapless::{
+    Deque, String
+};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::{menu::selector::Menu, INPUT_SIGNAL};
+use crate::set_current;
+
+use {defmt_rtt as _, panic_probe as _};
+use defmt::*;
+
+use rust_pico_console::{Input, MenuOption};
+
+pub const WIDTH: usize = 10;
+// 20 rows on screen and 2 hidden above them where the pieces spawn
+pub const HEIGHT: usize = 22;
+const HIDDEN: usize = 2;
+const CELL: i32 = 6;
+const MINI: i32 = 3;
+const WELL_Y: i32 = 35;
+// x of the well border, one well in the middle or one on each half of the screen
+const SINGLE_X: i32 = 33;
+const VERSUS_X: [i32; 2] = [1, 65];
+// cell value of a garbage row, pieces are 1 to 7
+const GARBAGE: u8 = 8;
+// milliseconds between two steps down, by level
+const GRAVITY: [u64; 15] = [800, 720, 630, 550, 470, 380, 300, 220, 130, 100, 80, 70, 60, 50, 40];
+// points for 1 to 4 lines at once, times the level plus one
+const LINE_POINTS: [u32; 5] = [0, 100, 300, 500, 800];
+// garbage rows sent to the other player for 1 to 4 lines at once
+const ATTACK: [u8; 5] = [0, 0, 1, 2, 4];
+const OPTIONS: usize = 2;
+
+// I O T S Z J L in their spawn rotation, I and O in a 4x4 box, the rest in 3x3
+const PIECES: [[(i8, i8); 4]; 7] = [
+    [(0, 1), (1, 1), (2, 1), (3, 1)],
+    [(1, 0), (2, 0), (1, 1), (2, 1)],
+    [(1, 0), (0, 1), (1, 1), (2, 1)],
+    [(1, 0), (2, 0), (0, 1), (1, 1)],
+    [(0, 0), (1, 0), (1, 1), (2, 1)],
+    [(0, 0), (0, 1), (1, 1), (2, 1)],
+    [(2, 0), (0, 1), (1, 1), (2, 1)],
+];
+
+// SRS wall kicks for turning clockwise out of every rotation (y points up, as in the guideline),
+// turning back the other way uses the same offsets negated
+const KICKS: [[(i8, i8); 5]; 4] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+];
+const I_KICKS: [[(i8, i8); 5]; 4] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+];
+
+fn color(cell: u8) -> Rgb565 {
+    match cell {
+        1 => Rgb565::CYAN,
+        2 => Rgb565::YELLOW,
+        3 => Rgb565::CSS_PURPLE,
+        4 => Rgb565::GREEN,
+        5 => Rgb565::RED,
+        6 => Rgb565::BLUE,
+        7 => Rgb565::CSS_ORANGE,
+        GARBAGE => Rgb565::CSS_GRAY,
+        _ => Rgb565::BLACK,
+    }
+}
+
+// the cells of a piece turned clockwise `rotation` times, the O piece never turns
+fn cells(piece: u8, rotation: u8) -> [(i8, i8); 4] {
+    let mut cells = PIECES[piece as usize];
+    let size = if piece == 0 { 4 } else { 3 };
+    if piece != 1 {
+        for _ in 0..rotation {
+            for cell in cells.iter_mut() {
+                *cell = (size - 1 - cell.1, cell.0);
+            }
+        }
+    }
+    cells
+}
+
+struct Player {
+    piece: u8,
+    rotation: u8,
+    x: i8,
+    y: i8,
+    hold: Option<u8>,
+    // a piece can only be held once, until it locks
+    held: bool,
+    // upcoming pieces, refilled one shuffled bag of all seven at a time
+    queue: Deque<u8, 14>,
+    score: u32,
+    lines: u16,
+    // garbage rows coming in from the other player
+    pending: u8,
+    fall: Instant,
+    alive: bool,
+}
+
+impl Player {
+    fn new() -> Player {
+        Player {
+            piece: 0,
+            rotation: 0,
+            x: 3,
+            y: 0,
+            hold: None,
+            held: false,
+            queue: Deque::new(),
+            score: 0,
+            lines: 0,
+            pending: 0,
+            fall: Instant::now(),
+            alive: true,
+        }
+    }
+}
+
+pub struct Tetris<'a> {
+    wells: &'a mut [[[u8; WIDTH]; HEIGHT]; 2],
+    players: [Player; 2],
+    versus: bool,
+    start_level: u8,
+}
+
+impl <'a> Tetris<'a> {
+    pub fn new(wells: &'a mut [[[u8; WIDTH]; HEIGHT]; 2]) -> Tetris<'a> {
+        Tetris {
+            wells,
+            players: [Player::new(), Player::new()],
+            versus: false,
+            start_level: 0,
+        }
+    }
+
+    // empties the wells and deals the first pieces, also used to restart
+    pub fn init(&mut self) {
+        for p in 0..2 {
+            self.wells[p] = [[0; WIDTH]; HEIGHT];
+            self.players[p] = Player::new();
+            self.refill(p);
+            self.spawn(p);
+        }
+        self.players[1].alive = self.versus;
+    }
+
+    fn refill(&mut self, p: usize) {
+        while self.players[p].queue.len() < 7 {
+            let mut bag = [0, 1, 2, 3, 4, 5, 6];
+            let mut rng = RoscRng;
+            bag.shuffle(&mut rng);
+            for piece in bag {
+                match self.players[p].queue.push_back(piece) { Ok(_) => {} Err(_) => {} }
+            }
+        }
+    }
+
+    fn fits(&self, p: usize, piece: u8, rotation: u8, x: i8, y: i8) -> bool {
+        cells(piece, rotation).iter().all(|(cx, cy)| {
+            let (col, row) = (x + cx, y + cy);
+            col >= 0 && row >= 0 && (col as usize) < WIDTH && (row as usize) < HEIGHT
+                && self.wells[p][row as usize][col as usize] == 0
+        })
+    }
+
+    // the next piece enters at the top, false when there is no room for it
+    fn spawn_piece(&mut self, p: usize, piece: u8) -> bool {
+        let player = &mut self.players[p];
+        player.piece = piece;
+        player.rotation = 0;
+        player.x = 3;
+        player.y = 0;
+        player.fall = Instant::now();
+        self.fits(p, piece, 0, 3, 0)
+    }
+
+    fn spawn(&mut self, p: usize) -> bool {
+        let piece = self.players[p].queue.pop_front().unwrap_or(0);
+        self.refill(p);
+        self.players[p].held = false;
+        self.spawn_piece(p, piece)
+    }
+
+    fn level(&self, p: usize) -> u8 {
+        self.start_level + (self.players[p].lines / 10).min(u8::MAX as u16) as u8
+    }
+
+    fn gravity(&self, p: usize) -> u64 {
+        GRAVITY[(self.level(p) as usize).min(GRAVITY.len() - 1)]
+    }
+
+    // moves the piece if it fits, false when something is in the way
+    fn shift(&mut self, p: usize, dx: i8, dy: i8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let player = &self.players[p];
+        if !self.fits(p, player.piece, player.rotation, player.x + dx, player.y + dy) {
+            return false;
+        }
+        self.draw_piece(p, false, screen);
+        self.players[p].x += dx;
+        self.players[p].y += dy;
+        self.draw_piece(p, true, screen);
+        true
+    }
+
+    // turns the piece, trying the SRS kicks one after the other until one fits
+    fn rotate(&mut self, p: usize, clockwise: bool, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let player = &self.players[p];
+        if player.piece == 1 {
+            return;
+        }
+        let table = if player.piece == 0 { &I_KICKS } else { &KICKS };
+        let (rotation, kicks, sign) = if clockwise {
+            ((player.rotation + 1) % 4, table[player.rotation as usize], 1)
+        } else {
+            let rotation = (player.rotation + 3) % 4;
+            (rotation, table[rotation as usize], -1)
+        };
+        for (dx, dy) in kicks {
+            let (x, y) = (player.x + dx * sign, player.y - dy * sign);
+            if self.fits(p, player.piece, rotation, x, y) {
+                self.draw_piece(p, false, screen);
+                let player = &mut self.players[p];
+                player.rotation = rotation;
+                player.x = x;
+                player.y = y;
+                self.draw_piece(p, true, screen);
+                return;
+            }
+        }
+    }
+
+    // swaps the piece with the held one, or puts it away and takes the next one
+    fn hold(&mut self, p: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if self.players[p].held {
+            return;
+        }
+        self.draw_piece(p, false, screen);
+        let current = self.players[p].piece;
+        let fits = match self.players[p].hold {
+            Some(piece) => self.spawn_piece(p, piece),
+            None => self.spawn(p),
+        };
+        self.players[p].hold = Some(current);
+        self.players[p].held = true;
+        self.players[p].alive = fits;
+        self.draw_piece(p, true, screen);
+        self.draw_hud(p, screen);
+    }
+
+    // one step down, the piece locks when it can't go any further
+    fn fall(&mut self, p: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        self.players[p].fall = Instant::now();
+        if self.shift(p, 0, 1, screen) {
+            true
+        } else {
+            self.lock(p, screen);
+            false
+        }
+    }
+
+    fn lock(&mut self, p: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let player = &self.players[p];
+        let (piece, x, y) = (player.piece, player.x, player.y);
+        for (cx, cy) in cells(piece, player.rotation) {
+            self.wells[p][(y + cy) as usize][(x + cx) as usize] = piece + 1;
+        }
+
+        let cleared = self.clear_lines(p);
+        let level = self.level(p) as u32;
+        let player = &mut self.players[p];
+        player.score += LINE_POINTS[cleared as usize] * (level + 1);
+        player.lines += cleared as u16;
+        if self.versus {
+            // lines sent cancel the garbage on its way in first, the rest goes to the other player
+            let attack = ATTACK[cleared as usize];
+            let cancel = attack.min(self.players[p].pending);
+            self.players[p].pending -= cancel;
+            let other = &mut self.players[1 - p];
+            other.pending = (other.pending + attack - cancel).min(HEIGHT as u8);
+            if cleared == 0 && self.players[p].pending > 0 {
+                let rows = self.players[p].pending;
+                self.players[p].pending = 0;
+                if !self.add_garbage(p, rows) {
+                    self.players[p].alive = false;
+                }
+            }
+            self.draw_hud(1 - p, screen);
+        }
+        if self.players[p].alive {
+            self.players[p].alive = self.spawn(p);
+        }
+        self.draw_well(p, screen);
+        self.draw_hud(p, screen);
+    }
+
+    // drops every full row and moves the ones above down, returns how many were cleared
+    fn clear_lines(&mut self, p: usize) -> u8 {
+        let well = &mut self.wells[p];
+        let mut write = HEIGHT;
+        for read in (0..HEIGHT).rev() {
+            if well[read].iter().all(|cell| *cell != 0) {
+                continue;
+            }
+            write -= 1;
+            well[write] = well[read];
+        }
+        for row in 0..write {
+            well[row] = [0; WIDTH];
+        }
+        write as u8
+    }
+
+    // pushes garbage rows in from the bottom, all with the same gap, false when the stack is pushed out the top
+    fn add_garbage(&mut self, p: usize, rows: u8) -> bool {
+        let rows = rows as usize;
+        let well = &mut self.wells[p];
+        let overflow = well[..rows].iter().any(|row| row.iter().any(|cell| *cell != 0));
+        for row in 0..HEIGHT - rows {
+            well[row] = well[row + rows];
+        }
+        let gap = RoscRng.gen_range(0..WIDTH);
+        for row in HEIGHT - rows..HEIGHT {
+            well[row] = [GARBAGE; WIDTH];
+            well[row][gap] = 0;
+        }
+        !overflow
+    }
+
+    fn well_x(&self, p: usize) -> i32 {
+        if self.versus { VERSUS_X[p] } else { SINGLE_X }
+    }
+
+    fn draw_cell(&self, p: usize, col: i8, row: i8, cell: u8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        // the hidden rows are never drawn
+        if (row as usize) < HIDDEN {
+            return;
+        }
+        Rectangle::new(Point::new(self.well_x(p) + 1 + col as i32 * CELL, WELL_Y + 1 + (row as i32 - HIDDEN as i32) * CELL), Size::new(CELL as u32 - 1, CELL as u32 - 1))
+            .into_styled(PrimitiveStyle::with_fill(color(cell)))
+            .draw(screen)
+            .unwrap();
+    }
+
+    fn draw_piece(&self, p: usize, visible: bool, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let player = &self.players[p];
+        for (cx, cy) in cells(player.piece, player.rotation) {
+            self.draw_cell(p, player.x + cx, player.y + cy, if visible { player.piece + 1 } else { 0 }, screen);
+        }
+    }
+
+    fn draw_well(&self, p: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        for row in HIDDEN..HEIGHT {
+            for col in 0..WIDTH {
+                self.draw_cell(p, col as i8, row as i8, self.wells[p][row][col], screen);
+            }
+        }
+        if self.players[p].alive {
+            self.draw_piece(p, true, screen);
+        }
+    }
+
+    // a small piece for the hold and next boxes
+    fn draw_mini(&self, x: i32, y: i32, piece: Option<u8>, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        match piece {
+            Some(piece) => {
+                for (cx, cy) in cells(piece, 0) {
+                    Rectangle::new(Point::new(x + cx as i32 * MINI, y + cy as i32 * MINI), Size::new(MINI as u32, MINI as u32))
+                        .into_styled(PrimitiveStyle::with_fill(color(piece + 1)))
+                        .draw(screen)
+                        .unwrap();
+                }
+            }
+            None => {}
+        }
+    }
+
+    // score, lines, the held piece and what comes next, with the garbage on its way in when playing versus
+    fn draw_hud(&self, p: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        let player = &self.players[p];
+        let mut temp: String<24> = String::new();
+        if self.versus {
+            let x = self.well_x(p);
+            Rectangle::new(Point::new(x, 0), Size::new(62, 34))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(screen)
+                .unwrap();
+            fmt::write(&mut temp, format_args!("P{} {}", p + 1, player.score)).unwrap();
+            Text::new(&temp, Point::new(x, 8), MonoTextStyle::new(&FONT_5X8, if p == 0 { Rgb565::CSS_LIME_GREEN } else { Rgb565::YELLOW }))
+                .draw(screen).unwrap();
+            temp.clear();
+            fmt::write(&mut temp, format_args!("lines {}", player.lines)).unwrap();
+            Text::new(&temp, Point::new(x, 17), MonoTextStyle::new(&FONT_5X8, Rgb565::CSS_GRAY))
+                .draw(screen).unwrap();
+            Text::new("H", Point::new(x, 30), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
+                .draw(screen).unwrap();
+            self.draw_mini(x + 6, 24, player.hold, screen);
+            Text::new("N", Point::new(x + 24, 30), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
+                .draw(screen).unwrap();
+            self.draw_mini(x + 30, 24, player.queue.front().copied(), screen);
+            if player.pending > 0 {
+                temp.clear();
+                fmt::write(&mut temp, format_args!("+{}", player.pending)).unwrap();
+                Text::new(&temp, Point::new(x + 47, 30), MonoTextStyle::new(&FONT_5X8, Rgb565::RED))
+                    .draw(screen).unwrap();
+            }
+        } else {
+            Rectangle::new(Point::new(0, 0), Size::new(128, 34))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(screen)
+                .unwrap();
+            fmt::write(&mut temp, format_args!("Score {}", player.score)).unwrap();
+            Text::new(&temp, Point::new(2, 10), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+                .draw(screen).unwrap();
+            temp.clear();
+            fmt::write(&mut temp, format_args!("Lines {} Level {}", player.lines, self.level(p) + 1)).unwrap();
+            Text::new(&temp, Point::new(2, 24), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_GRAY))
+                .draw(screen).unwrap();
+            Rectangle::new(Point::new(0, 36), Size::new(32, 50))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(screen)
+                .unwrap();
+            Rectangle::new(Point::new(96, 36), Size::new(32, 50))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(screen)
+                .unwrap();
+            Text::new("hold", Point::new(4, 46), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
+                .draw(screen).unwrap();
+            self.draw_mini(8, 52, player.hold, screen);
+            Text::new("next", Point::new(100, 46), MonoTextStyle::new(&FONT_5X8, Rgb565::WHITE))
+                .draw(screen).unwrap();
+            for (index, piece) in player.queue.iter().take(3).enumerate() {
+                self.draw_mini(104, 52 + index as i32 * 10, Some(*piece), screen);
+            }
+        }
+    }
+
+    fn redraw(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let players = if self.versus { 2 } else { 1 };
+        for p in 0..players {
+            Rectangle::new(Point::new(self.well_x(p), WELL_Y), Size::new(WIDTH as u32 * CELL as u32 + 1, (HEIGHT - HIDDEN) as u32 * CELL as u32 + 1))
+                .into_styled(PrimitiveStyleBuilder::new().stroke_color(Rgb565::CSS_DARK_SEA_GREEN).stroke_width(1).build())
+                .draw(screen)
+                .unwrap();
+            self.draw_well(p, screen);
+            self.draw_hud(p, screen);
+        }
+    }
+
+    fn handle_input(&mut self, input: &Input, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let (p, action) = match input {
+            Input::Back => return false,
+            Input::Left => (0, Input::Left),
+            Input::Right => (0, Input::Right),
+            Input::Down => (0, Input::Down),
+            Input::Up => (0, Input::Up),
+            Input::Left_Shoot => (0, Input::Left_Shoot),
+            Input::Right_Shoot => (0, Input::Right_Shoot),
+            Input::Left2 if self.versus => (1, Input::Left),
+            Input::Right2 if self.versus => (1, Input::Right),
+            Input::Down2 if self.versus => (1, Input::Down),
+            Input::Up2 if self.versus => (1, Input::Up),
+            Input::Left2_Shoot if self.versus => (1, Input::Left_Shoot),
+            Input::Right2_Shoot if self.versus => (1, Input::Right_Shoot),
+            _ => return true,
+        };
+        if !self.players[p].alive {
+            return true;
+        }
+        match action {
+            Input::Left => { self.shift(p, -1, 0, screen); }
+            Input::Right => { self.shift(p, 1, 0, screen); }
+            // soft drop, one point for every row
+            Input::Down => {
+                if self.fall(p, screen) {
+                    self.players[p].score += 1;
+                }
+            }
+            Input::Up => self.rotate(p, true, screen),
+            Input::Left_Shoot => self.rotate(p, false, screen),
+            _ => self.hold(p, screen),
+        }
+        true
+    }
+
+    // pre-game settings, up and down pick a row, left and right change it, false when the players leave
+    async fn options(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let mut selected = 0;
+        self.draw_options(selected, screen);
+        loop {
+            Timer::after(Duration::from_millis(100)).await;
+            INPUT_SIGNAL.reset();
+            match INPUT_SIGNAL.wait().await {
+                Input::Up | Input::Up2 => {
+                    selected = if selected > 0 { selected - 1 } else { OPTIONS - 1 };
+                }
+                Input::Down | Input::Down2 => {
+                    selected = if selected + 1 < OPTIONS { selected + 1 } else { 0 };
+                }
+                input @ (Input::Left | Input::Left2 | Input::Right | Input::Right2) => {
+                    let forward = input == Input::Right || input == Input::Right2;
+                    match selected {
+                        0 => self.versus = !self.versus,
+                        _ => {
+                            let levels = GRAVITY.len() as u8;
+                            self.start_level = if forward { (self.start_level + 1) % levels } else { (self.start_level + levels - 1) % levels };
+                        }
+                    }
+                }
+                Input::Select => return true,
+                Input::Back => return false,
+                _ => continue,
+            }
+            self.draw_options(selected, screen);
+        }
+    }
+
+    fn draw_options(&self, selected: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        Text::new("Tetris", Point::new(34, 30), MonoTextStyle::new(&FONT_10X20, Rgb565::RED))
+            .draw(screen).unwrap();
+        for row in 0..OPTIONS {
+            let mut temp: String<24> = String::new();
+            match row {
+                0 => fmt::write(&mut temp, format_args!("Players: < {} >", if self.versus { 2 } else { 1 })).unwrap(),
+                _ => fmt::write(&mut temp, format_args!("Level: < {} >", self.start_level + 1)).unwrap(),
+            }
+            Text::new(&temp, Point::new(4, 60 + row as i32 * 15), MonoTextStyle::new(&FONT_6X10, if row == selected { Rgb565::WHITE } else { Rgb565::CSS_GRAY }))
+                .draw(screen).unwrap();
+        }
+        Text::new("e: play q: exit", Point::new(19, 150), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
+            .draw(screen).unwrap();
+    }
+
+    fn draw_results(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 120), Size::new(128, 40))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let players = if self.versus { 2 } else { 1 };
+        let mut temp: String<24> = String::new();
+        for p in 0..players {
+            temp.clear();
+            fmt::write(&mut temp, format_args!("P{}: {} ({} lines)", p + 1, self.players[p].score, self.players[p].lines)).unwrap();
+            Text::new(&temp, Point::new(4, 135 + p as i32 * 15), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+                .draw(screen).unwrap();
+        }
+    }
+
+    pub async fn game_loop(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if self.options(screen).await == false {
+            set_current(0);
+            return;
+        }
+        self.init();
+        self.redraw(screen);
+        loop {
+            match select(INPUT_SIGNAL.wait(), Timer::after(Duration::from_millis(10))).await {
+                Either::First(input) => {
+                    if self.handle_input(&input, screen) == false {
+                        // create pause menu
+                        let mut pause_menu: Menu<'_> = Menu::init("Pause menu", &[MenuOption::Resume, MenuOption::Restart, MenuOption::Exit], screen);
+                        let result: MenuOption = pause_menu.menu_loop(screen).await;
+                        match result {
+                            MenuOption::Resume | MenuOption::None => {}
+                            MenuOption::Restart => self.init(),
+                            MenuOption::Exit => {
+                                set_current(0);
+                                return;
+                            }
+                            _ => {}
+                        }
+                        // the pieces don't fall while the game is paused
+                        for player in self.players.iter_mut() {
+                            player.fall = Instant::now();
+                        }
+                        self.redraw(screen);
+                        Timer::after(Duration::from_millis(100)).await;
+                        INPUT_SIGNAL.reset();
+                    }
+                }
+                _ => {}
+            }
+            for p in 0..2 {
+                if self.players[p].alive && (Instant::now() - self.players[p].fall).as_millis() >= self.gravity(p) {
+                    self.fall(p, screen);
+                }
+            }
+
+            let over = if self.versus { !(self.players[0].alive && self.players[1].alive) } else { !self.players[0].alive };
+            if over {
+                info!("game over!");
+                let title = if !self.versus {
+                    "Game over"
+                } else if self.players[0].alive {
+                    "P1 wins!"
+                } else if self.players[1].alive {
+                    "P2 wins!"
+                } else {
+                    "Draw!"
+                };
+                let mut end_menu: Menu<'_> = Menu::init(title, &[MenuOption::Restart, MenuOption::Options, MenuOption::Exit], screen);
+                self.draw_results(screen);
+                let result: MenuOption = end_menu.menu_loop(screen).await;
+                match result {
+                    MenuOption::Restart | MenuOption::None => {}
+                    MenuOption::Options => {
+                        if self.options(screen).await == false {
+                            set_current(0);
+                            return;
+                        }
+                    }
+                    MenuOption::Exit => {
+                        set_current(0);
+                        return;
+                    }
+                    _ => {}
+                }
+                self.init();
+                self.redraw(screen);
+                Timer::after(Duration::from_millis(100)).await;
+                INPUT_SIGNAL.reset();
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use embassy_embedded_hal::shared_bus::blocking::spi::SpiDevice;
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_rp::{
+    gpio::Output, spi::Spi
+};
+
+use mipidsi::interface::SpiInterface;
+use mipidsi::models::ST7735s;
+
+use embedded_graphics::{
+    pixelcolor::Rgb565, prelude::*, primitives::{
+        Circle, Line, PrimitiveStyle, Rectangle
+    }
+};
+
+use crate::games::board::Rules;
+
+// 3x3, three in a row wins, the hard cpu searches the whole game and never loses
+pub const RULES: Rules = Rules {
+    title: "Tic-Tac-Toe",
+    cols: 3,
+    rows: 3,
+    line: 3,
+    gravity: false,
+    depths: [1, 2, 9],
+    colors: [Rgb565::CYAN, Rgb565::CSS_ORANGE],
+    background: Rgb565::BLACK,
+    draw_board,
+    draw_piece,
+};
+
+// grid lines between the cells
+fn draw_board(area: Rectangle, size: i32, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+    for k in 1..RULES.cols as i32 {
+        Rectangle::new(area.top_left + Point::new(k * size, 0), Size::new(1, area.size.height))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_GRAY))
+            .draw(screen)
+            .unwrap();
+    }
+    for k in 1..RULES.rows as i32 {
+        Rectangle::new(area.top_left + Point::new(0, k * size), Size::new(area.size.width, 1))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_GRAY))
+            .draw(screen)
+            .unwrap();
+    }
+}
+
+// x for player 1, o for player 2
+fn draw_piece(corner: Point, size: i32, player: u8, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+    let margin = size / 5;
+    match player {
+        1 => {
+            let style = PrimitiveStyle::with_stroke(RULES.colors[0], 3);
+            Line::new(corner + Point::new(margin, margin), corner + Point::new(size - margin, size - margin))
+                .into_styled(style)
+                .draw(screen)
+                .unwrap();
+            Line::new(corner + Point::new(size - margin, margin), corner + Point::new(margin, size - margin))
+                .into_styled(style)
+                .draw(screen)
+                .unwrap();
+        }
+        2 => {
+            Circle::new(corner + Point::new(margin, margin), (size - 2 * margin) as u32)
+                .into_styled(PrimitiveStyle::with_stroke(RULES.colors[1], 3))
+                .draw(screen)
+                .unwrap();
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,545 @@
+use core::fmt;
+
+use embassy_embedded_hal::shared_bus::blocking::spi::SpiDevice;
+
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embassy_rp::{
+    clocks::RoscRng, gpio::Output, spi::Spi
+};
+
+use mipidsi::interface::SpiInterface;
+use mipidsi::models::ST7735s;
+
+use embedded_graphics::{
+    mono_font::{ascii::{FONT_10X20, FONT_6X10}, iso_8859_14::FONT_5X8, MonoTextStyle}, pixelcolor::Rgb565, prelude::*, primitives::{
+        PrimitiveStyle, Rectangle
+    }, text::Text
+};
+use embassy_futures::select::{select, Either};
+use embassy_time::{Duration, Instant, Timer};
+
+use heapless::String;
+use rand::RngCore;
+
+use crate::{menu::selector::Menu, INPUT_SIGNAL};
+use crate::set_current;
+use rust_pico_console::snake_ai::{self, Grid, Rng};
+
+use {defmt_rtt as _, panic_probe as _};
+use defmt::*;
+
+use rust_pico_console::{Input, MenuOption};
+
+// the arena is 32x32 cells of 4 pixels under the hud, so one u32 holds a row of trails
+const SIZE: u8 = 32;
+const CELL: i32 = 4;
+const BOARD_Y: i32 = 32;
+const TICK: u64 = 80;
+// boost energy, a burst moves a rider two cells a tick and uses one energy every tick
+const BOOST_MAX: u8 = 30;
+const BOOST_BURST: u8 = 6;
+// ticks between two points of energy coming back
+const RECHARGE: u8 = 4;
+// how far ahead the cpu riders look for room
+const CPU_LOOKAHEAD: u16 = 64;
+const ROUND_GOALS: [u8; 3] = [3, 5, 7];
+const OPTIONS: usize = 5;
+// where every rider starts and which way it faces, 0 up 1 down 2 left 3 right like the snakes
+const STARTS: [((u8, u8), u8); 4] = [((3, 16), 3), ((28, 15), 2), ((15, 3), 1), ((16, 28), 0)];
+const COLORS: [Rgb565; 4] = [Rgb565::CYAN, Rgb565::CSS_ORANGE, Rgb565::CSS_LIME_GREEN, Rgb565::MAGENTA];
+
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+enum Slot {
+    Human,
+    Cpu,
+    Off,
+}
+
+#[derive(Clone)]
+#[derive(Copy)]
+struct Rider {
+    head: (u8, u8),
+    facing: u8,
+    // one turn per tick, like the snakes
+    updated: bool,
+    alive: bool,
+    energy: u8,
+    // ticks left in the current burst
+    boost: u8,
+    score: u8,
+}
+
+impl Rider {
+    fn new() -> Rider {
+        Rider {
+            head: (0, 0),
+            facing: 0,
+            updated: false,
+            alive: false,
+            energy: BOOST_MAX,
+            boost: 0,
+            score: 0,
+        }
+    }
+}
+
+pub struct Tron {
+    riders: [Rider; 4],
+    // every trail on the arena, one bit per cell, trails never shrink
+    trails: [u32; 32],
+    // player 1 is always a person, the second controller can play player 2
+    slots: [Slot; 4],
+    goal: usize,
+    boosts: bool,
+    round: u8,
+    ticks: u8,
+    rng: Rng,
+}
+
+impl Tron {
+    pub fn new() -> Tron {
+        Tron {
+            riders: [Rider::new(); 4],
+            trails: [0; 32],
+            slots: [Slot::Human, Slot::Human, Slot::Cpu, Slot::Off],
+            goal: 1,
+            boosts: true,
+            round: 0,
+            ticks: 0,
+            rng: Rng::new(1),
+        }
+    }
+
+    // a new match, the scores go back to 0
+    pub fn init(&mut self) {
+        for rider in self.riders.iter_mut() {
+            rider.score = 0;
+        }
+        self.round = 0;
+        self.rng = Rng::new(RoscRng.next_u32());
+        self.new_round();
+    }
+
+    fn new_round(&mut self) {
+        self.trails = [0; 32];
+        self.round += 1;
+        self.ticks = 0;
+        for r in 0..4 {
+            let (head, facing) = STARTS[r];
+            let rider = &mut self.riders[r];
+            rider.head = head;
+            rider.facing = facing;
+            rider.updated = false;
+            rider.alive = self.slots[r] != Slot::Off;
+            rider.energy = BOOST_MAX;
+            rider.boost = 0;
+            if rider.alive {
+                self.trails[head.1 as usize] |= 1 << head.0;
+            }
+        }
+    }
+
+    fn trail(&self, cell: (u8, u8)) -> bool {
+        (self.trails[cell.1 as usize] & (1 << cell.0)) != 0
+    }
+
+    fn turn(&mut self, r: usize, facing: u8) {
+        let rider = &mut self.riders[r];
+        if rider.alive && rider.facing != facing ^ 1 && rider.updated == false {
+            rider.facing = facing;
+            rider.updated = true;
+        }
+    }
+
+    fn start_boost(&mut self, r: usize) {
+        let rider = &mut self.riders[r];
+        if self.boosts && rider.alive && rider.boost == 0 {
+            rider.boost = BOOST_BURST.min(rider.energy);
+        }
+    }
+
+    // cpu riders go where there is the most room and boost now and then when their energy is full
+    fn think(&mut self) {
+        let empty = [0u32; 32];
+        for r in 0..4 {
+            if self.slots[r] != Slot::Cpu || !self.riders[r].alive {
+                continue;
+            }
+            let grid = Grid {
+                frame: &self.trails,
+                walls: &empty,
+                apples: &empty,
+                apple: (0, 0),
+                size: SIZE,
+                borders: true,
+            };
+            let rider = self.riders[r];
+            self.riders[r].facing = snake_ai::roam(&grid, rider.head, rider.facing, CPU_LOOKAHEAD, &mut self.rng);
+            if rider.energy == BOOST_MAX && self.rng.below(20) == 0 {
+                self.start_boost(r);
+            }
+        }
+    }
+
+    // every rider moves one cell, boosting ones twice, riders that hit a trail, a wall or each other crash
+    fn update_frame(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        self.think();
+        for substep in 0..2 {
+            let mut next: [Option<(u8, u8)>; 4] = [None; 4];
+            let mut moving = [false; 4];
+            for r in 0..4 {
+                let rider = self.riders[r];
+                moving[r] = rider.alive && (substep == 0 || rider.boost > 0);
+                if moving[r] {
+                    next[r] = snake_ai::step(rider.head, rider.facing, SIZE, true);
+                }
+            }
+            let mut crashed = [false; 4];
+            for r in 0..4 {
+                if !moving[r] {
+                    continue;
+                }
+                crashed[r] = match next[r] {
+                    None => true,
+                    Some(cell) => self.trail(cell) || (0..4).any(|other| other != r && moving[other] && next[other] == Some(cell)),
+                };
+            }
+            for r in 0..4 {
+                if !moving[r] {
+                    continue;
+                }
+                if crashed[r] {
+                    info!("rider {} crashed", r + 1);
+                    self.riders[r].alive = false;
+                    self.draw_cell(self.riders[r].head, COLORS[r], screen);
+                    continue;
+                }
+                let cell = next[r].unwrap_or(self.riders[r].head);
+                self.draw_cell(self.riders[r].head, COLORS[r], screen);
+                self.trails[cell.1 as usize] |= 1 << cell.0;
+                self.riders[r].head = cell;
+                self.draw_cell(cell, Rgb565::WHITE, screen);
+            }
+        }
+
+        self.ticks = self.ticks.wrapping_add(1);
+        for rider in self.riders.iter_mut() {
+            if rider.boost > 0 {
+                rider.boost -= 1;
+                rider.energy = rider.energy.saturating_sub(1);
+            } else if self.ticks % RECHARGE == 0 {
+                rider.energy = (rider.energy + 1).min(BOOST_MAX);
+            }
+            rider.updated = false;
+        }
+        if self.boosts {
+            self.draw_energy(screen);
+        }
+    }
+
+    // the last rider left, Some(None) when nobody made it
+    fn round_over(&self) -> Option<Option<usize>> {
+        let mut alive = (0..4).filter(|r| self.riders[*r].alive);
+        match (alive.next(), alive.next()) {
+            (Some(_), Some(_)) => None,
+            (Some(r), None) => Some(Some(r)),
+            _ => Some(None),
+        }
+    }
+
+    fn draw_cell(&self, cell: (u8, u8), color: Rgb565, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(cell.0 as i32 * CELL, BOARD_Y + cell.1 as i32 * CELL), Size::new(CELL as u32, CELL as u32))
+            .into_styled(PrimitiveStyle::with_fill(color))
+            .draw(screen)
+            .unwrap();
+    }
+
+    // one bar per rider under its score
+    fn draw_energy(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        for r in 0..4 {
+            if self.slots[r] == Slot::Off {
+                continue;
+            }
+            let x = r as i32 * 32;
+            let energy = self.riders[r].energy as u32;
+            Rectangle::new(Point::new(x + energy as i32, 12), Size::new(BOOST_MAX as u32 - energy, 3))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                .draw(screen)
+                .unwrap();
+            Rectangle::new(Point::new(x, 12), Size::new(energy, 3))
+                .into_styled(PrimitiveStyle::with_fill(if self.riders[r].boost > 0 { Rgb565::WHITE } else { COLORS[r] }))
+                .draw(screen)
+                .unwrap();
+        }
+    }
+
+    fn draw_hud(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, BOARD_Y as u32))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<24> = String::new();
+        for r in 0..4 {
+            if self.slots[r] == Slot::Off {
+                continue;
+            }
+            temp.clear();
+            fmt::write(&mut temp, format_args!("P{} {}", r + 1, self.riders[r].score)).unwrap();
+            Text::new(&temp, Point::new(r as i32 * 32, 8), MonoTextStyle::new(&FONT_5X8, COLORS[r]))
+                .draw(screen).unwrap();
+        }
+        temp.clear();
+        fmt::write(&mut temp, format_args!("round {}, first to {}", self.round, ROUND_GOALS[self.goal])).unwrap();
+        Text::new(&temp, Point::new(0, 25), MonoTextStyle::new(&FONT_5X8, Rgb565::CSS_GRAY))
+            .draw(screen).unwrap();
+        Rectangle::new(Point::new(0, BOARD_Y - 1), Size::new(128, 1))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_DARK_SEA_GREEN))
+            .draw(screen)
+            .unwrap();
+        if self.boosts {
+            self.draw_energy(screen);
+        }
+    }
+
+    fn redraw(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        self.draw_hud(screen);
+        // trails don't remember whose they are, they come back grey after a pause
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                if self.trail((x, y)) {
+                    self.draw_cell((x, y), Rgb565::CSS_GRAY, screen);
+                }
+            }
+        }
+        for r in 0..4 {
+            if self.riders[r].alive {
+                self.draw_cell(self.riders[r].head, Rgb565::WHITE, screen);
+            }
+        }
+    }
+
+    // the arrows turn, left+shoot and right+shoot turn and boost
+    fn handle_input(&mut self, input: &Input) -> bool {
+        let second = self.slots[1] == Slot::Human;
+        match input {
+            Input::Back => return false,
+            Input::Up => self.turn(0, 0),
+            Input::Down => self.turn(0, 1),
+            Input::Left => self.turn(0, 2),
+            Input::Right => self.turn(0, 3),
+            Input::Left_Shoot => {
+                self.turn(0, 2);
+                self.start_boost(0);
+            }
+            Input::Right_Shoot => {
+                self.turn(0, 3);
+                self.start_boost(0);
+            }
+            Input::Up2 if second => self.turn(1, 0),
+            Input::Down2 if second => self.turn(1, 1),
+            Input::Left2 if second => self.turn(1, 2),
+            Input::Right2 if second => self.turn(1, 3),
+            Input::Left2_Shoot if second => {
+                self.turn(1, 2);
+                self.start_boost(1);
+            }
+            Input::Right2_Shoot if second => {
+                self.turn(1, 3);
+                self.start_boost(1);
+            }
+            _ => {}
+        }
+        true
+    }
+
+    // pre-game settings, up and down pick a row, left and right change it, false when the players leave
+    async fn options(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> bool {
+        let mut selected = 0;
+        self.draw_options(selected, screen);
+        loop {
+            Timer::after(Duration::from_millis(100)).await;
+            INPUT_SIGNAL.reset();
+            match INPUT_SIGNAL.wait().await {
+                Input::Up | Input::Up2 => {
+                    selected = if selected > 0 { selected - 1 } else { OPTIONS - 1 };
+                }
+                Input::Down | Input::Down2 => {
+                    selected = if selected + 1 < OPTIONS { selected + 1 } else { 0 };
+                }
+                input @ (Input::Left | Input::Left2 | Input::Right | Input::Right2) => {
+                    let forward = input == Input::Right || input == Input::Right2;
+                    match selected {
+                        0 => {
+                            let choices = [Slot::Human, Slot::Cpu, Slot::Off];
+                            let index = choices.iter().position(|slot| *slot == self.slots[1]).unwrap_or(0);
+                            self.slots[1] = choices[if forward { (index + 1) % 3 } else { (index + 2) % 3 }];
+                        }
+                        1 | 2 => {
+                            let slot = &mut self.slots[selected + 1];
+                            *slot = if *slot == Slot::Off { Slot::Cpu } else { Slot::Off };
+                        }
+                        3 => {
+                            let count = ROUND_GOALS.len();
+                            self.goal = if forward { (self.goal + 1) % count } else { (self.goal + count - 1) % count };
+                        }
+                        _ => self.boosts = !self.boosts,
+                    }
+                }
+                // a round needs at least two riders
+                Input::Select if self.slots.iter().filter(|slot| **slot != Slot::Off).count() >= 2 => return true,
+                Input::Back => return false,
+                _ => continue,
+            }
+            self.draw_options(selected, screen);
+        }
+    }
+
+    fn draw_options(&self, selected: usize, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 0), Size::new(128, 160))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        Text::new("Tron", Point::new(44, 30), MonoTextStyle::new(&FONT_10X20, Rgb565::RED))
+            .draw(screen).unwrap();
+        let label = |slot: Slot| match slot {
+            Slot::Human => "Human",
+            Slot::Cpu => "CPU",
+            Slot::Off => "Off",
+        };
+        for row in 0..OPTIONS {
+            let mut temp: String<24> = String::new();
+            match row {
+                0..=2 => fmt::write(&mut temp, format_args!("P{}: < {} >", row + 2, label(self.slots[row + 1]))).unwrap(),
+                3 => fmt::write(&mut temp, format_args!("Rounds: < {} >", ROUND_GOALS[self.goal])).unwrap(),
+                _ => fmt::write(&mut temp, format_args!("Boost: < {} >", if self.boosts { "On" } else { "Off" })).unwrap(),
+            }
+            Text::new(&temp, Point::new(4, 55 + row as i32 * 15), MonoTextStyle::new(&FONT_6X10, if row == selected { Rgb565::WHITE } else { Rgb565::CSS_GRAY }))
+                .draw(screen).unwrap();
+        }
+        Text::new("e: play q: exit", Point::new(19, 150), MonoTextStyle::new(&FONT_6X10, Rgb565::CSS_ORANGE))
+            .draw(screen).unwrap();
+    }
+
+    fn draw_results(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        Rectangle::new(Point::new(0, 116), Size::new(128, 44))
+            .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+            .draw(screen)
+            .unwrap();
+        let mut temp: String<24> = String::new();
+        let mut line = 0;
+        for r in 0..4 {
+            if self.slots[r] == Slot::Off {
+                continue;
+            }
+            temp.clear();
+            fmt::write(&mut temp, format_args!("P{}: {} rounds", r + 1, self.riders[r].score)).unwrap();
+            Text::new(&temp, Point::new(10, 126 + line * 10), MonoTextStyle::new(&FONT_6X10, COLORS[r]))
+                .draw(screen).unwrap();
+            line += 1;
+        }
+    }
+
+    pub async fn game_loop(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
+        if self.options(screen).await == false {
+            set_current(0);
+            return;
+        }
+        self.init();
+        self.redraw(screen);
+        // ticks keep their pace however fast the inputs come in
+        let mut next_tick = Instant::now() + Duration::from_millis(TICK);
+        loop {
+            match select(INPUT_SIGNAL.wait(), Timer::at(next_tick)).await {
+                Either::First(input) => {
+                    if self.handle_input(&input) == false {
+                        // create pause menu
+                        let mut pause_menu: Menu<'_> = Menu::init("Pause menu", &[MenuOption::Resume, MenuOption::Restart, MenuOption::Exit], screen);
+                        let result: MenuOption = pause_menu.menu_loop(screen).await;
+                        match result {
+                            MenuOption::Resume | MenuOption::None => {}
+                            MenuOption::Restart => self.init(),
+                            MenuOption::Exit => {
+                                set_current(0);
+                                return;
+                            }
+                            _ => {}
+                        }
+                        self.redraw(screen);
+                        Timer::after(Duration::from_millis(100)).await;
+                        INPUT_SIGNAL.reset();
+                        next_tick = Instant::now() + Duration::from_millis(TICK);
+                    }
+                    // turns wait for the next tick
+                    continue;
+                }
+                _ => {}
+            }
+            next_tick = (next_tick + Duration::from_millis(TICK)).max(Instant::now());
+            self.update_frame(screen);
+
+            let winner = match self.round_over() {
+                None => continue,
+                Some(winner) => winner,
+            };
+            let mut temp: String<24> = String::new();
+            match winner {
+                Some(r) => {
+                    self.riders[r].score += 1;
+                    fmt::write(&mut temp, format_args!("P{} takes round {}", r + 1, self.round)).unwrap();
+                }
+                None => fmt::write(&mut temp, format_args!("Nobody makes it")).unwrap(),
+            }
+            self.draw_hud(screen);
+
+            let champion = (0..4).find(|r| self.riders[*r].score >= ROUND_GOALS[self.goal]);
+            match champion {
+                None => {
+                    Rectangle::new(Point::new(0, 88), Size::new(128, 16))
+                        .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
+                        .draw(screen)
+                        .unwrap();
+                    Text::new(&temp, Point::new(64 - temp.len() as i32 * 3, 99), MonoTextStyle::new(&FONT_6X10, Rgb565::WHITE))
+                        .draw(screen).unwrap();
+                    Timer::after(Duration::from_millis(1500)).await;
+                    self.new_round();
+                    self.redraw(screen);
+                    INPUT_SIGNAL.reset();
+                    next_tick = Instant::now() + Duration::from_millis(TICK);
+                }
+                Some(r) => {
+                    info!("game over!");
+                    let mut title: String<16> = String::new();
+                    fmt::write(&mut title, format_args!("P{} wins!", r + 1)).unwrap();
+                    let mut end_menu: Menu<'_> = Menu::init(&title, &[MenuOption::Restart, MenuOption::Options, MenuOption::Exit], screen);
+                    self.draw_results(screen);
+                    let result: MenuOption = end_menu.menu_loop(screen).await;
+                    match result {
+                        MenuOption::Restart | MenuOption::None => {}
+                        MenuOption::Options => {
+                            if self.options(screen).await == false {
+                                set_current(0);
+                                return;
+                            }
+                        }
+                        MenuOption::Exit => {
+                            set_current(0);
+                            return;
+                        }
+                        _ => {}
+                    }
+                    self.init();
+                    self.redraw(screen);
+                    Timer::after(Duration::from_millis(100)).await;
+                    INPUT_SIGNAL.reset();
+                    next_tick = Instant::now() + Duration::from_millis(TICK);
+                }
+            }
+        }
+    }
+}
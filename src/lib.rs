@@ -1,5 +1,8 @@
 #![no_std]
 
+pub mod physics;
+pub mod snake_ai;
+
 #[derive(PartialEq)]
 pub enum Input {
     Up,
@@ -30,11 +33,25 @@ pub enum MenuOption {
     SpaceInvaders,
     Sokoban,
     Breakout,
+    Tetris,
+    Pong,
+    Tron,
+    Minesweeper,
+    ConnectFour,
+    TicTacToe,
+    Asteroids,
     Resume, 
     Continue,
     Next,
     Previous,
     Restart,
+    Play,
+    Edit,
+    Test,
+    Save,
+    Replay,
+    Export,
+    Options,
     Exit,
     Debug,
 }
\ No newline at end of file
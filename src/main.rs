@@ -43,32 +43,58 @@ use menu::selector::Menu;
 mod games;
 use games::{
     snake::Snake, 
-    spaceinvaders::{SpaceInvaders, Enemy}, 
+    spaceinvaders::{SpaceInvaders, Enemy, EnemyShot}, 
     sokoban:: Sokoban,
-    breakout::{Breakout, Block}
+    breakout::{Breakout, Block, PowerUp},
+    tetris::{self, Tetris},
+    pong::Pong,
+    tron::Tron,
+    minesweeper::Minesweeper,
+    board::Board,
+    connect_four,
+    tictactoe,
+    asteroids::Asteroids
 };
 
 mod irqs;
+
+mod storage;
 use rust_pico_console::{Input, MenuOption};
+use rust_pico_console::physics::Ball;
 
 use {defmt_rtt as _, panic_probe as _};
 use defmt::*;
 
-use heapless::{Vec, Deque, spsc::Queue};
+use heapless::{Vec, Deque, String, spsc::Queue};
+
+use embassy_futures::select::{select, Either};
 
 // yellow 1 orange 2 red 29 black 38
 // blue black purple
 
-static mut LAST_SELECTED: u8 = 100;
-static mut CURRENT: u8 = 0;
+// the game that is running, 0 is the main menu, shared with the receive task that reports it to the controller
+static CURRENT: Mutex<CriticalSectionRawMutex, Cell<u8>> = Mutex::new(Cell::new(0));
 static INPUT_SIGNAL: Signal<CriticalSectionRawMutex, Input> = Signal::new();
-static mut LAST_REMOTE: Option<IpEndpoint> = None;
+// text sent to the controller on EXPORT_PORT, like sokoban solutions
+pub const EXPORT_LEN: usize = 1400;
+const EXPORT_PORT: u16 = 7882;
+static EXPORT_SIGNAL: Signal<CriticalSectionRawMutex, String<EXPORT_LEN>> = Signal::new();
+
+pub fn current() -> u8 {
+    CURRENT.lock(|current| current.get())
+}
+
+pub fn set_current(game: u8) {
+    CURRENT.lock(|current| current.set(game));
+}
+
 #[embassy_executor::main]
 async fn main(spawner: Spawner) {
     info!("main!");
     const LOCAL_PORT: u16 = 7880;
 
     let p = embassy_rp::init(Default::default());
+    storage::init(p.FLASH);
 
     info!("started");
     let mut screen_config = embassy_rp::spi::Config::default();
@@ -129,158 +155,173 @@ async fn main(spawner: Spawner) {
             .into_styled(PrimitiveStyle::with_fill(Rgb565::BLACK))
             .draw(&mut screen)
             .unwrap(); 
-        unsafe {
-            match CURRENT {
-                0 => {
-                    let mut main_menu: Menu<'_> = Menu::init("Main menu", &[MenuOption::Snake, MenuOption::SpaceInvaders, MenuOption::Sokoban, MenuOption::Breakout, MenuOption::Debug], &mut screen);
-                    let result: MenuOption = main_menu.menu_loop(&mut screen).await;
-                    match result {
-                        MenuOption::None => CURRENT = 0,
-                        MenuOption::Snake => CURRENT = 1,
-                        MenuOption::SpaceInvaders => CURRENT = 2,
-                        MenuOption::Sokoban => CURRENT = 3,
-                        MenuOption::Breakout => CURRENT = 4,
-                        MenuOption::Debug => CURRENT = 10,
-                        _ => {}
-                    }
-                },
-                1 => {
-                    let mut frame = Vec::<u32, 32>::from_slice(&[0; 31]).unwrap();
-                    let mut body_1 = Deque::<(u8, u8), 1025>::new();
-                    let mut body_2 = Deque::<(u8, u8), 1025>::new();
-                    let mut apples = Vec::<u32, 32>::from_slice(&[0; 31]).unwrap();
-                    let mut snake: Snake = Snake::new(&mut frame, &mut body_1, &mut body_2, &mut apples);
-                    snake.init(&mut screen);
-                    snake.game_loop(&mut screen).await;
-                },
-                2 => {
-                    let mut enemies = Vec::<Vec::<(Enemy, u8), 5>, 5>::from_iter(
-                        [
-                            Vec::from_iter([(Enemy::None, 0); 5].iter().cloned()),
-                            Vec::from_iter([(Enemy::None, 0); 5].iter().cloned()),
-                            Vec::from_iter([(Enemy::None, 0); 5].iter().cloned()),
-                            Vec::from_iter([(Enemy::None, 0); 5].iter().cloned()),
-                            Vec::from_iter([(Enemy::None, 0); 5].iter().cloned()),
-                        ]
-                        .iter()
-                        .cloned()
-                    );
-                    let mut last_row = Vec::<(Enemy, u8, u8, bool), 5>::from_iter([(Enemy::None, 0, 0, false); 5].iter().cloned());
-                    let mut enemy_projectiles = Vec::<(u8, u8, u8, bool), 5>::new();
-                    let mut player1_projectiles = Vec::<(u8, u8, bool), 20>::new();
-                    let mut player2_projectiles = Vec::<(u8, u8, bool), 20>::new();
-                    let mut spaceinvaders: SpaceInvaders = SpaceInvaders::new(&mut enemies, &mut last_row, &mut enemy_projectiles, &mut player1_projectiles, &mut player2_projectiles);
-                    spaceinvaders.init();
-                    spaceinvaders.game_loop(&mut screen).await;
-                },
-                3 => {
-                    let mut frame = Vec::<Vec::<u8, 15>, 15>::from_iter(
-                        [
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                            Vec::from_iter([0; 15].iter().cloned()),
-                        ]
-                        .iter()
-                        .cloned()
-                    );
-                    let mut destinations = Vec::<(u8, u8), 20>::new();
-                    let mut sokoban: Sokoban = Sokoban::new(&mut frame, &mut destinations); 
-                    sokoban.init();
-                    sokoban.game_loop(&mut screen).await;
-                }
-                4 => {
-                    let mut bricks = Vec::<Vec::<Block, 16>, 36>::from_iter(
-                        [
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                            Vec::from_iter([Block::None; 16].iter().cloned()),
-                        ].iter()
-                        .cloned()
-                    );
-                    let mut walls: Vec<bool, 32> = Vec::from_iter([false; 32].iter().cloned());
-                    let mut balls: Vec<(f32, i16, f32 ,i16, bool), 50> = Vec::<(f32, i16, f32 ,i16, bool), 50>::new();
-                    let mut powerups: Vec<(u8, u8, bool, bool), 20> = Vec::<(u8, u8, bool, bool), 20>::new();
-                    let mut breakout: Breakout = Breakout::new(&mut bricks, &mut walls, &mut balls, &mut powerups);
-                    breakout.init();
-                    breakout.game_loop(&mut screen).await;
+        match current() {
+            0 => {
+                let mut main_menu: Menu<'_> = Menu::init("Main menu", &[MenuOption::Snake, MenuOption::SpaceInvaders, MenuOption::Sokoban, MenuOption::Breakout, MenuOption::Tetris, MenuOption::Pong, MenuOption::Tron, MenuOption::Minesweeper, MenuOption::ConnectFour, MenuOption::TicTacToe, MenuOption::Asteroids, MenuOption::Debug], &mut screen);
+                let result: MenuOption = main_menu.menu_loop(&mut screen).await;
+                match result {
+                    MenuOption::None => set_current(0),
+                    MenuOption::Snake => set_current(1),
+                    MenuOption::SpaceInvaders => set_current(2),
+                    MenuOption::Sokoban => set_current(3),
+                    MenuOption::Breakout => set_current(4),
+                    MenuOption::Tetris => set_current(5),
+                    MenuOption::Pong => set_current(6),
+                    MenuOption::Tron => set_current(7),
+                    MenuOption::Minesweeper => set_current(8),
+                    MenuOption::ConnectFour => set_current(9),
+                    MenuOption::TicTacToe => set_current(11),
+                    MenuOption::Asteroids => set_current(12),
+                    MenuOption::Debug => set_current(10),
+                    _ => {}
                 }
-                // debug, the coordinates are inverted
-                10 => {
-                    Rectangle::new(Point::new( 0 , 0), Size::new(10, 10))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_TURQUOISE))
-                        .draw(&mut screen)
-                        .unwrap();  
-
-                        Rectangle::new(Point::new( 20 , 0), Size::new(10, 10))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::YELLOW))
-                        .draw(&mut screen)
-                        .unwrap();  
-
-
-                        Rectangle::new(Point::new( 0 , 20), Size::new(10, 10))
-                        .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_FLORAL_WHITE))
-                        .draw(&mut screen)
-                        .unwrap();  
-                    loop {
-                        match INPUT_SIGNAL.wait().await {
-                            input => {
-                                match input {
-                                    Input::Back => {
-                                        CURRENT = 0;
-                                        break;
-                                    }
-                                    _ => {}
-                                }    
-                            }
+            },
+            1 => {
+                let mut frame = Vec::<u32, 32>::from_slice(&[0; 31]).unwrap();
+                let mut body_1 = Deque::<(u8, u8), 1025>::new();
+                let mut body_2 = Deque::<(u8, u8), 1025>::new();
+                let mut apples = Vec::<u32, 32>::from_slice(&[0; 31]).unwrap();
+                let mut snake: Snake = Snake::new(&mut frame, &mut body_1, &mut body_2, &mut apples);
+                snake.game_loop(&mut screen).await;
+            },
+            2 => {
+                let mut enemies = Vec::<Vec::<(Enemy, u8), 7>, 5>::from_iter(
+                    [
+                        Vec::from_iter([(Enemy::None, 0); 7].iter().cloned()),
+                        Vec::from_iter([(Enemy::None, 0); 7].iter().cloned()),
+                        Vec::from_iter([(Enemy::None, 0); 7].iter().cloned()),
+                        Vec::from_iter([(Enemy::None, 0); 7].iter().cloned()),
+                        Vec::from_iter([(Enemy::None, 0); 7].iter().cloned()),
+                    ]
+                    .iter()
+                    .cloned()
+                );
+                let mut last_row = Vec::<(Enemy, u8, u8, bool), 7>::from_iter([(Enemy::None, 0, 0, false); 7].iter().cloned());
+                let mut enemy_projectiles = Vec::<EnemyShot, 16>::new();
+                let mut player1_projectiles = Vec::<(u8, u8, bool), 20>::new();
+                let mut player2_projectiles = Vec::<(u8, u8, bool), 20>::new();
+                let mut spaceinvaders: SpaceInvaders = SpaceInvaders::new(&mut enemies, &mut last_row, &mut enemy_projectiles, &mut player1_projectiles, &mut player2_projectiles);
+                spaceinvaders.init();
+                spaceinvaders.game_loop(&mut screen).await;
+            },
+            3 => {
+                // filled by Sokoban::init, levels can be up to 32x32
+                let mut frame = Vec::<Vec::<u8, 32>, 32>::new();
+                let mut destinations = Vec::<(u8, u8), 32>::new();
+                let mut sokoban: Sokoban = Sokoban::new(&mut frame, &mut destinations); 
+                sokoban.init();
+                sokoban.game_loop(&mut screen).await;
+            }
+            4 => {
+                let mut bricks = Vec::<Vec::<Block, 16>, 36>::from_iter(
+                    [
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                        Vec::from_iter([Block::None; 16].iter().cloned()),
+                    ].iter()
+                    .cloned()
+                );
+                let mut walls: Vec<bool, 32> = Vec::from_iter([false; 32].iter().cloned());
+                let mut balls: Vec<Ball, 50> = Vec::<Ball, 50>::new();
+                let mut powerups: Vec<PowerUp, 20> = Vec::<PowerUp, 20>::new();
+                let mut breakout: Breakout = Breakout::new(&mut bricks, &mut walls, &mut balls, &mut powerups);
+                breakout.init();
+                breakout.game_loop(&mut screen).await;
+            }
+            5 => {
+                let mut wells = [[[0u8; tetris::WIDTH]; tetris::HEIGHT]; 2];
+                let mut tetris: Tetris = Tetris::new(&mut wells);
+                tetris.game_loop(&mut screen).await;
+            }
+            6 => {
+                let mut pong: Pong = Pong::new();
+                pong.game_loop(&mut screen).await;
+            }
+            7 => {
+                let mut tron: Tron = Tron::new();
+                tron.game_loop(&mut screen).await;
+            }
+            8 => {
+                let mut minesweeper: Minesweeper = Minesweeper::new();
+                minesweeper.game_loop(&mut screen).await;
+            }
+            9 => {
+                let mut board: Board = Board::new(&connect_four::RULES);
+                board.game_loop(&mut screen).await;
+            }
+            // debug, the coordinates are inverted
+            10 => {
+                Rectangle::new(Point::new( 0 , 0), Size::new(10, 10))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_TURQUOISE))
+                    .draw(&mut screen)
+                    .unwrap();  
+
+                    Rectangle::new(Point::new( 20 , 0), Size::new(10, 10))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::YELLOW))
+                    .draw(&mut screen)
+                    .unwrap();  
+
+
+                    Rectangle::new(Point::new( 0 , 20), Size::new(10, 10))
+                    .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_FLORAL_WHITE))
+                    .draw(&mut screen)
+                    .unwrap();  
+                loop {
+                    match INPUT_SIGNAL.wait().await {
+                        input => {
+                            match input {
+                                Input::Back => {
+                                    set_current(0);
+                                    break;
+                                }
+                                _ => {}
+                            }    
                         }
                     }
                 }
-                _ => continue,
             }
+            11 => {
+                let mut board: Board = Board::new(&tictactoe::RULES);
+                board.game_loop(&mut screen).await;
+            }
+            12 => {
+                let mut asteroids: Asteroids = Asteroids::new();
+                asteroids.game_loop(&mut screen).await;
+            }
+            _ => continue,
         }
         info!("returned from loop");
     }
@@ -289,49 +330,64 @@ async fn main(spawner: Spawner) {
 #[embassy_executor::task]
 async fn receive(socket: UdpSocket<'static>) {
     let mut buf: [u8; 1500] = [0; 1500];
+    // the game last reported to the controller and where the controller talks from
+    let mut last_selected: u8 = 100;
+    let mut last_remote: Option<IpEndpoint> = None;
     loop {
-        match socket.recv_from(&mut buf).await {
-            Ok((len, meta)) => match from_utf8(&buf[..len]) {
-                Ok(s) => {
-                    let input: Input = match s {
-                        "w" => Input::Up,
-                        "a" => Input::Left,
-                        "s" => Input::Down,
-                        "d" => Input::Right,
-                        "f" => Input::Right_Shoot,
-                        "g" => Input::Left_Shoot,
-                        "u" => Input::Up2,
-                        "h" => Input::Left2,
-                        "j" => Input::Down2,
-                        "k" => Input::Right2,
-                        "o" => Input::Right2_Shoot,
-                        "p" => Input::Left2_Shoot,
-                        "1" => Input::LeftLeft, 
-                        "2" => Input::RightLeft,
-                        "3" => Input::LeftRight,
-                        "4" => Input::RightRight,
-                        "e" => Input::Select,
-                        "q" => Input::Back,
-                        _ => Input::Ignore,
-                    };
-                    if input != Input::Ignore {
-                        INPUT_SIGNAL.signal(input);
-                    }
-                    unsafe {
-                        if LAST_SELECTED != CURRENT {
-                            LAST_REMOTE = Some(meta.endpoint);
-                            if let Some(mut remote) = LAST_REMOTE {
-                                // info!("sending {}", CURRENT);
+        match select(socket.recv_from(&mut buf), EXPORT_SIGNAL.wait()).await {
+            Either::First(received) => match received {
+                Ok((len, meta)) => match from_utf8(&buf[..len]) {
+                    Ok(s) => {
+                        let input: Input = match s {
+                            "w" => Input::Up,
+                            "a" => Input::Left,
+                            "s" => Input::Down,
+                            "d" => Input::Right,
+                            "f" => Input::Right_Shoot,
+                            "g" => Input::Left_Shoot,
+                            "u" => Input::Up2,
+                            "h" => Input::Left2,
+                            "j" => Input::Down2,
+                            "k" => Input::Right2,
+                            "o" => Input::Right2_Shoot,
+                            "p" => Input::Left2_Shoot,
+                            "1" => Input::LeftLeft, 
+                            "2" => Input::RightLeft,
+                            "3" => Input::LeftRight,
+                            "4" => Input::RightRight,
+                            "e" => Input::Select,
+                            "q" => Input::Back,
+                            _ => Input::Ignore,
+                        };
+                        if input != Input::Ignore {
+                            INPUT_SIGNAL.signal(input);
+                        }
+                        let current = current();
+                        if last_selected != current {
+                            last_remote = Some(meta.endpoint);
+                            if let Some(mut remote) = last_remote {
+                                // info!("sending {}", current);
                                 remote.port = 7881;
-                                socket.send_to(&CURRENT.to_be_bytes(), remote).await.unwrap();
+                                socket.send_to(&current.to_be_bytes(), remote).await.unwrap();
                             }
-                            LAST_SELECTED = CURRENT;
-                        }   
+                            last_selected = current;
+                        }
+                    }
+                    Err(_e) => warn!("received {} bytes from", len),
+                },
+                Err(e) => error!("error receiving packet: {:?}", e),
+            },
+            Either::Second(text) => {
+                match last_remote {
+                    Some(mut remote) => {
+                        remote.port = EXPORT_PORT;
+                        if socket.send_to(text.as_bytes(), remote).await.is_err() {
+                            warn!("failed to export {} bytes", text.len());
+                        }
                     }
+                    None => warn!("no controller to export to"),
                 }
-                Err(_e) => warn!("received {} bytes from", len),
             },
-            Err(e) => error!("error receiving packet: {:?}", e),
         }
     }
 }
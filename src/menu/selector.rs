@@ -21,17 +21,22 @@ use defmt::*;
 
 use rust_pico_console::{Input, MenuOption};
 use crate::INPUT_SIGNAL;
-use crate::CURRENT;
+
+// options shown at once, longer menus scroll
+const VISIBLE: usize = 7;
 
 pub struct Menu<'a> {
     title: &'a str,
     options: &'a [MenuOption],
     selected: usize,
+    // first option on screen
+    top: usize,
 }
 
 impl <'a> Menu<'a> {
     pub fn init(title: &'a str, options: &'a [MenuOption], screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> Menu<'a> {
-        Rectangle::new(Point::new(16, 10), Size::new(96, 27 + options.len() as u32 * 16))
+        let rows = options.len().min(VISIBLE);
+        Rectangle::new(Point::new(16, 10), Size::new(96, 27 + rows as u32 * 16))
             .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_AQUA))
             .draw(screen)
             .unwrap();
@@ -47,7 +52,7 @@ impl <'a> Menu<'a> {
             .draw(screen)
             .unwrap();
 
-        for i in 0..options.len() {
+        for i in 0..rows {
             Rectangle::new(Point::new(19, 36 + i as i32 * 16), Size::new(90, 14))
                 .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
                 .draw(screen)
@@ -57,34 +62,62 @@ impl <'a> Menu<'a> {
             title,
             options,
             selected: 0,
+            top: 0,
         }
     }
 
     pub fn draw(&self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) {
         // info!("init screen with selected = {}", self.selected);
-        for (i , option) in self.options.iter().enumerate() {
+        for (i , option) in self.options.iter().skip(self.top).take(VISIBLE).enumerate() {
+            let index = self.top + i;
             Rectangle::new(Point::new(19, 36 + i as i32 * 16), Size::new(90, 14))
-                .into_styled(PrimitiveStyle::with_fill(if self.selected == i { Rgb565::WHITE } else { Rgb565::BLUE }))
+                .into_styled(PrimitiveStyle::with_fill(if self.selected == index { Rgb565::WHITE } else { Rgb565::BLUE }))
                 .draw(screen)
                 .unwrap();
-            let color = if self.selected == i { Rgb565::BLUE } else { Rgb565::CSS_ORANGE };
+            let color = if self.selected == index { Rgb565::BLUE } else { Rgb565::CSS_ORANGE };
             Text::new(match option {
                 MenuOption::Snake => "Snake",
                 MenuOption::SpaceInvaders => "Space Invaders",
                 MenuOption::Sokoban => "Sokoban",
                 MenuOption::Breakout => "Breakout",
+                MenuOption::Tetris => "Tetris",
+                MenuOption::Pong => "Pong",
+                MenuOption::Tron => "Tron",
+                MenuOption::Minesweeper => "Minesweeper",
+                MenuOption::ConnectFour => "Connect Four",
+                MenuOption::TicTacToe => "Tic-Tac-Toe",
+                MenuOption::Asteroids => "Asteroids",
                 MenuOption::Debug => "Debug",
                 MenuOption::Resume => "Resume",
                 MenuOption::Continue => "Continue",
                 MenuOption::Next => "Next",
                 MenuOption::Previous => "Previous",
                 MenuOption::Restart => "Restart",
+                MenuOption::Play => "Play",
+                MenuOption::Edit => "Edit",
+                MenuOption::Test => "Test",
+                MenuOption::Save => "Save",
+                MenuOption::Replay => "Replay",
+                MenuOption::Export => "Export",
+                MenuOption::Options => "Options",
                 MenuOption::Exit => "Exit",
                 _ => ""
             }, Point::new(23, 45 + i as i32 * 16),MonoTextStyle::new(&FONT_6X10, color))
                 .draw(screen)
                 .unwrap();
         }
+        // scroll bar in the right border when not every option fits
+        if self.options.len() > VISIBLE {
+            let track = VISIBLE as u32 * 16 - 2;
+            Rectangle::new(Point::new(110, 36), Size::new(2, track))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_AQUA))
+                .draw(screen)
+                .unwrap();
+            Rectangle::new(Point::new(110, 36 + (track as usize * self.top / self.options.len()) as i32), Size::new(2, track * VISIBLE as u32 / self.options.len() as u32))
+                .into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE))
+                .draw(screen)
+                .unwrap();
+        }
     }
     
     pub fn handle_input(&mut self, input: &Input) {
@@ -105,6 +138,12 @@ impl <'a> Menu<'a> {
             }
             _ => {}
         }
+        // keep the selected option on screen
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected >= self.top + VISIBLE {
+            self.top = self.selected + 1 - VISIBLE;
+        }
     }
 
     pub async fn menu_loop(&mut self, screen: &mut mipidsi::Display<SpiInterface<'_, &mut SpiDevice<'_, NoopRawMutex, Spi<'_, embassy_rp::peripherals::SPI1, embassy_rp::spi::Blocking>, Output<'_>>, Output<'_>>, ST7735s, Output<'_>>) -> MenuOption {
@@ -0,0 +1,353 @@
+// ball physics shared by the games, everything is fixed point so it runs the same on the board and on the host
+// numbers have 8 fractional bits, ONE is a single pixel
+
+pub const ONE: i32 = 256;
+
+// ball speed in pixels per frame, the ball gets faster with every paddle hit
+pub const MIN_SPEED: i32 = ONE;
+pub const MAX_SPEED: i32 = ONE * 2;
+pub const SPEEDUP: i32 = ONE / 32;
+// the widest angle from vertical a ball leaves a paddle at, about 60 degrees in radians
+pub const MAX_ANGLE: i32 = 268;
+// the vertical speed never drops under this part of the speed, so balls never travel nearly horizontal
+pub const MIN_VERTICAL: i32 = ONE / 2;
+// how much a paddle moving one pixel per frame turns the ball, as a part of MAX_ANGLE
+pub const SPIN: i32 = ONE / 3;
+
+#[derive(Clone, Copy)]
+pub struct Ball {
+    pub x: i32,
+    pub y: i32,
+    pub vx: i32,
+    pub vy: i32,
+    pub active: bool,
+    // the player who touched the ball last
+    pub owner: usize,
+}
+
+impl Ball {
+    pub fn new(x: i32, y: i32, vx: i32, vy: i32, owner: usize) -> Ball {
+        Ball { x, y, vx, vy, active: true, owner }
+    }
+
+    // moves the ball along dx, dy, time is the part of that move to travel, ONE being all of it
+    pub fn advance(&mut self, dx: i32, dy: i32, time: i32) {
+        self.x += dx * time / ONE;
+        self.y += dy * time / ONE;
+    }
+
+    // sends the ball away from the surface it hit
+    pub fn bounce(&mut self, normal: (i32, i32)) {
+        if normal.0 != 0 {
+            self.vx = normal.0 * self.vx.abs();
+        }
+        if normal.1 != 0 {
+            self.vy = normal.1 * self.vy.abs();
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+// a grid of equal cells, like the bricks in breakout, pitch is the distance between two cells
+pub struct Grid {
+    pub x: i32,
+    pub y: i32,
+    pub pitch_x: i32,
+    pub pitch_y: i32,
+    pub width: i32,
+    pub height: i32,
+    pub cols: i32,
+    pub rows: i32,
+}
+
+#[derive(Clone, Copy)]
+pub struct Hit {
+    // how far along the move the ball touched, ONE being the whole move
+    pub time: i32,
+    // the side that was hit, -1 or 1 on the axis that bounces, 0 on the other one
+    pub normal: (i32, i32),
+}
+
+// entry and exit time of a moving span against a still one on a single axis
+fn slab(start: i32, size: i32, delta: i32, target: i32, target_size: i32) -> Option<(i32, i32)> {
+    if delta == 0 {
+        // not moving on this axis, the spans either always overlap or never do
+        if start < target + target_size && start + size > target {
+            return Some((i32::MIN, i32::MAX));
+        }
+        return None;
+    }
+    let (entry, exit) = if delta > 0 {
+        (target - (start + size), target + target_size - start)
+    } else {
+        (target + target_size - start, target - (start + size))
+    };
+    Some(((entry as i64 * ONE as i64 / delta as i64) as i32, (exit as i64 * ONE as i64 / delta as i64) as i32))
+}
+
+// swept box test, finds when a square ball moving by dx, dy first touches the target
+// a ball that already overlaps the target does not hit it, so it can always leave after a bounce
+pub fn sweep(ball: &Ball, size: i32, dx: i32, dy: i32, target: &Rect) -> Option<Hit> {
+    let (entry_x, exit_x) = slab(ball.x, size, dx, target.x, target.width)?;
+    let (entry_y, exit_y) = slab(ball.y, size, dy, target.y, target.height)?;
+    let entry = entry_x.max(entry_y);
+    let exit = exit_x.min(exit_y);
+    if entry < 0 || entry > ONE || entry >= exit {
+        return None;
+    }
+    let normal_x = if entry_x >= entry_y { -dx.signum() } else { 0 };
+    let normal_y = if entry_y >= entry_x { -dy.signum() } else { 0 };
+    Some(Hit { time: entry, normal: (normal_x, normal_y) })
+}
+
+// first solid cell of the grid the ball touches while moving by dx, dy
+// only the cells under the box covering the whole move are tested, so the cost does not grow with the grid
+pub fn sweep_grid(ball: &Ball, size: i32, dx: i32, dy: i32, grid: &Grid, solid: impl Fn(usize, usize) -> bool) -> Option<(usize, usize, Hit)> {
+    let left = ball.x.min(ball.x + dx) - grid.x;
+    let right = ball.x.max(ball.x + dx) + size - grid.x;
+    let top = ball.y.min(ball.y + dy) - grid.y;
+    let bottom = ball.y.max(ball.y + dy) + size - grid.y;
+    if right < 0 || bottom < 0 {
+        return None;
+    }
+    let first_col = left.max(0) / grid.pitch_x;
+    let last_col = (right / grid.pitch_x).min(grid.cols - 1);
+    let first_row = top.max(0) / grid.pitch_y;
+    let last_row = (bottom / grid.pitch_y).min(grid.rows - 1);
+    let mut first: Option<(usize, usize, Hit)> = None;
+    for row in first_row..=last_row {
+        for col in first_col..=last_col {
+            if !solid(row as usize, col as usize) {
+                continue;
+            }
+            let cell = Rect {
+                x: grid.x + col * grid.pitch_x,
+                y: grid.y + row * grid.pitch_y,
+                width: grid.width,
+                height: grid.height,
+            };
+            if let Some(hit) = sweep(ball, size, dx, dy, &cell) {
+                if first.map_or(true, |(_, _, best)| hit.time < best.time) {
+                    first = Some((row as usize, col as usize, hit));
+                }
+            }
+        }
+    }
+    first
+}
+
+// velocity of a ball bouncing off a paddle, the ball always leaves upwards
+// offset is where the ball hit, from -half_width on the left edge to half_width on the right edge
+// paddle_velocity is how far the paddle moved during the last frame
+pub fn paddle_bounce(offset: i32, half_width: i32, paddle_velocity: i32, speed: i32) -> (i32, i32) {
+    let speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+    let half_width = half_width.max(1);
+    // -ONE on the left edge, ONE on the right edge
+    let aim = offset.clamp(-half_width, half_width) * ONE / half_width;
+    let aim = (aim + paddle_velocity * SPIN / ONE).clamp(-ONE, ONE);
+    let (sin, cos) = sin_cos(aim * MAX_ANGLE / ONE);
+    let vx = speed * sin / ONE;
+    let vy = (speed * cos / ONE).max(speed * MIN_VERTICAL / ONE);
+    // raising vy must not make the ball faster, the rest of the speed goes sideways
+    let widest = isqrt((speed as i64 * speed as i64 - vy as i64 * vy as i64) as u64) as i32;
+    (vx.clamp(-widest, widest), -vy)
+}
+
+// length of a velocity
+pub fn speed(vx: i32, vy: i32) -> i32 {
+    isqrt((vx as i64 * vx as i64 + vy as i64 * vy as i64) as u64) as i32
+}
+
+// sine and cosine of a small angle in radians, good enough up to about 90 degrees
+pub fn sin_cos(angle: i32) -> (i32, i32) {
+    let x = angle as i64;
+    let one = ONE as i64;
+    let x2 = x * x / one;
+    let sin = x - x * x2 / (6 * one) + x * x2 / one * x2 / (120 * one);
+    let cos = one - x2 / 2 + x2 * x2 / (24 * one) - x2 * x2 / one * x2 / (720 * one);
+    (sin as i32, cos as i32)
+}
+
+pub fn isqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+    // newton's method, starting above the root
+    let mut root = value;
+    let mut next = (root + 1) / 2;
+    while next < root {
+        root = next;
+        next = (root + value / root) / 2;
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a single row of bricks laid out like the breakout wall
+    const ROW: Grid = Grid {
+        x: 0,
+        y: 40 * ONE,
+        pitch_x: 8 * ONE,
+        pitch_y: 4 * ONE,
+        width: 7 * ONE,
+        height: 3 * ONE,
+        cols: 16,
+        rows: 1,
+    };
+    const BALL: i32 = 2 * ONE;
+
+    // moves the ball frame by frame towards the row, it must hit a brick before it ever overlaps one
+    fn fly(x: i32, y: i32, dx: i32, dy: i32) -> Option<(usize, usize, Hit)> {
+        let mut ball = Ball::new(x, y, dx, dy, 0);
+        for _ in 0..100 {
+            let overlapping = ball.y < ROW.y + ROW.height && ball.y + BALL > ROW.y;
+            assert!(!overlapping, "ball went into the row at {} {}", ball.x, ball.y);
+            if let Some(hit) = sweep_grid(&ball, BALL, dx, dy, &ROW, |_, _| true) {
+                return Some(hit);
+            }
+            ball.advance(dx, dy, ONE);
+        }
+        None
+    }
+
+    #[test]
+    fn no_tunnelling_straight_up_at_max_speed() {
+        // every sub-pixel start, so the last frame before the row ends anywhere against it
+        for offset in 0..ONE {
+            let hit = fly(20 * ONE, 60 * ONE + offset, 0, -MAX_SPEED);
+            let (row, col, hit) = hit.expect("ball tunnelled through the row");
+            assert_eq!((row, col), (0, 2));
+            assert_eq!(hit.normal, (0, 1));
+        }
+    }
+
+    #[test]
+    fn no_tunnelling_straight_down_at_max_speed() {
+        for offset in 0..ONE {
+            let (_, _, hit) = fly(20 * ONE, 20 * ONE + offset, 0, MAX_SPEED).expect("ball tunnelled through the row");
+            assert_eq!(hit.normal, (0, -1));
+        }
+    }
+
+    #[test]
+    fn no_tunnelling_diagonally_at_max_speed() {
+        // 45 degrees, both ways across the row
+        let step = MAX_SPEED * 181 / 256;
+        for offset in (0..ONE).step_by(7) {
+            for (dx, dy, y) in [(step, -step, 60 * ONE), (-step, -step, 60 * ONE), (step, step, 20 * ONE), (-step, step, 20 * ONE)] {
+                let (_, _, hit) = fly(64 * ONE + offset, y + offset, dx, dy).expect("ball tunnelled through the row");
+                assert!(hit.time >= 0 && hit.time <= ONE);
+                assert!(hit.normal != (0, 0));
+            }
+        }
+    }
+
+    #[test]
+    fn no_tunnelling_through_the_gaps_between_bricks() {
+        // a ball aimed at a 1 pixel gap is wider than it, so it still catches a brick
+        let (_, col, _) = fly(7 * ONE, 60 * ONE, 0, -MAX_SPEED).expect("ball slipped between two bricks");
+        assert!(col == 0 || col == 1);
+    }
+
+    // a wall 4 pixels wide, 10 pixels to the right of the ball
+    const WALL: Rect = Rect { x: 100 * ONE, y: 0, width: 4 * ONE, height: 160 * ONE };
+
+    #[test]
+    fn sweep_stops_short_of_the_wall() {
+        let ball = Ball::new(WALL.x - BALL - ONE, 50 * ONE, MAX_SPEED, 0, 0);
+        assert!(sweep(&ball, BALL, ONE / 2, 0, &WALL).is_none());
+        assert!(sweep(&ball, BALL, ONE - 1, 0, &WALL).is_none());
+    }
+
+    #[test]
+    fn sweep_reaches_the_wall_at_the_end_of_the_move() {
+        let ball = Ball::new(WALL.x - BALL - ONE, 50 * ONE, MAX_SPEED, 0, 0);
+        let hit = sweep(&ball, BALL, ONE, 0, &WALL).expect("the move ends against the wall");
+        assert_eq!(hit.time, ONE);
+        assert_eq!(hit.normal, (-1, 0));
+    }
+
+    #[test]
+    fn sweep_past_the_wall_hits_it_part_way() {
+        // 1 pixel of a 2 pixel move is left before the wall
+        let ball = Ball::new(WALL.x - BALL - ONE, 50 * ONE, MAX_SPEED, 0, 0);
+        let hit = sweep(&ball, BALL, MAX_SPEED, 0, &WALL).expect("the move goes past the wall");
+        assert_eq!(hit.time, ONE / 2);
+        assert_eq!(hit.normal, (-1, 0));
+
+        // the same from the other side
+        let ball = Ball::new(WALL.x + WALL.width + ONE, 50 * ONE, -MAX_SPEED, 0, 0);
+        let hit = sweep(&ball, BALL, -MAX_SPEED, 0, &WALL).expect("the move goes past the wall");
+        assert_eq!(hit.time, ONE / 2);
+        assert_eq!(hit.normal, (1, 0));
+    }
+
+    const HALF_WIDTH: i32 = 12 * ONE;
+
+    #[test]
+    fn paddle_bounce_from_the_centre_goes_straight_up() {
+        let (vx, vy) = paddle_bounce(0, HALF_WIDTH, 0, MIN_SPEED);
+        assert_eq!(vx, 0);
+        assert_eq!(vy, -MIN_SPEED);
+    }
+
+    #[test]
+    fn paddle_bounce_from_the_edge_is_capped_at_max_angle() {
+        let (sin, _) = sin_cos(MAX_ANGLE);
+        for speed in [MIN_SPEED, MAX_SPEED] {
+            let (edge, _) = paddle_bounce(HALF_WIDTH, HALF_WIDTH, 0, speed);
+            assert!(edge > 0 && edge <= speed * sin / ONE);
+            // past the edge and the edge with the paddle moving the same way leave at the same angle
+            for (offset, velocity) in [(HALF_WIDTH, 0), (3 * HALF_WIDTH, 0), (HALF_WIDTH, 4 * ONE)] {
+                let (vx, _) = paddle_bounce(offset, HALF_WIDTH, velocity, speed);
+                assert_eq!(vx, edge);
+                let (vx, _) = paddle_bounce(-offset, HALF_WIDTH, -velocity, speed);
+                assert_eq!(vx, -edge);
+            }
+        }
+    }
+
+    #[test]
+    fn paddle_bounce_keeps_the_ball_going_up() {
+        // every offset across the paddle, with the paddle still and moving at full spin both ways
+        for speed in [MIN_SPEED, MAX_SPEED, MAX_SPEED * 2] {
+            for offset in (-HALF_WIDTH - ONE..=HALF_WIDTH + ONE).step_by(ONE as usize / 8) {
+                for velocity in [-ONE * ONE / SPIN, -ONE, 0, ONE, ONE * ONE / SPIN] {
+                    let (_, vy) = paddle_bounce(offset, HALF_WIDTH, velocity, speed);
+                    let speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+                    assert!(vy < 0);
+                    assert!(-vy >= speed * MIN_VERTICAL / ONE);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn paddle_bounce_stays_within_max_speed() {
+        for asked in [MIN_SPEED / 2, MIN_SPEED, MAX_SPEED, MAX_SPEED * 3] {
+            for offset in (-HALF_WIDTH..=HALF_WIDTH).step_by(ONE as usize / 8) {
+                for velocity in [-ONE * ONE / SPIN, 0, ONE * ONE / SPIN] {
+                    let (vx, vy) = paddle_bounce(offset, HALF_WIDTH, velocity, asked);
+                    assert!(speed(vx, vy) <= MAX_SPEED, "{} {} at offset {}", vx, vy, offset);
+                    assert!(speed(vx, vy) >= MIN_SPEED - 2);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sweep_ignores_a_ball_already_inside() {
+        let ball = Ball::new(WALL.x + ONE, 50 * ONE, MAX_SPEED, 0, 0);
+        assert!(sweep(&ball, BALL, MAX_SPEED, 0, &WALL).is_none());
+    }
+}
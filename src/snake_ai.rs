@@ -0,0 +1,366 @@
+// the cpu snake, it looks at the same bitboards as the game: one u32 per row, one bit per column
+// everything here is plain data and a seeded rng, so the same board and seed always give the same move
+
+use heapless::Deque;
+
+// greedy: heads straight for the apple, sometimes wanders off
+pub const EASY: u8 = 1;
+// normal: shortest path to the closest apple
+pub const NORMAL: u8 = 2;
+// hard: shortest path, but only when the snake can still reach its tail afterwards
+pub const HARD: u8 = 3;
+
+// xorshift, small and fast, never seed it with 0
+pub struct Rng(u32);
+
+impl Rng {
+    pub fn new(seed: u32) -> Rng {
+        Rng(if seed == 0 { 0x9e37_79b9 } else { seed })
+    }
+
+    pub fn next(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    pub fn below(&mut self, count: u32) -> u32 {
+        self.next() % count
+    }
+}
+
+pub struct Grid<'a> {
+    pub frame: &'a [u32],
+    pub walls: &'a [u32],
+    pub apples: &'a [u32],
+    pub apple: (u8, u8),
+    pub size: u8,
+    pub borders: bool,
+}
+
+// the cell one step away, wrapping around the edges, None when a border wall is in the way
+pub fn step(cell: (u8, u8), facing: u8, size: u8, borders: bool) -> Option<(u8, u8)> {
+    let (dx, dy) = match facing {
+        0 => (0, -1),
+        1 => (0, 1),
+        2 => (-1, 0),
+        _ => (1, 0),
+    };
+    let size = size as i16;
+    let (x, y) = (cell.0 as i16 + dx, cell.1 as i16 + dy);
+    if x >= 0 && y >= 0 && x < size && y < size {
+        Some((x as u8, y as u8))
+    } else if borders {
+        None
+    } else {
+        Some((x.rem_euclid(size) as u8, y.rem_euclid(size) as u8))
+    }
+}
+
+fn bit(rows: &[u32], cell: (u8, u8)) -> bool {
+    (rows[cell.1 as usize] & (1 << cell.0)) != 0
+}
+
+impl Grid<'_> {
+    fn free(&self, cell: (u8, u8)) -> bool {
+        !(bit(self.frame, cell) || bit(self.walls, cell))
+    }
+
+    fn food(&self, cell: (u8, u8)) -> bool {
+        cell == self.apple || bit(self.apples, cell)
+    }
+
+    fn distance(&self, a: (u8, u8), b: (u8, u8)) -> u16 {
+        let axis = |a: u8, b: u8| {
+            let d = (a as i16 - b as i16).unsigned_abs();
+            if self.borders { d } else { d.min(self.size as u16 - d) }
+        };
+        axis(a.0, b.0) + axis(a.1, b.1)
+    }
+
+    // breadth first search from `from`, returns the first move towards the closest cell that passes `goal`
+    fn path(&self, from: (u8, u8), goal: impl Fn((u8, u8)) -> bool) -> Option<u8> {
+        let mut seen = [0u32; 32];
+        let mut queue: Deque<((u8, u8), u8), 1024> = Deque::new();
+        seen[from.1 as usize] |= 1 << from.0;
+        for facing in 0..4 {
+            match step(from, facing, self.size, self.borders) {
+                Some(cell) if self.free(cell) && !bit(&seen, cell) => {
+                    seen[cell.1 as usize] |= 1 << cell.0;
+                    match queue.push_back((cell, facing)) { Ok(_) => {} Err(_) => {} }
+                }
+                _ => {}
+            }
+        }
+        while let Some((cell, first)) = queue.pop_front() {
+            if goal(cell) {
+                return Some(first);
+            }
+            for facing in 0..4 {
+                match step(cell, facing, self.size, self.borders) {
+                    Some(next) if self.free(next) && !bit(&seen, next) => {
+                        seen[next.1 as usize] |= 1 << next.0;
+                        match queue.push_back((next, first)) { Ok(_) => {} Err(_) => {} }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        None
+    }
+
+    // how many cells can still be reached from `from`, stops counting at `limit`
+    fn room(&self, from: (u8, u8), limit: u16) -> u16 {
+        let mut seen = [0u32; 32];
+        let mut queue: Deque<(u8, u8), 1024> = Deque::new();
+        let mut count = 0;
+        seen[from.1 as usize] |= 1 << from.0;
+        match queue.push_back(from) { Ok(_) => {} Err(_) => {} }
+        while let Some(cell) = queue.pop_front() {
+            count += 1;
+            if count >= limit {
+                break;
+            }
+            for facing in 0..4 {
+                match step(cell, facing, self.size, self.borders) {
+                    Some(next) if self.free(next) && !bit(&seen, next) => {
+                        seen[next.1 as usize] |= 1 << next.0;
+                        match queue.push_back(next) { Ok(_) => {} Err(_) => {} }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        count
+    }
+}
+
+// the turns that don't crash right away and the cells they lead to, snakes can't turn back so `facing ^ 1` is never one of them
+fn moves(grid: &Grid, head: (u8, u8), facing: u8) -> ([(u8, (u8, u8)); 3], usize) {
+    let mut moves: [(u8, (u8, u8)); 3] = [(0, (0, 0)); 3];
+    let mut count = 0;
+    for turn in 0..4 {
+        if turn == facing ^ 1 {
+            continue;
+        }
+        match step(head, turn, grid.size, grid.borders) {
+            Some(cell) if grid.free(cell) => {
+                moves[count] = (turn, cell);
+                count += 1;
+            }
+            _ => {}
+        }
+    }
+    (moves, count)
+}
+
+// the turn with the most room behind it, counting up to `limit` cells, ties keep going straight
+// used by the tron riders, their trails are a bitboard just like the snakes
+pub fn roam(grid: &Grid, head: (u8, u8), facing: u8, limit: u16, rng: &mut Rng) -> u8 {
+    let (moves, count) = moves(grid, head, facing);
+    let mut best = (0, facing);
+    for (turn, cell) in &moves[..count] {
+        let room = grid.room(*cell, limit);
+        if room > best.0 || (room == best.0 && best.1 != facing && (*turn == facing || rng.below(2) == 0)) {
+            best = (room, *turn);
+        }
+    }
+    best.1
+}
+
+// picks the direction for the next tick, snakes can't turn back so `facing ^ 1` is never chosen
+pub fn think(grid: &Grid, head: (u8, u8), facing: u8, tail: (u8, u8), length: u16, level: u8, rng: &mut Rng) -> u8 {
+    let (moves, count) = moves(grid, head, facing);
+    if count == 0 {
+        // boxed in, nothing left to do
+        return facing;
+    }
+    let moves = &moves[..count];
+
+    if level <= EASY {
+        if rng.below(8) == 0 {
+            return moves[rng.below(count as u32) as usize].0;
+        }
+        // closest to the apple, ties are broken at random
+        let best = moves.iter().map(|(_, cell)| grid.distance(*cell, grid.apple)).min().unwrap_or(0);
+        let mut ties = moves.iter().filter(|(_, cell)| grid.distance(*cell, grid.apple) == best);
+        let pick = rng.below(ties.clone().count() as u32) as usize;
+        return ties.nth(pick).map(|(turn, _)| *turn).unwrap_or(facing);
+    }
+
+    let food = grid.path(head, |cell| grid.food(cell));
+    if level == NORMAL {
+        if let Some(turn) = food {
+            if moves.iter().any(|(m, _)| *m == turn) {
+                return turn;
+            }
+        }
+    } else if let Some(turn) = food {
+        // only take the apple when there is enough room behind it to fit the whole snake
+        let cell = moves.iter().find(|(m, _)| *m == turn).map(|(_, cell)| *cell);
+        if let Some(cell) = cell {
+            if grid.room(cell, length + 1) > length {
+                return turn;
+            }
+        }
+        // otherwise follow the tail, the space it leaves behind always leads somewhere
+        let behind = |cell| (0..4).any(|turn| step(cell, turn, grid.size, grid.borders) == Some(tail));
+        if let Some(turn) = grid.path(head, behind) {
+            if moves.iter().any(|(m, _)| *m == turn) {
+                return turn;
+            }
+        }
+    }
+
+    // no apple in reach, go where there is the most room
+    let limit = length.max(16) * 2;
+    let mut best = (0, moves[0].0);
+    for (turn, cell) in moves {
+        let room = grid.room(*cell, limit);
+        if room > best.0 || (room == best.0 && rng.below(2) == 0) {
+            best = (room, *turn);
+        }
+    }
+    best.1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPEN: [u32; 32] = [0; 32];
+
+    fn set(rows: &mut [u32], cell: (u8, u8)) {
+        rows[cell.1 as usize] |= 1 << cell.0;
+    }
+
+    // plays one snake on an open wrapping board and records every turn it takes
+    fn play(seed: u32, level: u8) -> [u8; 96] {
+        let mut rng = Rng::new(seed);
+        let mut frame = [0u32; 32];
+        let mut body: Deque<(u8, u8), 64> = Deque::new();
+        for x in 0..4 {
+            set(&mut frame, (x, 8));
+            match body.push_front((x, 8)) { Ok(_) => {} Err(_) => {} }
+        }
+        let mut facing = 3;
+        let mut apple = (12, 3);
+        let mut turns = [0u8; 96];
+        for turn in turns.iter_mut() {
+            let head = *body.front().unwrap();
+            let tail = *body.back().unwrap();
+            let grid = Grid { frame: &frame, walls: &OPEN, apples: &OPEN, apple, size: 16, borders: false };
+            let next = think(&grid, head, facing, tail, body.len() as u16, level, &mut rng);
+            assert_ne!(next, facing ^ 1);
+            facing = next;
+            *turn = facing;
+            let cell = step(head, facing, 16, false).unwrap();
+            if cell == apple {
+                apple = ((apple.0 * 7 + 3) % 16, (apple.1 * 5 + 1) % 16);
+            } else {
+                let (x, y) = body.pop_back().unwrap();
+                frame[y as usize] &= !(1 << x);
+            }
+            if bit(&frame, cell) || body.is_full() {
+                break;
+            }
+            set(&mut frame, cell);
+            match body.push_front(cell) { Ok(_) => {} Err(_) => {} }
+        }
+        turns
+    }
+
+    #[test]
+    fn same_seed_same_moves() {
+        for level in [EASY, NORMAL, HARD] {
+            for seed in [1, 7, 0xdead_beef] {
+                assert_eq!(play(seed, level), play(seed, level));
+            }
+        }
+    }
+
+    #[test]
+    fn normal_takes_the_shortest_path() {
+        // a wall down column 3 with a gap at the top, heading straight for the apple runs into it
+        let mut walls = [0u32; 32];
+        for y in 1..8 {
+            set(&mut walls, (3, y));
+        }
+        let apple = (6, 4);
+        let mut rng = Rng::new(1);
+        let (mut head, mut facing) = ((1, 4), 3);
+        let mut steps = 0;
+        while head != apple && steps < 64 {
+            let mut frame = [0u32; 32];
+            set(&mut frame, head);
+            let grid = Grid { frame: &frame, walls: &walls, apples: &OPEN, apple, size: 8, borders: true };
+            facing = think(&grid, head, facing, head, 1, NORMAL, &mut rng);
+            head = step(head, facing, 8, true).unwrap();
+            steps += 1;
+        }
+        // 2 across, 4 up to the gap, 3 across, 4 back down
+        assert_eq!(steps, 13);
+    }
+
+    #[test]
+    fn hard_skips_a_pocket_too_small_for_the_snake() {
+        // the apple sits at the end of a 5 cell pocket, the head is its only way in
+        let mut walls = [0u32; 32];
+        for x in 0..4 {
+            set(&mut walls, (x, 1));
+        }
+        set(&mut walls, (5, 0));
+        let mut frame = [0u32; 32];
+        for y in 1..8 {
+            set(&mut frame, (4, y));
+        }
+        set(&mut frame, (5, 7));
+        let grid = Grid { frame: &frame, walls: &walls, apples: &OPEN, apple: (0, 0), size: 8, borders: true };
+        let (head, tail) = ((4, 1), (5, 7));
+
+        // normal dives in for the apple
+        assert_eq!(think(&grid, head, 0, tail, 8, NORMAL, &mut Rng::new(1)), 0);
+        // hard turns right and heads back down towards its tail
+        for seed in 1..32 {
+            assert_eq!(think(&grid, head, 0, tail, 8, HARD, &mut Rng::new(seed)), 3);
+        }
+        // a short snake fits in the pocket, so hard takes the apple after all
+        assert_eq!(think(&grid, head, 0, tail, 4, HARD, &mut Rng::new(1)), 0);
+    }
+
+    #[test]
+    fn never_turns_back() {
+        let apples = [(0, 0), (8, 8), (15, 8), (8, 15), (0, 8)];
+        for level in [EASY, NORMAL, HARD] {
+            for seed in 1..64 {
+                let mut rng = Rng::new(seed);
+                for facing in 0..4 {
+                    for apple in apples {
+                        let mut frame = [0u32; 32];
+                        set(&mut frame, (8, 8));
+                        let grid = Grid { frame: &frame, walls: &OPEN, apples: &OPEN, apple, size: 16, borders: false };
+                        assert_ne!(think(&grid, (8, 8), facing, (8, 8), 1, level, &mut rng), facing ^ 1);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn boxed_in_keeps_going_rather_than_turning_back() {
+        // the only free cell is behind the head, the snake can't go there
+        let mut walls = [0u32; 32];
+        set(&mut walls, (4, 3));
+        set(&mut walls, (3, 4));
+        set(&mut walls, (5, 4));
+        let mut frame = [0u32; 32];
+        set(&mut frame, (4, 4));
+        let grid = Grid { frame: &frame, walls: &walls, apples: &OPEN, apple: (4, 6), size: 8, borders: true };
+        for level in [EASY, NORMAL, HARD] {
+            assert_eq!(think(&grid, (4, 4), 0, (4, 4), 1, level, &mut Rng::new(3)), 0);
+        }
+    }
+}
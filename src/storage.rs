@@ -0,0 +1,75 @@
+use core::cell::RefCell;
+
+use embassy_rp::{
+    flash::{Blocking, Flash, ERASE_SIZE}, peripherals::FLASH
+};
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+
+use {defmt_rtt as _, panic_probe as _};
+use defmt::*;
+
+// the Pico 2W has 4M of flash, memory.x only hands the first 2M to the program
+const FLASH_SIZE: usize = 4 * 1024 * 1024;
+// saved data lives in the last 256K, far away from the program and the cyw43 firmware
+const STORAGE_OFFSET: u32 = 0x3C0000;
+pub const SLOT_SIZE: usize = ERASE_SIZE;
+
+// every slot is one erase sector
+pub const SOKOBAN_LEVELS: u8 = 0;
+pub const SOKOBAN_LEVEL_SLOTS: u8 = 4;
+// best run of every built-in level, followed by the custom slots
+pub const SOKOBAN_REPLAYS: u8 = 4;
+pub const SOKOBAN_REPLAY_SLOTS: u8 = 17;
+// best minesweeper time of every board size
+pub const MINESWEEPER_TIMES: u8 = 21;
+
+static FLASH_STORAGE: Mutex<CriticalSectionRawMutex, RefCell<Option<Flash<'static, FLASH, Blocking, FLASH_SIZE>>>> = Mutex::new(RefCell::new(None));
+
+pub fn init(flash: FLASH) {
+    FLASH_STORAGE.lock(|storage| {
+        storage.replace(Some(Flash::<_, Blocking, FLASH_SIZE>::new_blocking(flash)));
+    });
+}
+
+pub fn read(slot: u8, data: &mut [u8]) -> bool {
+    if data.len() > SLOT_SIZE {
+        return false;
+    }
+    FLASH_STORAGE.lock(|storage| {
+        match storage.borrow_mut().as_mut() {
+            Some(flash) => match flash.blocking_read(STORAGE_OFFSET + slot as u32 * SLOT_SIZE as u32, data) {
+                Ok(_) => true,
+                Err(_) => {
+                    warn!("failed to read slot {}", slot);
+                    false
+                }
+            },
+            None => false,
+        }
+    })
+}
+
+pub fn write(slot: u8, data: &[u8]) -> bool {
+    if data.len() > SLOT_SIZE {
+        return false;
+    }
+    let offset = STORAGE_OFFSET + slot as u32 * SLOT_SIZE as u32;
+    FLASH_STORAGE.lock(|storage| {
+        match storage.borrow_mut().as_mut() {
+            Some(flash) => {
+                if flash.blocking_erase(offset, offset + SLOT_SIZE as u32).is_err() {
+                    warn!("failed to erase slot {}", slot);
+                    return false;
+                }
+                match flash.blocking_write(offset, data) {
+                    Ok(_) => true,
+                    Err(_) => {
+                        warn!("failed to write slot {}", slot);
+                        false
+                    }
+                }
+            }
+            None => false,
+        }
+    })
+}